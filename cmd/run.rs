@@ -1,5 +1,5 @@
 use engine::{
-    geometry::{Grid, Triangle},
+    geometry::{Grid, IcoSphere, Plane, Triangle, UvSphere},
     *,
 };
 use gpu::{
@@ -14,25 +14,95 @@ fn main() {
         .with_title("wgpu Test Scene")
         .build(&event_loop)
         .unwrap();
-    let mut engine = SchwarzEngine::new(0.005, window);
+    let mut engine = SchwarzEngine::new(0.005, window).expect("renderer init error");
 
     let triangle = Triangle::new(Mat4::IDENTITY);
-    let grid = Grid::new(100, 0.5, 0.01, Mat4::IDENTITY);
+    let grid = Grid::new(100, 0.5, 1.0, Mat4::IDENTITY).with_opacity(0.5);
 
     let obj = utilities::obj_import::load_obj("resources/mercedes_ponos.obj", Mat4::IDENTITY)
         .expect("obj load error");
 
+    // земля чуть ниже сетки, чтобы на ней были видны тени
+    let ground = Plane::new(
+        30.0,
+        30.0,
+        1,
+        1,
+        Mat4::from_translation(Vec3::new(0.0, -0.01, 0.0)),
+    );
+
+    // небо на фоне и рассеянный свет от него
+    engine.scene_mut().set_environment(
+        Environment::gradient([0.25, 0.45, 0.9], [0.75, 0.8, 0.85], [0.3, 0.28, 0.25])
+            .with_intensity(0.5),
+    );
+
+    engine.add_object_to_scene(ground);
     engine.add_object_to_scene(grid);
     engine.add_object_to_scene(triangle);
 
+    // стеклянная сфера: рисуется после непрозрачных, F11 переключает сортировку и WBOIT
+    let glass = UvSphere::new(
+        0.6,
+        32,
+        16,
+        Mat4::from_translation(Vec3::new(1.5, 0.6, 0.0)),
+    );
+    engine.add_object_to_scene(glass.to_object3d().with_opacity(0.35));
+
+    // кольцо астероидов: один меш, один draw call
+    let rock = IcoSphere::new(0.05, 1, Mat4::IDENTITY);
+    let asteroids = (0..2000)
+        .map(|i| {
+            let angle = i as f32 * 0.618_034 * std::f32::consts::TAU;
+            let radius = 3.0 + (i % 7) as f32 * 0.1;
+            let position = Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius);
+            Instance::new(Mat4::from_translation(position), [0.6, 0.55, 0.5])
+        })
+        .collect();
+    engine.add_instanced_to_scene(
+        InstancedObject::new(rock.mesh().clone(), Mat4::IDENTITY).with_instances(asteroids),
+    );
+
     println!("Meshes len: {}", obj.len());
 
     for mut mesh in obj {
         mesh.translate(Vec3::new(10f32, 0f32, 10f32));
         mesh.scale(Vec3::new(0.33, 0.33, 0.33));
-        engine.add_object_to_scene(mesh);
+        // вдали машина рисуется упрощёнными мешами
+        engine.add_object_to_scene(mesh.with_generated_lods(3, 0.5));
     }
 
+    // спутник на круговой орбите со следом
+    let planet = engine.add_object_to_scene(UvSphere::new(0.1, 16, 8, Mat4::IDENTITY));
+    engine.scene_mut().add_trail(
+        Trail::new(256)
+            .following(planet)
+            .with_max_age(4.0)
+            .with_color([0.3, 0.7, 1.0])
+            .with_style(TrailStyle::Ribbon { width: 0.05 }),
+    );
+
+    // лёгкое затемнение краёв кадра
+    engine
+        .renderer_mut()
+        .add_post_effect(
+            PostEffect::new("Vignette", VIGNETTE_EFFECT_SHADER).with_params(&[0.4, 0.3]),
+        )
+        .expect("post effect error");
+
+    let mut time = 0.0f32;
+    engine.on_update(move |ctx| {
+        time += ctx.delta_time;
+        let position = Vec3::new(time.cos() * 2.0, 0.5, time.sin() * 2.0);
+        ctx.scene.objects_mut()[planet].set_model_matrix(Mat4::from_translation(position));
+
+        // оси мира и подпись в начале координат
+        ctx.debug.axes(Mat4::IDENTITY, 1.0);
+        ctx.debug
+            .text3d(Vec3::new(0.1, 0.1, 0.0), "origin", 0.1, [1.0, 1.0, 0.0]);
+    });
+
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Wait;
 
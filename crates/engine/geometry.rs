@@ -1,37 +1,52 @@
-use gpu::{Mat4, Object, Object3D, Vertex};
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    sync::Arc,
+};
 
-pub struct Triangle {
-    vertices: Vec<Vertex>,
-    indices: Vec<u32>,
-    model_matrix: Mat4,
-}
+use gpu::{Mat4, Mesh, Object, Quat, Topology, Vec2, Vec3, Vertex};
 
-impl Object for Triangle {
-    fn vertices(&self) -> &[Vertex] {
-        &self.vertices
-    }
-    fn indices(&self) -> &[u32] {
-        &self.indices
-    }
-    fn model_matrix(&self) -> Mat4 {
-        self.model_matrix
-    }
+/// Реализация `Object` для типов геометрии с полями `mesh` и `model_matrix`
+macro_rules! impl_object {
+    ($($name:ident),* $(,)?) => {$(
+        impl Object for $name {
+            fn mesh(&self) -> &Arc<Mesh> {
+                &self.mesh
+            }
 
-    fn to_object3d(self) -> Object3D {
-        Object3D::new(
-            self.vertices.clone(),
-            self.indices.clone(),
-            self.model_matrix,
-        )
-    }
+            fn model_matrix(&self) -> Mat4 {
+                self.model_matrix
+            }
 
-    fn translate(&mut self, offset: glam::Vec3) {
-        self.model_matrix = Mat4::from_translation(offset) * self.model_matrix;
-    }
+            fn set_model_matrix(&mut self, model_matrix: Mat4) {
+                self.model_matrix = model_matrix;
+            }
+        }
 
-    fn scale(&mut self, factor: glam::Vec3) {
-        self.model_matrix = Mat4::from_scale(factor) * self.model_matrix;
-    }
+        impl $name {
+            /// перекрасить все вершины (копирует меш, если он уже разделён)
+            pub fn with_color(mut self, color: [f32; 3]) -> Self {
+                Arc::make_mut(&mut self.mesh).set_color(color);
+                self
+            }
+
+            fn from_mesh(mesh: Mesh, model_matrix: Mat4) -> Self {
+                Self {
+                    mesh: Arc::new(mesh),
+                    model_matrix,
+                }
+            }
+        }
+    )*};
+}
+
+impl_object!(
+    Triangle, UvSphere, IcoSphere, Cuboid, Cylinder, Cone, Torus, Capsule, Plane, Disk, Arrow,
+);
+
+pub struct Triangle {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
 }
 
 impl Triangle {
@@ -46,145 +61,565 @@ impl Triangle {
         let normal = (b - a).cross(c - a).normalize().to_array();
 
         // собираем вершины
-        let vertices = vec![
+        let mut vertices = vec![
             Vertex {
                 position: positions[0],
                 normal,
                 color: [1.0, 0.0, 0.0],
+                uv: [0.0, 1.0],
+                ..Default::default()
             },
             Vertex {
                 position: positions[1],
                 normal,
                 color: [0.0, 1.0, 0.0],
+                uv: [1.0, 1.0],
+                ..Default::default()
             },
             Vertex {
                 position: positions[2],
                 normal,
                 color: [0.0, 0.0, 1.0],
+                uv: [0.5, 0.0],
+                ..Default::default()
             },
         ];
 
         let indices = vec![0, 1, 2];
+        Vertex::compute_tangents(&mut vertices, &indices);
 
-        Self {
-            vertices,
-            indices,
-            model_matrix,
-        }
+        Self::from_mesh(Mesh::new(vertices, indices), model_matrix)
     }
 }
 
+/// Сетка в плоскости XZ из линий (LineList)
 pub struct Grid {
-    vertices: Vec<Vertex>,
-    indices: Vec<u32>,
+    mesh: Arc<Mesh>,
     model_matrix: Mat4,
+    line_width: f32,
+    opacity: f32,
 }
 
 impl Object for Grid {
-    fn vertices(&self) -> &[Vertex] {
-        &self.vertices
-    }
-    fn indices(&self) -> &[u32] {
-        &self.indices
+    fn mesh(&self) -> &Arc<Mesh> {
+        &self.mesh
     }
+
     fn model_matrix(&self) -> Mat4 {
         self.model_matrix
     }
 
-    fn to_object3d(self) -> Object3D {
-        Object3D::new(
-            self.vertices.clone(),
-            self.indices.clone(),
-            self.model_matrix,
-        )
+    fn set_model_matrix(&mut self, model_matrix: Mat4) {
+        self.model_matrix = model_matrix;
     }
 
-    fn translate(&mut self, offset: glam::Vec3) {
-        self.model_matrix = Mat4::from_translation(offset) * self.model_matrix;
+    fn line_width(&self) -> f32 {
+        self.line_width
     }
 
-    fn scale(&mut self, factor: glam::Vec3) {
-        self.model_matrix = Mat4::from_scale(factor) * self.model_matrix;
+    fn opacity(&self) -> f32 {
+        self.opacity
     }
 }
 
 impl Grid {
+    /// `size` x `size` ячеек с шагом `step`; `line_width` — толщина линий в пикселях
     pub fn new(size: usize, step: f32, line_width: f32, model_matrix: Mat4) -> Self {
         let half = size as f32 * step / 2.0;
-        let mut vertices = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-
-        let mut add_line = |start: [f32; 3], end: [f32; 3], perp_axis: usize, color: [f32; 3]| {
-            let base_idx = vertices.len() as u32;
-            let half_width = line_width / 2.0;
-
-            // четыре точки линии
-            let mut v0 = start;
-            v0[perp_axis] -= half_width;
-            let mut v1 = end;
-            v1[perp_axis] -= half_width;
-            let mut v2 = end;
-            v2[perp_axis] += half_width;
-            let mut v3 = start;
-            v3[perp_axis] += half_width;
-
-            let normal_front = [0.0, 1.0, 0.0];
-            let normal_back = [0.0, -1.0, 0.0];
-
-            // front
-            for pos in &[v0, v1, v2, v3] {
-                vertices.push(Vertex {
-                    position: *pos,
-                    normal: normal_front,
-                    color,
-                });
-            }
-            // back
-            for pos in &[v0, v1, v2, v3] {
+        let mut vertices = Vec::with_capacity((size + 1) * 4);
+        let mut indices: Vec<u32> = Vec::with_capacity((size + 1) * 4);
+
+        let mut add_line = |start: [f32; 3], end: [f32; 3], color: [f32; 3]| {
+            for position in [start, end] {
+                indices.push(vertices.len() as u32);
                 vertices.push(Vertex {
-                    position: *pos,
-                    normal: normal_back,
+                    position,
+                    normal: [0.0, 1.0, 0.0],
                     color,
+                    ..Default::default()
                 });
             }
-
-            // индексы для front (0..3)
-            indices.extend_from_slice(&[
-                base_idx,
-                base_idx + 1,
-                base_idx + 2,
-                base_idx,
-                base_idx + 2,
-                base_idx + 3,
-            ]);
-
-            // индексы для back (4..7)
-            let b = base_idx + 4;
-            indices.extend_from_slice(&[b, b + 2, b + 1, b, b + 3, b + 2]);
         };
 
         // линии X
         for i in 0..=size {
             let z = -half + i as f32 * step;
-            add_line([-half, 0.0, z], [half, 0.0, z], 2, [1.0, 1.0, 1.0]);
+            add_line([-half, 0.0, z], [half, 0.0, z], [1.0, 1.0, 1.0]);
         }
 
         // линии Z
         for i in 0..=size {
             let x = -half + i as f32 * step;
-            add_line([x, 0.0, -half], [x, 0.0, half], 0, [1.0, 1.0, 1.0]);
+            add_line([x, 0.0, -half], [x, 0.0, half], [1.0, 1.0, 1.0]);
         }
 
-        println!(
-            "Grid created: {} vertices, {} indices",
-            vertices.len(),
-            indices.len()
-        );
-
         Self {
-            vertices,
-            indices,
+            mesh: Arc::new(Mesh::new(vertices, indices).with_topology(Topology::LineList)),
             model_matrix,
+            line_width,
+            opacity: 1.0,
         }
     }
+
+    /// перекрасить все линии
+    pub fn with_color(mut self, color: [f32; 3]) -> Self {
+        Arc::make_mut(&mut self.mesh).set_color(color);
+        self
+    }
+
+    /// полупрозрачная сетка не заслоняет объекты под ней
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
+/// цвет примитивов по умолчанию
+const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+
+/// Точка профиля тела вращения: радиус, высота, нормаль в плоскости (r, y) и координата v
+#[derive(Copy, Clone)]
+struct ProfilePoint {
+    r: f32,
+    y: f32,
+    normal: Vec2,
+    v: f32,
+}
+
+impl ProfilePoint {
+    fn new(r: f32, y: f32, normal: Vec2, v: f32) -> Self {
+        Self {
+            r,
+            y,
+            normal: normal.normalize(),
+            v,
+        }
+    }
+}
+
+/// направление в плоскости XZ для угла `phi`; рост угла — против часовой стрелки, если смотреть с +Y
+fn ring(phi: f32) -> Vec3 {
+    Vec3::new(phi.cos(), 0.0, -phi.sin())
+}
+
+/// Тело вращения вокруг оси Y.
+/// Каждая секция — непрерывный профиль; между секциями нормали не сглаживаются (острые рёбра).
+/// Профиль должен идти так, чтобы нормаль смотрела влево от направления обхода в плоскости (r, y):
+/// сверху вниз по боковой поверхности, от центра к краю на верхней крышке, от края к центру на нижней.
+fn revolve(sections: &[Vec<ProfilePoint>], segments: u32) -> Mesh {
+    let segments = segments.max(3);
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for section in sections {
+        let base = vertices.len() as u32;
+        for point in section {
+            for j in 0..=segments {
+                let u = j as f32 / segments as f32;
+                let dir = ring(u * TAU);
+                vertices.push(Vertex {
+                    position: (dir * point.r + Vec3::Y * point.y).to_array(),
+                    normal: (dir * point.normal.x + Vec3::Y * point.normal.y).to_array(),
+                    color: WHITE,
+                    uv: [u, point.v],
+                    ..Default::default()
+                });
+            }
+        }
+        grid_indices(&mut indices, base, section.len() as u32 - 1, segments);
+    }
+
+    Vertex::compute_tangents(&mut vertices, &indices);
+    Mesh::new(vertices, indices)
+}
+
+/// Индексы прямоугольной сетки `rows` x `cols` квадов, вершины построчно.
+/// Обход против часовой стрелки, если векторное произведение (шаг по строкам) x (шаг по столбцам) смотрит наружу.
+fn grid_indices(indices: &mut Vec<u32>, base: u32, rows: u32, cols: u32) {
+    let stride = cols + 1;
+    for i in 0..rows {
+        for j in 0..cols {
+            let a = base + i * stride + j;
+            let b = a + 1;
+            let c = a + stride;
+            let d = c + 1;
+            indices.extend_from_slice(&[a, c, b, b, c, d]);
+        }
+    }
+}
+
+/// профиль полуокружности/дуги сферы радиуса `radius` с центром на высоте `center_y`,
+/// полярный угол от `from` до `to` (0 — верхний полюс)
+fn arc(radius: f32, center_y: f32, from: f32, to: f32, steps: u32) -> Vec<ProfilePoint> {
+    (0..=steps)
+        .map(|k| {
+            let t = k as f32 / steps as f32;
+            let theta = from + (to - from) * t;
+            let (sin, cos) = theta.sin_cos();
+            ProfilePoint::new(
+                radius * sin,
+                center_y + radius * cos,
+                Vec2::new(sin, cos),
+                t,
+            )
+        })
+        .collect()
+}
+
+/// прямой отрезок профиля; нормаль перпендикулярна ему (влево от направления обхода)
+fn segment(from: Vec2, to: Vec2) -> Vec<ProfilePoint> {
+    let d = to - from;
+    let normal = Vec2::new(-d.y, d.x);
+    vec![
+        ProfilePoint::new(from.x, from.y, normal, 0.0),
+        ProfilePoint::new(to.x, to.y, normal, 1.0),
+    ]
+}
+
+/// Сфера с разбиением по долготе (`segments`) и широте (`rings`)
+pub struct UvSphere {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl UvSphere {
+    pub fn new(radius: f32, segments: u32, rings: u32, model_matrix: Mat4) -> Self {
+        let profile = arc(radius, 0.0, 0.0, PI, rings.max(2));
+        Self::from_mesh(revolve(&[profile], segments), model_matrix)
+    }
+}
+
+/// Икосфера: икосаэдр, каждая грань которого `subdivisions` раз делится на четыре
+pub struct IcoSphere {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl IcoSphere {
+    pub fn new(radius: f32, subdivisions: u32, model_matrix: Mat4) -> Self {
+        let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+        let mut positions: Vec<Vec3> = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .map(|p| Vec3::from(p).normalize())
+        .to_vec();
+        let mut faces: Vec<[u32; 3]> = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            // середина ребра общая для двух соседних граней
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| -> u32 {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let p = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(p);
+                    positions.len() as u32 - 1
+                })
+            };
+            faces = faces
+                .iter()
+                .flat_map(|&[a, b, c]| {
+                    let ab = midpoint(a, b);
+                    let bc = midpoint(b, c);
+                    let ca = midpoint(c, a);
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        // сферическая развёртка; на шве у -X текстура сжимается в одну грань
+        let mut vertices: Vec<Vertex> = positions
+            .iter()
+            .map(|&n| Vertex {
+                position: (n * radius).to_array(),
+                normal: n.to_array(),
+                color: WHITE,
+                uv: [
+                    0.5 + (-n.z).atan2(n.x) / TAU,
+                    n.y.clamp(-1.0, 1.0).acos() / PI,
+                ],
+                ..Default::default()
+            })
+            .collect();
+        let indices: Vec<u32> = faces.into_iter().flatten().collect();
+        Vertex::compute_tangents(&mut vertices, &indices);
+
+        Self::from_mesh(Mesh::new(vertices, indices), model_matrix)
+    }
+}
+
+/// Прямоугольный параллелепипед с центром в начале координат
+pub struct Cuboid {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Cuboid {
+    pub fn new(size: Vec3, model_matrix: Mat4) -> Self {
+        let half = size / 2.0;
+        let mut vertices = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+
+        // нормаль грани и две оси в её плоскости, u x v = нормаль
+        let faces = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        ];
+        for (normal, u, v) in faces {
+            let base = vertices.len() as u32;
+            let center = normal * half;
+            let (u_half, v_half) = (u * half, v * half);
+            let corners = [
+                (center - u_half - v_half, [0.0, 1.0]),
+                (center + u_half - v_half, [1.0, 1.0]),
+                (center + u_half + v_half, [1.0, 0.0]),
+                (center - u_half + v_half, [0.0, 0.0]),
+            ];
+            for (position, uv) in corners {
+                vertices.push(Vertex {
+                    position: position.to_array(),
+                    normal: normal.to_array(),
+                    color: WHITE,
+                    uv,
+                    ..Default::default()
+                });
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        Vertex::compute_tangents(&mut vertices, &indices);
+
+        Self::from_mesh(Mesh::new(vertices, indices), model_matrix)
+    }
+
+    /// куб с ребром `size`
+    pub fn cube(size: f32, model_matrix: Mat4) -> Self {
+        Self::new(Vec3::splat(size), model_matrix)
+    }
+}
+
+/// Цилиндр вдоль оси Y с крышками, центр в начале координат
+pub struct Cylinder {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Cylinder {
+    pub fn new(radius: f32, height: f32, segments: u32, model_matrix: Mat4) -> Self {
+        let (top, bottom) = (height / 2.0, -height / 2.0);
+        let sections = [
+            segment(Vec2::new(0.0, top), Vec2::new(radius, top)),
+            segment(Vec2::new(radius, top), Vec2::new(radius, bottom)),
+            segment(Vec2::new(radius, bottom), Vec2::new(0.0, bottom)),
+        ];
+        Self::from_mesh(revolve(&sections, segments), model_matrix)
+    }
+}
+
+/// Конус вдоль оси Y: основание на y = 0, вершина на y = `height`
+pub struct Cone {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Cone {
+    pub fn new(radius: f32, height: f32, segments: u32, model_matrix: Mat4) -> Self {
+        let sections = [
+            segment(Vec2::new(0.0, height), Vec2::new(radius, 0.0)),
+            segment(Vec2::new(radius, 0.0), Vec2::ZERO),
+        ];
+        Self::from_mesh(revolve(&sections, segments), model_matrix)
+    }
+}
+
+/// Тор в плоскости XZ: `radius` — до центра трубки, `tube_radius` — радиус трубки
+pub struct Torus {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Torus {
+    pub fn new(
+        radius: f32,
+        tube_radius: f32,
+        segments: u32,
+        tube_segments: u32,
+        model_matrix: Mat4,
+    ) -> Self {
+        let steps = tube_segments.max(3);
+        // обход сечения трубки: верх -> внешняя сторона -> низ -> внутренняя сторона
+        let profile = (0..=steps)
+            .map(|k| {
+                let t = k as f32 / steps as f32;
+                let alpha = FRAC_PI_2 - t * TAU;
+                let normal = Vec2::new(alpha.cos(), alpha.sin());
+                let point = Vec2::new(radius, 0.0) + normal * tube_radius;
+                ProfilePoint::new(point.x, point.y, normal, t)
+            })
+            .collect();
+        Self::from_mesh(revolve(&[profile], segments), model_matrix)
+    }
+}
+
+/// Капсула вдоль оси Y: цилиндр высотой `height` с полусферами на концах
+pub struct Capsule {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Capsule {
+    pub fn new(radius: f32, height: f32, segments: u32, rings: u32, model_matrix: Mat4) -> Self {
+        let rings = rings.max(1);
+        let half = height / 2.0;
+        let total = height + PI * radius;
+        // одна секция: нормали на стыке полусфер и цилиндра совпадают
+        let mut profile = arc(radius, half, 0.0, FRAC_PI_2, rings);
+        profile.extend(arc(radius, -half, FRAC_PI_2, PI, rings));
+        for (i, point) in profile.iter_mut().enumerate() {
+            let arc_length = if i <= rings as usize {
+                point.v * FRAC_PI_2 * radius
+            } else {
+                FRAC_PI_2 * radius + height + point.v * FRAC_PI_2 * radius
+            };
+            point.v = if total > 0.0 { arc_length / total } else { 0.0 };
+        }
+        Self::from_mesh(revolve(&[profile], segments), model_matrix)
+    }
+}
+
+/// Плоскость в XZ с нормалью +Y, разбитая на `segments_x` x `segments_z` квадов
+pub struct Plane {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Plane {
+    pub fn new(
+        width: f32,
+        depth: f32,
+        segments_x: u32,
+        segments_z: u32,
+        model_matrix: Mat4,
+    ) -> Self {
+        let (cols, rows) = (segments_x.max(1), segments_z.max(1));
+        let mut vertices = Vec::with_capacity(((rows + 1) * (cols + 1)) as usize);
+        for i in 0..=rows {
+            for j in 0..=cols {
+                let (u, v) = (j as f32 / cols as f32, i as f32 / rows as f32);
+                vertices.push(Vertex {
+                    position: [(u - 0.5) * width, 0.0, (v - 0.5) * depth],
+                    normal: [0.0, 1.0, 0.0],
+                    color: WHITE,
+                    uv: [u, v],
+                    ..Default::default()
+                });
+            }
+        }
+        let mut indices = Vec::with_capacity((rows * cols * 6) as usize);
+        grid_indices(&mut indices, 0, rows, cols);
+        Vertex::compute_tangents(&mut vertices, &indices);
+
+        Self::from_mesh(Mesh::new(vertices, indices), model_matrix)
+    }
+}
+
+/// Двусторонний диск или кольцо (`inner_radius` > 0) в плоскости XZ, например аккреционный диск.
+/// Координата v растёт от внутреннего края к внешнему на обеих сторонах.
+pub struct Disk {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Disk {
+    pub fn new(inner_radius: f32, outer_radius: f32, segments: u32, model_matrix: Mat4) -> Self {
+        let point = |r: f32, normal: Vec2, v: f32| ProfilePoint::new(r, 0.0, normal, v);
+        let sections = [
+            vec![
+                point(inner_radius, Vec2::Y, 0.0),
+                point(outer_radius, Vec2::Y, 1.0),
+            ],
+            vec![
+                point(outer_radius, Vec2::NEG_Y, 1.0),
+                point(inner_radius, Vec2::NEG_Y, 0.0),
+            ],
+        ];
+        Self::from_mesh(revolve(&sections, segments), model_matrix)
+    }
+}
+
+/// Стрелка вдоль +Y от начала координат длиной `length` (включая наконечник)
+pub struct Arrow {
+    mesh: Arc<Mesh>,
+    model_matrix: Mat4,
+}
+
+impl Arrow {
+    pub fn new(
+        length: f32,
+        shaft_radius: f32,
+        head_length: f32,
+        head_radius: f32,
+        segments: u32,
+        model_matrix: Mat4,
+    ) -> Self {
+        let head_length = head_length.min(length);
+        let neck = length - head_length;
+        let sections = [
+            segment(Vec2::new(0.0, length), Vec2::new(head_radius, neck)),
+            segment(Vec2::new(head_radius, neck), Vec2::new(shaft_radius, neck)),
+            segment(Vec2::new(shaft_radius, neck), Vec2::new(shaft_radius, 0.0)),
+            segment(Vec2::new(shaft_radius, 0.0), Vec2::ZERO),
+        ];
+        Self::from_mesh(revolve(&sections, segments), model_matrix)
+    }
+
+    /// стрелка из `from` в `to`; пропорции наконечника — от длины
+    pub fn between(from: Vec3, to: Vec3, segments: u32) -> Self {
+        let direction = to - from;
+        let length = direction.length();
+        let rotation = Quat::from_rotation_arc(Vec3::Y, direction.normalize_or(Vec3::Y));
+        let model_matrix = Mat4::from_rotation_translation(rotation, from);
+        Self::new(
+            length,
+            length * 0.02,
+            length * 0.2,
+            length * 0.06,
+            segments,
+            model_matrix,
+        )
+    }
 }
@@ -4,7 +4,8 @@ mod movement;
 use std::time::Instant;
 
 use gpu::{
-    Camera, Object, Renderer, Scene, Vec3,
+    Camera, DebugDraw, Exposure, InstancedObject, Object, Renderer, RendererConfig, RendererError,
+    Scene, SceneHit, Shading, Vec3,
     event::{DeviceEvent, ElementState, Event, MouseButton, WindowEvent},
     event_loop::ControlFlow,
     window::Window,
@@ -13,6 +14,18 @@ use gpu::{
 use crate::movement::CameraMovement;
 pub use winit::*;
 
+/// То, что доступно коду обновления перед отрисовкой каждого кадра
+pub struct UpdateContext<'a> {
+    pub scene: &'a mut Scene,
+    /// отладочная графика этого кадра
+    pub debug: &'a mut DebugDraw,
+    pub camera: &'a Camera,
+    /// секунды с прошлого кадра
+    pub delta_time: f32,
+}
+
+type UpdateFn = Box<dyn FnMut(&mut UpdateContext)>;
+
 pub struct SchwarzEngine {
     scene: Scene,
     window: Window,
@@ -20,12 +33,29 @@ pub struct SchwarzEngine {
     camera_movement: CameraMovement,
     camera: Camera,
     last_frame_time: Instant,
+    update: Option<UpdateFn>,
+    // последняя позиция курсора в пикселях окна
+    cursor_position: (f32, f32),
+    // кадры подряд, завершившиеся ошибкой рендера
+    render_failures: u32,
 }
 
+/// сколько кадров подряд может не отрисоваться, прежде чем движок завершится
+const MAX_RENDER_FAILURES: u32 = 60;
+
 impl SchwarzEngine {
     #[allow(clippy::new_without_default)]
-    pub fn new(sensitivity: f32, window: Window) -> SchwarzEngine {
-        let renderer = gpu::block_on(Renderer::new(&window));
+    pub fn new(sensitivity: f32, window: Window) -> Result<SchwarzEngine, RendererError> {
+        Self::with_config(sensitivity, window, RendererConfig::default())
+    }
+
+    /// движок с настройками рендерера (MSAA, vsync, бэкенды, цвет фона)
+    pub fn with_config(
+        sensitivity: f32,
+        window: Window,
+        config: RendererConfig,
+    ) -> Result<SchwarzEngine, RendererError> {
+        let renderer = gpu::block_on(Renderer::with_config(&window, config))?;
 
         let scene = Scene::new();
 
@@ -39,18 +69,51 @@ impl SchwarzEngine {
             100.0,
         );
 
-        Self {
+        Ok(Self {
             scene,
             window,
             renderer,
             camera_movement: CameraMovement::new(sensitivity),
             last_frame_time: Instant::now(),
             camera,
-        }
+            update: None,
+            cursor_position: (0.0, 0.0),
+            render_failures: 0,
+        })
     }
 
-    pub fn add_object_to_scene(&mut self, obj: impl Object) {
-        self.scene.add_object(obj.to_object3d());
+    /// добавить объект; индекс — для `Trail::following` и `scene_mut().objects_mut()`
+    pub fn add_object_to_scene(&mut self, obj: impl Object) -> usize {
+        self.scene.add_object(obj.to_object3d())
+    }
+
+    /// добавить инстансированный объект; индекс — для `scene_mut().instanced_mut()`
+    pub fn add_instanced_to_scene(&mut self, obj: InstancedObject) -> usize {
+        self.scene.add_instanced(obj)
+    }
+
+    /// код, вызываемый каждый кадр перед отрисовкой (симуляция, отладочная графика)
+    pub fn on_update(&mut self, update: impl FnMut(&mut UpdateContext) + 'static) {
+        self.update = Some(Box::new(update));
+    }
+
+    /// сцена для обновления из кода симуляции между кадрами
+    pub fn scene_mut(&mut self) -> &mut Scene {
+        &mut self.scene
+    }
+
+    /// рендерер для настройки из кода приложения (например, стека постобработки)
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// объект сцены под точкой окна `(x, y)` в пикселях (CPU, по BVH сцены и мешей)
+    pub fn pick(&self, x: f32, y: f32) -> Option<SceneHit> {
+        let size = self.window.inner_size();
+        let ray = self
+            .camera
+            .ray_from_pixel(x, y, size.width as f32, size.height as f32);
+        self.scene.raycast(&ray)
     }
 
     pub fn render<T>(&mut self, event: Event<'_, T>, control_flow: &mut ControlFlow) {
@@ -61,15 +124,13 @@ impl SchwarzEngine {
             Event::DeviceEvent {
                 event: DeviceEvent::MouseMotion { delta },
                 ..
-            } => {
-                if self.camera_movement.mouse_captured {
-                    self.camera_movement.yaw += delta.0 as f32 * self.camera_movement.sensitivity;
-                    self.camera_movement.pitch -= delta.1 as f32 * self.camera_movement.sensitivity;
-                    self.camera_movement.pitch = self
-                        .camera_movement
-                        .pitch
-                        .clamp(-89f32.to_radians(), 89f32.to_radians());
-                }
+            } if self.camera_movement.mouse_captured => {
+                self.camera_movement.yaw += delta.0 as f32 * self.camera_movement.sensitivity;
+                self.camera_movement.pitch -= delta.1 as f32 * self.camera_movement.sensitivity;
+                self.camera_movement.pitch = self
+                    .camera_movement
+                    .pitch
+                    .clamp(-89f32.to_radians(), 89f32.to_radians());
             }
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
@@ -81,10 +142,25 @@ impl SchwarzEngine {
                         .resize(new_inner_size.width, new_inner_size.height);
                 }
                 // Обработка нажатия/отпускания кнопок мыши
-                WindowEvent::MouseInput { state, button, .. } => {
-                    if button == MouseButton::Left {
-                        self.camera_movement.mouse_captured = state == ElementState::Pressed;
-                    }
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    self.camera_movement.mouse_captured = state == ElementState::Pressed;
+                }
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.cursor_position = (position.x as f32, position.y as f32);
+                }
+                // правая кнопка выделяет объект под курсором
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed,
+                    button: MouseButton::Right,
+                    ..
+                } => {
+                    let (x, y) = self.cursor_position;
+                    let hit = self.pick(x, y);
+                    self.renderer.set_selection(hit.map(|hit| hit.object));
                 }
                 // Обработка нажатия/отпускания клавиш
                 WindowEvent::KeyboardInput { input, .. } => {
@@ -114,6 +190,73 @@ impl SchwarzEngine {
                                 self.camera_movement.moving_down =
                                     input.state == ElementState::Pressed;
                             }
+                            // Отладочные режимы: F1 каркас, F2 нормали, F3 глубина,
+                            // F4 цвет объектов, F5 обратные грани
+                            _ if input.state == ElementState::Pressed => {
+                                let view = self.renderer.debug_view_mut();
+                                match key {
+                                    event::VirtualKeyCode::F1 => view.wireframe = !view.wireframe,
+                                    event::VirtualKeyCode::F2 => view.normals = !view.normals,
+                                    event::VirtualKeyCode::F3 => {
+                                        view.toggle_shading(Shading::Depth)
+                                    }
+                                    event::VirtualKeyCode::F4 => {
+                                        view.toggle_shading(Shading::FlatColor)
+                                    }
+                                    event::VirtualKeyCode::F5 => {
+                                        view.toggle_shading(Shading::Backfaces)
+                                    }
+                                    // F6 следующий режим MSAA, F7 вертикальная синхронизация
+                                    event::VirtualKeyCode::F6 => {
+                                        let counts = self.renderer.supported_sample_counts();
+                                        let current = self.renderer.settings().sample_count;
+                                        let next = counts
+                                            .iter()
+                                            .copied()
+                                            .find(|&count| count > current)
+                                            .unwrap_or(counts[0]);
+                                        self.renderer.set_sample_count(next);
+                                    }
+                                    event::VirtualKeyCode::F7 => {
+                                        let vsync = self.renderer.settings().vsync();
+                                        self.renderer.set_vsync(!vsync);
+                                    }
+                                    // F8 следующий оператор тональной компрессии, F9 автоэкспозиция
+                                    event::VirtualKeyCode::F8 => {
+                                        let mut tone_mapping =
+                                            self.renderer.settings().tone_mapping;
+                                        tone_mapping.operator = tone_mapping.operator.next();
+                                        self.renderer.set_tone_mapping(tone_mapping);
+                                    }
+                                    event::VirtualKeyCode::F9 => {
+                                        let mut tone_mapping =
+                                            self.renderer.settings().tone_mapping;
+                                        tone_mapping.exposure = if tone_mapping.exposure.is_auto() {
+                                            Exposure::default()
+                                        } else {
+                                            Exposure::auto()
+                                        };
+                                        self.renderer.set_tone_mapping(tone_mapping);
+                                    }
+                                    // F10 bloom
+                                    event::VirtualKeyCode::F10 => {
+                                        let mut bloom = self.renderer.settings().bloom;
+                                        bloom.enabled = !bloom.enabled;
+                                        self.renderer.set_bloom(bloom);
+                                    }
+                                    // F11 сортировка или WBOIT для прозрачных объектов
+                                    event::VirtualKeyCode::F11 => {
+                                        let transparency = self.renderer.settings().transparency;
+                                        self.renderer.set_transparency(transparency.next());
+                                    }
+                                    // F12 отсечение по пирамиде камеры
+                                    event::VirtualKeyCode::F12 => {
+                                        let culling = self.renderer.settings().frustum_culling;
+                                        self.renderer.set_frustum_culling(!culling);
+                                    }
+                                    _ => {}
+                                }
+                            }
                             _ => {}
                         }
                     }
@@ -216,7 +359,29 @@ impl SchwarzEngine {
                 let target = self.camera.position + front;
                 self.camera.target = target;
 
-                self.renderer.render(&self.scene, &self.camera);
+                if let Some(update) = &mut self.update {
+                    update(&mut UpdateContext {
+                        scene: &mut self.scene,
+                        debug: self.renderer.debug_draw(),
+                        camera: &self.camera,
+                        delta_time,
+                    });
+                }
+                self.scene.update_trails(delta_time);
+
+                match self.renderer.render(&self.scene, &self.camera) {
+                    Ok(()) => self.render_failures = 0,
+                    // устройство уже пересоздано, следующий кадр рисуется как обычно
+                    Err(RendererError::DeviceLost) => log::warn!("GPU device lost, recreated"),
+                    // кадр пропущен; выходим, только если не рисуется ни один кадр подряд
+                    Err(e) => {
+                        log::error!("Render error: {e}");
+                        self.render_failures += 1;
+                        if self.render_failures >= MAX_RENDER_FAILURES {
+                            *control_flow = ControlFlow::Exit;
+                        }
+                    }
+                }
             }
             Event::MainEventsCleared => {
                 self.window.request_redraw();
@@ -5,7 +5,6 @@ pub(crate) struct CameraMovement {
 
     // Параметры движения камеры
     pub move_speed: f32,
-    pub acceleration_time: f32,
     pub acceleration: f32,
 
     // Текущие скорости по осям
@@ -55,7 +54,6 @@ impl CameraMovement {
             pitch,
             sensitivity,
             move_speed,
-            acceleration_time,
             acceleration,
             velocity_forward,
             velocity_right,
@@ -0,0 +1,51 @@
+use std::io::ErrorKind;
+
+use engine::geometry::{Grid, Plane};
+use gpu::{Mat4, Object, Topology, Vec3};
+use utilities::{
+    ply::{PlyFormat, read_ply, write_ply},
+    stl::{StlFormat, read_stl, write_stl},
+};
+
+#[test]
+fn plane_round_trips_through_stl_and_ply() {
+    let mut plane = Plane::new(2.0, 2.0, 4, 4, Mat4::IDENTITY);
+    plane.translate(Vec3::new(1.0, 2.0, 3.0));
+
+    let mut stl = Vec::new();
+    write_stl(&mut stl, &plane, StlFormat::Binary).unwrap();
+    let from_stl = read_stl(stl.as_slice(), Mat4::IDENTITY).unwrap();
+    assert_eq!(from_stl.indices().len(), plane.indices().len());
+
+    let mut ply = Vec::new();
+    write_ply(&mut ply, &plane, PlyFormat::BinaryLittleEndian).unwrap();
+    let from_ply = read_ply(ply.as_slice(), Mat4::IDENTITY).unwrap();
+    assert_eq!(from_ply.indices(), plane.indices());
+    for (a, b) in from_ply.vertices().iter().zip(plane.vertices()) {
+        let expected = Vec3::from(b.position) + Vec3::new(1.0, 2.0, 3.0);
+        assert!(Vec3::from(a.position).abs_diff_eq(expected, 1e-6));
+    }
+}
+
+#[test]
+fn grid_round_trips_through_ply() {
+    let mut grid = Grid::new(4, 0.5, 1.0, Mat4::IDENTITY);
+    grid.translate(Vec3::new(1.0, 2.0, 3.0));
+
+    // STL хранит только треугольники
+    let mut stl = Vec::new();
+    let error = write_stl(&mut stl, &grid, StlFormat::Binary).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+
+    for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian] {
+        let mut ply = Vec::new();
+        write_ply(&mut ply, &grid, format).unwrap();
+        let from_ply = read_ply(ply.as_slice(), Mat4::IDENTITY).unwrap();
+        assert_eq!(from_ply.topology(), Topology::LineList);
+        assert_eq!(from_ply.indices(), grid.indices());
+        for (a, b) in from_ply.vertices().iter().zip(grid.vertices()) {
+            let expected = Vec3::from(b.position) + Vec3::new(1.0, 2.0, 3.0);
+            assert!(Vec3::from(a.position).abs_diff_eq(expected, 1e-6));
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::marker::PhantomData;
+
+use engine::geometry::IcoSphere;
+use gpu::{Instance, InstancedObject, Mat4, Object, Scene, Vec3};
+
+// `Check::<T>::IS_OBJECT` берёт собственную константу, только если `T: Object`
+struct Check<T>(PhantomData<T>);
+
+trait NotObject {
+    const IS_OBJECT: bool = false;
+}
+
+impl<T> NotObject for Check<T> {}
+
+impl<T: Object> Check<T> {
+    const IS_OBJECT: bool = true;
+}
+
+#[test]
+fn instanced_objects_keep_their_instances() {
+    // `add_object_to_scene` принимает `Object` и сводит его к одному `Object3D`;
+    // группа экземпляров туда не пройдёт, только в `add_instanced_to_scene`
+    const {
+        assert!(Check::<IcoSphere>::IS_OBJECT);
+        assert!(!Check::<InstancedObject>::IS_OBJECT);
+    }
+
+    let sphere = IcoSphere::new(0.5, 1, Mat4::IDENTITY);
+    let instances: Vec<Instance> = (0..3)
+        .map(|i| {
+            let transform = Mat4::from_translation(Vec3::new(i as f32, 0.0, 0.0));
+            Instance::new(transform, [1.0, 0.5 * i as f32, 0.25])
+        })
+        .collect();
+    let mut scene = Scene::new();
+    let index = scene.add_instanced(
+        InstancedObject::new(sphere.mesh().clone(), Mat4::IDENTITY)
+            .with_instances(instances.clone()),
+    );
+
+    assert!(scene.objects().is_empty());
+    let stored = scene.instanced()[index].instances();
+    assert_eq!(stored.len(), instances.len());
+    for (a, b) in stored.iter().zip(&instances) {
+        assert_eq!(a.transform(), b.transform());
+        assert_eq!(a.color, b.color);
+    }
+}
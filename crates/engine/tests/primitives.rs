@@ -0,0 +1,141 @@
+use engine::geometry::{
+    Arrow, Capsule, Cone, Cuboid, Cylinder, Disk, IcoSphere, Plane, Torus, UvSphere,
+};
+use std::sync::Arc;
+
+use gpu::{Mat4, Object, Object3D, Vec3};
+
+/// Обход каждого невырожденного треугольника должен совпадать с нормалями его вершин
+fn assert_consistent(name: &str, object: &impl Object) {
+    let vertices = object.vertices();
+    let indices = object.indices();
+    assert!(!indices.is_empty(), "{name}: no triangles");
+    assert_eq!(indices.len() % 3, 0, "{name}: not a triangle list");
+
+    for v in vertices {
+        let length = Vec3::from(v.normal).length();
+        assert!(
+            (length - 1.0).abs() < 1e-4,
+            "{name}: normal length {length}"
+        );
+    }
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| {
+            let index = tri[i] as usize;
+            assert!(index < vertices.len(), "{name}: index out of range");
+            Vec3::from(vertices[index].position)
+        });
+        let face = (b - a).cross(c - a);
+        if face.length() < 1e-6 {
+            continue;
+        }
+        for &i in tri {
+            let normal = Vec3::from(vertices[i as usize].normal);
+            assert!(
+                face.normalize().dot(normal) > 0.0,
+                "{name}: triangle {tri:?} is wound against its normals"
+            );
+        }
+    }
+}
+
+/// Объём замкнутой поверхности положителен, если грани смотрят наружу
+fn signed_volume(object: &impl Object) -> f32 {
+    let vertices = object.vertices();
+    object
+        .indices()
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(vertices[tri[i] as usize].position));
+            a.dot(b.cross(c)) / 6.0
+        })
+        .sum()
+}
+
+#[test]
+fn primitives_are_wound_counter_clockwise() {
+    let m = Mat4::IDENTITY;
+    assert_consistent("uv sphere", &UvSphere::new(1.0, 16, 8, m));
+    assert_consistent("icosphere", &IcoSphere::new(1.0, 2, m));
+    assert_consistent("cuboid", &Cuboid::new(Vec3::new(1.0, 2.0, 3.0), m));
+    assert_consistent("cylinder", &Cylinder::new(0.5, 2.0, 12, m));
+    assert_consistent("cone", &Cone::new(0.5, 1.0, 12, m));
+    assert_consistent("torus", &Torus::new(1.0, 0.25, 16, 8, m));
+    assert_consistent("capsule", &Capsule::new(0.5, 1.0, 12, 4, m));
+    assert_consistent("plane", &Plane::new(2.0, 3.0, 4, 5, m));
+    assert_consistent("disk", &Disk::new(0.0, 1.0, 16, m));
+    assert_consistent("annulus", &Disk::new(0.5, 1.0, 16, m));
+    assert_consistent("arrow", &Arrow::new(1.0, 0.05, 0.2, 0.1, 8, m));
+}
+
+#[test]
+fn closed_primitives_have_expected_volume() {
+    let m = Mat4::IDENTITY;
+    let cases: [(&str, f32, f32); 6] = [
+        (
+            "uv sphere",
+            signed_volume(&UvSphere::new(1.0, 64, 32, m)),
+            4.0 / 3.0 * std::f32::consts::PI,
+        ),
+        (
+            "icosphere",
+            signed_volume(&IcoSphere::new(1.0, 4, m)),
+            4.0 / 3.0 * std::f32::consts::PI,
+        ),
+        (
+            "cuboid",
+            signed_volume(&Cuboid::new(Vec3::new(1.0, 2.0, 3.0), m)),
+            6.0,
+        ),
+        (
+            "cylinder",
+            signed_volume(&Cylinder::new(1.0, 2.0, 64, m)),
+            2.0 * std::f32::consts::PI,
+        ),
+        (
+            "cone",
+            signed_volume(&Cone::new(1.0, 3.0, 64, m)),
+            std::f32::consts::PI,
+        ),
+        (
+            "torus",
+            signed_volume(&Torus::new(2.0, 0.5, 64, 32, m)),
+            2.0 * std::f32::consts::PI.powi(2) * 2.0 * 0.25,
+        ),
+    ];
+    for (name, volume, expected) in cases {
+        assert!(
+            (volume - expected).abs() / expected < 0.02,
+            "{name}: volume {volume}, expected {expected}"
+        );
+    }
+}
+
+#[test]
+fn arrow_between_points_along_direction() {
+    let from = Vec3::new(1.0, 0.0, 0.0);
+    let to = Vec3::new(1.0, 0.0, 4.0);
+    let arrow = Arrow::between(from, to, 8);
+    let tip = arrow
+        .world_vertices()
+        .iter()
+        .map(|v| Vec3::from(v.position))
+        .max_by(|a, b| a.z.total_cmp(&b.z))
+        .unwrap();
+    assert!(tip.abs_diff_eq(to, 1e-4), "tip at {tip}");
+}
+
+#[test]
+fn objects_share_one_mesh() {
+    let rock = IcoSphere::new(1.0, 1, Mat4::IDENTITY);
+    let copy = Object3D::from_mesh(rock.mesh().clone(), Mat4::from_translation(Vec3::X));
+    assert!(Arc::ptr_eq(rock.mesh(), copy.mesh()));
+
+    let mesh = rock.mesh().clone();
+    let object = rock.to_object3d();
+    assert!(Arc::ptr_eq(&mesh, object.mesh()));
+    assert_eq!(
+        copy.world_vertices()[0].position[0],
+        mesh.vertices[0].position[0] + 1.0
+    );
+}
@@ -0,0 +1,390 @@
+use crate::{
+    shaders::{BLOOM_SHADER, FULLSCREEN_VERTEX_SHADER},
+    tonemap::HDR_FORMAT,
+};
+
+/// Больше уровней цепочки не строится: свечение и так охватывает почти весь экран
+pub const MAX_BLOOM_MIPS: u32 = 6;
+
+/// Настройки bloom
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BloomConfig {
+    pub enabled: bool,
+    /// яркость, с которой начинается свечение; 0 — светится всё (физически корректно)
+    pub threshold: f32,
+    /// ширина мягкого перехода у порога
+    pub knee: f32,
+    /// доля свечения в итоговом кадре, 0..1
+    pub intensity: f32,
+    /// радиус фильтра увеличения в долях ширины экрана
+    pub radius: f32,
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.0,
+            knee: 0.5,
+            intensity: 0.04,
+            radius: 0.005,
+        }
+    }
+}
+
+impl BloomConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_threshold(mut self, threshold: f32, knee: f32) -> Self {
+        self.threshold = threshold;
+        self.knee = knee;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// bloom выключен
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// Число уровней цепочки для кадра: последний уровень не меньше 2 пикселей
+pub fn mip_count(width: u32, height: u32) -> u32 {
+    let smallest = (width.min(height) / 2).max(1);
+    (smallest.ilog2()).clamp(1, MAX_BLOOM_MIPS)
+}
+
+/// Параметры шейдера (`BloomParams` в WGSL)
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    radius: f32,
+    scale: f32,
+}
+
+impl BloomParams {
+    fn as_byte_slice(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
+
+/// Проход bloom по HDR-кадру: уровни цепочки — мипы текстуры вдвое меньше кадра
+pub(crate) struct BloomPass {
+    prefilter: wgpu::RenderPipeline,
+    downsample: wgpu::RenderPipeline,
+    upsample: wgpu::RenderPipeline,
+    composite: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params: wgpu::Buffer,
+    // по виду на уровень цепочки
+    mips: Vec<wgpu::TextureView>,
+    // источник — HDR-кадр, затем по уровню цепочки
+    hdr_bind_group: wgpu::BindGroup,
+    mip_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl BloomPass {
+    pub fn new(device: &wgpu::Device, hdr: &wgpu::TextureView, size: (u32, u32)) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom BGL"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fullscreen Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(FULLSCREEN_VERTEX_SHADER.into()),
+        });
+        let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(BLOOM_SHADER.into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, entry_point, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &vs_module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &fs_module,
+                    entry_point,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: HDR_FORMAT,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+        let blend = |src_factor, dst_factor| {
+            let component = wgpu::BlendComponent {
+                src_factor,
+                dst_factor,
+                operation: wgpu::BlendOperation::Add,
+            };
+            Some(wgpu::BlendState {
+                color: component,
+                alpha: component,
+            })
+        };
+        let prefilter = pipeline("Bloom Prefilter Pipeline", "downsample_prefilter", None);
+        let downsample = pipeline("Bloom Downsample Pipeline", "downsample", None);
+        // уровни складываются
+        let upsample = pipeline(
+            "Bloom Upsample Pipeline",
+            "upsample",
+            blend(wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+        );
+        // кадр = кадр * (1 - intensity) + свечение * intensity
+        let composite = pipeline(
+            "Bloom Composite Pipeline",
+            "composite",
+            blend(
+                wgpu::BlendFactor::Constant,
+                wgpu::BlendFactor::OneMinusConstant,
+            ),
+        );
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bloom Params"),
+            size: std::mem::size_of::<BloomParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let (mips, hdr_bind_group, mip_bind_groups) =
+            Self::chain(device, &layout, &sampler, &params, hdr, size);
+        Self {
+            prefilter,
+            downsample,
+            upsample,
+            composite,
+            layout,
+            sampler,
+            params,
+            mips,
+            hdr_bind_group,
+            mip_bind_groups,
+        }
+    }
+
+    /// новый HDR-кадр после ресайза или смены MSAA
+    pub fn set_input(&mut self, device: &wgpu::Device, hdr: &wgpu::TextureView, size: (u32, u32)) {
+        (self.mips, self.hdr_bind_group, self.mip_bind_groups) =
+            Self::chain(device, &self.layout, &self.sampler, &self.params, hdr, size);
+    }
+
+    /// Добавить в энкодер цепочку bloom и смешать результат с `hdr`
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        hdr: &wgpu::TextureView,
+        settings: &BloomConfig,
+    ) {
+        let params = BloomParams {
+            threshold: settings.threshold,
+            knee: settings.knee.max(1e-4),
+            radius: settings.radius,
+            scale: 1.0 / self.mips.len() as f32,
+        };
+        queue.write_buffer(&self.params, 0, params.as_byte_slice());
+
+        let pass = |encoder: &mut wgpu::CommandEncoder,
+                    label,
+                    target: &wgpu::TextureView,
+                    load,
+                    pipeline: &wgpu::RenderPipeline,
+                    bind_group: &wgpu::BindGroup| {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load, store: true },
+                })],
+                depth_stencil_attachment: None,
+            });
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.set_blend_constant(wgpu::Color {
+                r: settings.intensity as f64,
+                g: settings.intensity as f64,
+                b: settings.intensity as f64,
+                a: settings.intensity as f64,
+            });
+            rpass.draw(0..3, 0..1);
+        };
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+
+        // уменьшение: кадр -> уровень 0 -> уровень 1 -> ...
+        pass(
+            encoder,
+            "Bloom Downsample Pass",
+            &self.mips[0],
+            clear,
+            &self.prefilter,
+            &self.hdr_bind_group,
+        );
+        for level in 1..self.mips.len() {
+            pass(
+                encoder,
+                "Bloom Downsample Pass",
+                &self.mips[level],
+                clear,
+                &self.downsample,
+                &self.mip_bind_groups[level - 1],
+            );
+        }
+        // увеличение: каждый уровень добавляется к предыдущему
+        for level in (1..self.mips.len()).rev() {
+            pass(
+                encoder,
+                "Bloom Upsample Pass",
+                &self.mips[level - 1],
+                wgpu::LoadOp::Load,
+                &self.upsample,
+                &self.mip_bind_groups[level],
+            );
+        }
+        pass(
+            encoder,
+            "Bloom Composite Pass",
+            hdr,
+            wgpu::LoadOp::Load,
+            &self.composite,
+            &self.mip_bind_groups[0],
+        );
+    }
+
+    /// мипы цепочки под размер кадра и bind group для каждого источника
+    fn chain(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        params: &wgpu::Buffer,
+        hdr: &wgpu::TextureView,
+        (width, height): (u32, u32),
+    ) -> (
+        Vec<wgpu::TextureView>,
+        wgpu::BindGroup,
+        Vec<wgpu::BindGroup>,
+    ) {
+        let count = mip_count(width, height);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Bloom Texture"),
+            size: wgpu::Extent3d {
+                width: (width / 2).max(1),
+                height: (height / 2).max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let mips: Vec<_> = (0..count)
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Bloom Mip View"),
+                    base_mip_level: level,
+                    mip_level_count: std::num::NonZeroU32::new(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Bloom Bind Group"),
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params.as_entire_binding(),
+                    },
+                ],
+            })
+        };
+        let hdr_bind_group = bind_group(hdr);
+        let mip_bind_groups = mips.iter().map(bind_group).collect();
+        (mips, hdr_bind_group, mip_bind_groups)
+    }
+}
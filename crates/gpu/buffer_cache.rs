@@ -0,0 +1,265 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
+
+use wgpu::util::DeviceExt;
+
+use crate::{Instance, Mesh, Topology, Vertex};
+
+/// Вершинный и индексный буферы меша на GPU
+pub(crate) struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_count: u32,
+}
+
+impl GpuMesh {
+    fn upload(device: &wgpu::Device, mesh: &Mesh) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Object Vertex Buffer"),
+            contents: Vertex::as_byte_slice(&mesh.vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Object Index Buffer"),
+            contents: unsafe {
+                std::slice::from_raw_parts(
+                    mesh.indices.as_ptr() as *const u8,
+                    std::mem::size_of_val(mesh.indices.as_slice()),
+                )
+            },
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        Self {
+            vertex_buffer,
+            index_buffer,
+            index_count: mesh.indices.len() as u32,
+        }
+    }
+}
+
+/// Вершины без индексов для экранных линий и точек: концы отрезков подряд
+/// (`[начало, конец, начало, конец, ...]`), точки или треугольники ленты
+pub(crate) struct GpuVertices {
+    pub buffer: wgpu::Buffer,
+    pub count: u32,
+}
+
+impl GpuVertices {
+    pub fn new(device: &wgpu::Device, label: &str, vertices: &[Vertex]) -> Self {
+        Self {
+            buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: Vertex::as_byte_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }),
+            count: vertices.len() as u32,
+        }
+    }
+
+    /// линии раскладываются на отрезки, точки берутся по индексам (без индексов — все вершины)
+    fn upload(device: &wgpu::Device, mesh: &Mesh) -> Self {
+        let by_index = |indices: &mut dyn Iterator<Item = &u32>| -> Vec<Vertex> {
+            indices.map(|&i| mesh.vertices[i as usize]).collect()
+        };
+        match mesh.topology {
+            Topology::PointList if mesh.indices.is_empty() => {
+                Self::new(device, "Point Buffer", &mesh.vertices)
+            }
+            Topology::PointList => {
+                Self::new(device, "Point Buffer", &by_index(&mut mesh.indices.iter()))
+            }
+            _ => {
+                let segments = mesh.line_segments();
+                let vertices = by_index(&mut segments.iter().flatten());
+                Self::new(device, "Line Segment Buffer", &vertices)
+            }
+        }
+    }
+}
+
+struct Cached<T> {
+    // адрес может достаться новому мешу только после удаления старого
+    mesh: Weak<Mesh>,
+    gpu: Arc<T>,
+    last_used: u64,
+}
+
+/// Буферы мешей по адресу `Arc<Mesh>`: меш за `Arc` не меняется, поэтому загружается
+/// один раз, сколько бы объектов его ни делили. Удалённые меши и меши, не нужные
+/// дольше `MAX_UNUSED_FRAMES` кадров, выгружаются в `end_frame`.
+#[derive(Default)]
+pub(crate) struct MeshCache {
+    meshes: HashMap<usize, Cached<GpuMesh>>,
+    vertices: HashMap<usize, Cached<GpuVertices>>,
+    frame: u64,
+}
+
+impl MeshCache {
+    const MAX_UNUSED_FRAMES: u64 = 120;
+
+    /// вершинный и индексный буферы треугольного меша; загружаются при первом обращении
+    pub fn get(&mut self, device: &wgpu::Device, mesh: &Arc<Mesh>) -> Arc<GpuMesh> {
+        lookup(&mut self.meshes, self.frame, mesh, || {
+            GpuMesh::upload(device, mesh)
+        })
+    }
+
+    /// отрезки или точки меша линий или точек; загружаются при первом обращении
+    pub fn vertices(&mut self, device: &wgpu::Device, mesh: &Arc<Mesh>) -> Arc<GpuVertices> {
+        lookup(&mut self.vertices, self.frame, mesh, || {
+            GpuVertices::upload(device, mesh)
+        })
+    }
+
+    /// выгрузить удалённые и давно не использованные меши
+    pub fn end_frame(&mut self) {
+        let frame = self.frame;
+        self.meshes.retain(|_, cached| cached.is_fresh(frame));
+        self.vertices.retain(|_, cached| cached.is_fresh(frame));
+        self.frame += 1;
+    }
+}
+
+impl<T> Cached<T> {
+    fn is_fresh(&self, frame: u64) -> bool {
+        self.mesh.strong_count() > 0 && frame - self.last_used < MeshCache::MAX_UNUSED_FRAMES
+    }
+}
+
+fn lookup<T>(
+    cache: &mut HashMap<usize, Cached<T>>,
+    frame: u64,
+    mesh: &Arc<Mesh>,
+    upload: impl FnOnce() -> T,
+) -> Arc<T> {
+    let key = Arc::as_ptr(mesh) as usize;
+    match cache.get_mut(&key) {
+        Some(cached) if cached.mesh.strong_count() > 0 => {
+            cached.last_used = frame;
+            cached.gpu.clone()
+        }
+        _ => {
+            let gpu = Arc::new(upload());
+            cache.insert(
+                key,
+                Cached {
+                    mesh: Arc::downgrade(mesh),
+                    gpu: gpu.clone(),
+                    last_used: frame,
+                },
+            );
+            gpu
+        }
+    }
+}
+
+/// Uniform-буферы с bind group, переживающие кадр: в начале кадра слоты раздаются
+/// заново, а содержимое только перезаписывается. Пул растёт до пика кадра
+pub(crate) struct UniformPool {
+    label: &'static str,
+    size: u64,
+    slots: Vec<(wgpu::Buffer, wgpu::BindGroup)>,
+    used: usize,
+}
+
+impl UniformPool {
+    /// пул слотов по `size` байт
+    pub fn new(label: &'static str, size: usize) -> Self {
+        Self {
+            label,
+            size: size as u64,
+            slots: Vec::new(),
+            used: 0,
+        }
+    }
+
+    /// занять следующий слот и записать в него `contents`; возвращает номер слота
+    pub fn push(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        contents: &[u8],
+    ) -> usize {
+        if self.used == self.slots.len() {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(self.label),
+                size: self.size,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(self.label),
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            self.slots.push((buffer, bind_group));
+        }
+        let slot = self.used;
+        queue.write_buffer(&self.slots[slot].0, 0, contents);
+        self.used += 1;
+        slot
+    }
+
+    pub fn bind_group(&self, slot: usize) -> &wgpu::BindGroup {
+        &self.slots[slot].1
+    }
+
+    /// освободить все слоты для нового кадра
+    pub fn reset(&mut self) {
+        self.used = 0;
+    }
+}
+
+/// Буферы экземпляров по порядку инстансированных объектов; пересоздаются только
+/// при росте числа экземпляров
+#[derive(Default)]
+pub(crate) struct InstanceBuffers {
+    buffers: Vec<wgpu::Buffer>,
+}
+
+impl InstanceBuffers {
+    /// записать экземпляры в буфер `slot`
+    pub fn write(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        slot: usize,
+        instances: &[Instance],
+    ) {
+        let contents = Instance::as_byte_slice(instances);
+        let size = contents.len() as u64;
+        if self
+            .buffers
+            .get(slot)
+            .is_none_or(|buffer| buffer.size() < size)
+        {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Instance Buffer"),
+                size: size.next_power_of_two(),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            if slot < self.buffers.len() {
+                self.buffers[slot] = buffer;
+            } else {
+                self.buffers.push(buffer);
+            }
+        }
+        queue.write_buffer(&self.buffers[slot], 0, contents);
+    }
+
+    pub fn get(&self, slot: usize) -> &wgpu::Buffer {
+        &self.buffers[slot]
+    }
+
+    /// оставить буферы только для `len` объектов
+    pub fn truncate(&mut self, len: usize) {
+        self.buffers.truncate(len);
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+
+use crate::{
+    bloom::BloomConfig, shadow::ShadowConfig, tonemap::ToneMapping, transparency::Transparency,
+};
+
+/// Настройки `Renderer`. Неподдерживаемые значения заменяются ближайшими допустимыми
+/// при создании (`RendererConfig::resolve`, с предупреждением через `log`);
+/// фактические — в `Renderer::settings`, замены — в `Renderer::fallbacks`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RendererConfig {
+    /// число сэмплов MSAA: 1 (без сглаживания), 2, 4 или 8
+    pub sample_count: u32,
+    pub present_mode: wgpu::PresentMode,
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    pub depth_format: wgpu::TextureFormat,
+    pub clear_color: wgpu::Color,
+    pub shadows: ShadowConfig,
+    pub tone_mapping: ToneMapping,
+    pub bloom: BloomConfig,
+    pub transparency: Transparency,
+    /// не рисовать объекты вне пирамиды камеры
+    pub frustum_culling: bool,
+    /// запас вокруг порогов `LodLevel::screen_size` (доля) против мигания уровней
+    pub lod_hysteresis: f32,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self {
+            sample_count: 4,
+            present_mode: wgpu::PresentMode::Fifo,
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            depth_format: wgpu::TextureFormat::Depth32Float,
+            clear_color: wgpu::Color {
+                r: 0.1,
+                g: 0.1,
+                b: 0.12,
+                a: 1.0,
+            },
+            shadows: ShadowConfig::default(),
+            tone_mapping: ToneMapping::default(),
+            bloom: BloomConfig::default(),
+            transparency: Transparency::default(),
+            frustum_culling: true,
+            lod_hysteresis: 0.15,
+        }
+    }
+}
+
+impl RendererConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// вертикальная синхронизация: `AutoVsync` или `AutoNoVsync` (всегда доступны)
+    pub fn with_vsync(self, vsync: bool) -> Self {
+        self.with_present_mode(present_mode(vsync))
+    }
+
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn with_depth_format(mut self, depth_format: wgpu::TextureFormat) -> Self {
+        self.depth_format = depth_format;
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: wgpu::Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    pub fn with_shadows(mut self, shadows: ShadowConfig) -> Self {
+        self.shadows = shadows;
+        self
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn with_bloom(mut self, bloom: BloomConfig) -> Self {
+        self.bloom = bloom;
+        self
+    }
+
+    pub fn with_transparency(mut self, transparency: Transparency) -> Self {
+        self.transparency = transparency;
+        self
+    }
+
+    pub fn with_frustum_culling(mut self, frustum_culling: bool) -> Self {
+        self.frustum_culling = frustum_culling;
+        self
+    }
+
+    pub fn with_lod_hysteresis(mut self, lod_hysteresis: f32) -> Self {
+        self.lod_hysteresis = lod_hysteresis;
+        self
+    }
+
+    /// включена ли вертикальная синхронизация
+    pub fn vsync(&self) -> bool {
+        matches!(
+            self.present_mode,
+            wgpu::PresentMode::AutoVsync | wgpu::PresentMode::Fifo | wgpu::PresentMode::FifoRelaxed
+        )
+    }
+
+    /// Заменить неподдерживаемые значения допустимыми: формат глубины — на `Depth32Float`,
+    /// MSAA — на ближайшее меньшее, режим презентации — на `Fifo`, разрешение теней —
+    /// на предел текстур. Возвращает фактические настройки и список замен
+    pub fn resolve(mut self, capabilities: &Capabilities) -> (Self, Vec<ConfigFallback>) {
+        let mut fallbacks = Vec::new();
+        if !capabilities.depth_format_supported {
+            fallbacks.push(ConfigFallback::DepthFormat {
+                requested: self.depth_format,
+                used: FALLBACK_DEPTH_FORMAT,
+            });
+            self.depth_format = FALLBACK_DEPTH_FORMAT;
+        }
+        if !capabilities.sample_counts.contains(&self.sample_count) {
+            let used = nearest_sample_count(&capabilities.sample_counts, self.sample_count);
+            fallbacks.push(ConfigFallback::SampleCount {
+                requested: self.sample_count,
+                used,
+            });
+            self.sample_count = used;
+        }
+        if !present_mode_supported(self.present_mode, &capabilities.present_modes) {
+            fallbacks.push(ConfigFallback::PresentMode {
+                requested: self.present_mode,
+                used: wgpu::PresentMode::Fifo,
+            });
+            self.present_mode = wgpu::PresentMode::Fifo;
+        }
+        if self.shadows.resolution > capabilities.max_texture_dimension {
+            fallbacks.push(ConfigFallback::ShadowResolution {
+                requested: self.shadows.resolution,
+                used: capabilities.max_texture_dimension,
+            });
+            self.shadows.resolution = capabilities.max_texture_dimension;
+        }
+        (self, fallbacks)
+    }
+}
+
+/// формат глубины, который есть у любого адаптера
+pub const FALLBACK_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Что умеют адаптер и surface — по этому проверяется `RendererConfig`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capabilities {
+    /// запрошенный формат глубины годится для вложения глубины
+    pub depth_format_supported: bool,
+    /// числа сэмплов MSAA, допустимые для HDR-кадра и итогового формата глубины
+    pub sample_counts: Vec<u32>,
+    pub present_modes: Vec<wgpu::PresentMode>,
+    /// предел стороны 2D-текстуры (карты теней)
+    pub max_texture_dimension: u32,
+}
+
+/// Настройка, заменённая при создании `Renderer`: запрошенное и фактическое значение
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConfigFallback {
+    DepthFormat {
+        requested: wgpu::TextureFormat,
+        used: wgpu::TextureFormat,
+    },
+    SampleCount {
+        requested: u32,
+        used: u32,
+    },
+    PresentMode {
+        requested: wgpu::PresentMode,
+        used: wgpu::PresentMode,
+    },
+    ShadowResolution {
+        requested: u32,
+        used: u32,
+    },
+}
+
+impl fmt::Display for ConfigFallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFallback::DepthFormat { requested, used } => {
+                write!(
+                    f,
+                    "depth format {requested:?} is not supported, using {used:?}"
+                )
+            }
+            ConfigFallback::SampleCount { requested, used } => {
+                write!(f, "MSAA {requested}x is not supported, using {used}x")
+            }
+            ConfigFallback::PresentMode { requested, used } => {
+                write!(
+                    f,
+                    "present mode {requested:?} is not supported, using {used:?}"
+                )
+            }
+            ConfigFallback::ShadowResolution { requested, used } => {
+                write!(
+                    f,
+                    "shadow map resolution {requested} is not supported, using {used}"
+                )
+            }
+        }
+    }
+}
+
+/// AutoVsync и AutoNoVsync wgpu подбирает сам, остальные — только из возможностей surface
+pub(crate) fn present_mode_supported(
+    mode: wgpu::PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> bool {
+    matches!(
+        mode,
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+    ) || supported.contains(&mode)
+}
+
+/// наибольшее поддерживаемое число сэмплов не выше запрошенного (1 поддерживается всегда)
+fn nearest_sample_count(supported: &[u32], requested: u32) -> u32 {
+    supported
+        .iter()
+        .copied()
+        .filter(|&count| count <= requested)
+        .max()
+        .unwrap_or(1)
+}
+
+pub(crate) fn present_mode(vsync: bool) -> wgpu::PresentMode {
+    if vsync {
+        wgpu::PresentMode::AutoVsync
+    } else {
+        wgpu::PresentMode::AutoNoVsync
+    }
+}
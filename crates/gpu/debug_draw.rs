@@ -0,0 +1,338 @@
+use std::f32::consts::TAU;
+
+use glam::{Mat4, Vec3};
+
+use crate::Vertex;
+
+/// Отрезок отладочной графики
+#[derive(Copy, Clone, Debug)]
+pub struct DebugLine {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub color: [f32; 3],
+    /// false — рисуется поверх сцены
+    pub depth_test: bool,
+    /// сколько кадров ещё показывать (включая текущий)
+    pub frames_left: u32,
+}
+
+/// Надпись, всегда повёрнутая к камере
+#[derive(Clone, Debug)]
+pub struct DebugText {
+    /// левый нижний угол первого символа
+    pub position: Vec3,
+    pub text: String,
+    /// высота символа в мировых единицах
+    pub size: f32,
+    pub color: [f32; 3],
+    pub depth_test: bool,
+    pub frames_left: u32,
+}
+
+/// Immediate-mode отладочная графика: вызовы копятся за кадр и рисуются `Renderer` поверх сцены.
+/// `depth_test` и `lifetime` применяются ко всем следующим вызовам.
+pub struct DebugDraw {
+    /// проверять глубину (иначе линии видны сквозь геометрию)
+    pub depth_test: bool,
+    /// время жизни в кадрах; 1 — только текущий кадр
+    pub lifetime: u32,
+    /// толщина всех отладочных линий в пикселях
+    pub line_width: f32,
+    lines: Vec<DebugLine>,
+    texts: Vec<DebugText>,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            depth_test: true,
+            lifetime: 1,
+            line_width: 1.5,
+            lines: Vec::new(),
+            texts: Vec::new(),
+        }
+    }
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+    pub fn texts(&self) -> &[DebugText] {
+        &self.texts
+    }
+
+    /// убрать всё, включая примитивы с оставшимся временем жизни
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.texts.clear();
+    }
+
+    /// конец кадра: уменьшить время жизни и удалить истёкшие (вызывает `Renderer`)
+    pub fn end_frame(&mut self) {
+        self.lines.retain_mut(|line| {
+            line.frames_left = line.frames_left.saturating_sub(1);
+            line.frames_left > 0
+        });
+        self.texts.retain_mut(|text| {
+            text.frames_left = text.frames_left.saturating_sub(1);
+            text.frames_left > 0
+        });
+    }
+
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: [f32; 3]) {
+        self.lines.push(DebugLine {
+            start,
+            end,
+            color,
+            depth_test: self.depth_test,
+            frames_left: self.lifetime.max(1),
+        });
+    }
+
+    /// ломаная через точки (траектории, геодезические)
+    pub fn polyline(&mut self, points: &[Vec3], color: [f32; 3]) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    /// вектор из `from` в `to` с наконечником из четырёх штрихов
+    pub fn arrow(&mut self, from: Vec3, to: Vec3, color: [f32; 3]) {
+        self.line(from, to, color);
+        let direction = to - from;
+        let length = direction.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let axis = direction / length;
+        let (a, b) = axis.any_orthonormal_pair();
+        let head = length * 0.15;
+        let base = to - axis * head;
+        for side in [a, -a, b, -b] {
+            self.line(to, base + side * head * 0.4, color);
+        }
+    }
+
+    /// ось-ориентированный параллелепипед
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: [f32; 3]) {
+        let corner = |i: usize| {
+            Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// каркасная сфера: три больших окружности
+    pub fn sphere(&mut self, center: Vec3, radius: f32, color: [f32; 3]) {
+        const SEGMENTS: usize = 32;
+        for (u, v) in [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)] {
+            let point = |k: usize| {
+                let angle = k as f32 / SEGMENTS as f32 * TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            };
+            for k in 0..SEGMENTS {
+                self.line(point(k), point(k + 1), color);
+            }
+        }
+    }
+
+    /// оси системы координат `transform`: X красная, Y зелёная, Z синяя
+    pub fn axes(&mut self, transform: Mat4, size: f32) {
+        let origin = transform.transform_point3(Vec3::ZERO);
+        let axes = [
+            (Vec3::X, [1.0, 0.0, 0.0]),
+            (Vec3::Y, [0.0, 1.0, 0.0]),
+            (Vec3::Z, [0.0, 0.0, 1.0]),
+        ];
+        for (axis, color) in axes {
+            self.arrow(origin, transform.transform_point3(axis * size), color);
+        }
+    }
+
+    /// пирамида видимости камеры по её матрице projection * view (NDC z в [-1, 1])
+    pub fn frustum(&mut self, view_proj: Mat4, color: [f32; 3]) {
+        let inverse = view_proj.inverse();
+        let corner = |i: usize| {
+            let ndc = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            inverse.project_point3(ndc)
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// текст (латиница, цифры, простые знаки), развёрнутый к камере
+    pub fn text3d(&mut self, position: Vec3, text: &str, size: f32, color: [f32; 3]) {
+        self.texts.push(DebugText {
+            position,
+            text: text.to_owned(),
+            size,
+            color,
+            depth_test: self.depth_test,
+            frames_left: self.lifetime.max(1),
+        });
+    }
+
+    /// 12 рёбер «коробки»; бит 0/1/2 индекса угла — сторона по x/y/z
+    fn box_edges(&mut self, corner: impl Fn(usize) -> Vec3, color: [f32; 3]) {
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    /// вершины отрезков парами [начало, конец, ...]: (с проверкой глубины, поверх сцены).
+    /// `right` и `up` — базис камеры для текста.
+    pub(crate) fn segment_vertices(&self, right: Vec3, up: Vec3) -> (Vec<Vertex>, Vec<Vertex>) {
+        let mut depth = Vec::new();
+        let mut overlay = Vec::new();
+        let mut push = |start: Vec3, end: Vec3, color: [f32; 3], depth_test: bool| {
+            let target = if depth_test { &mut depth } else { &mut overlay };
+            for position in [start, end] {
+                target.push(Vertex {
+                    position: position.to_array(),
+                    color,
+                    ..Default::default()
+                });
+            }
+        };
+
+        for line in &self.lines {
+            push(line.start, line.end, line.color, line.depth_test);
+        }
+        for text in &self.texts {
+            for (start, end) in text_segments(text, right, up) {
+                push(start, end, text.color, text.depth_test);
+            }
+        }
+        (depth, overlay)
+    }
+}
+
+/// Отрезки надписи в мире: символ занимает квадрат 2x2 шрифта, масштаб — от высоты
+fn text_segments(text: &DebugText, right: Vec3, up: Vec3) -> Vec<(Vec3, Vec3)> {
+    let scale = text.size / 2.0;
+    let advance = text.size * 0.75;
+    let mut segments = Vec::new();
+    for (i, c) in text.text.chars().enumerate() {
+        let origin = text.position + right * (advance * i as f32);
+        let mask = glyph(c);
+        for (bit, [from, to]) in SEGMENTS.iter().enumerate() {
+            if mask & (1 << bit) != 0 {
+                let point = |[x, y]: [f32; 2]| origin + (right * x * 0.5 + up * y) * scale;
+                segments.push((point(*from), point(*to)));
+            }
+        }
+    }
+    segments
+}
+
+// Шестнадцатисегментный шрифт: сегменты в квадрате 2x2 (x сжимается вдвое при выводе)
+const SEGMENTS: [[[f32; 2]; 2]; 16] = [
+    [[0.0, 2.0], [1.0, 2.0]], // A1
+    [[1.0, 2.0], [2.0, 2.0]], // A2
+    [[2.0, 2.0], [2.0, 1.0]], // B
+    [[2.0, 1.0], [2.0, 0.0]], // C
+    [[0.0, 0.0], [1.0, 0.0]], // D1
+    [[1.0, 0.0], [2.0, 0.0]], // D2
+    [[0.0, 0.0], [0.0, 1.0]], // E
+    [[0.0, 1.0], [0.0, 2.0]], // F
+    [[0.0, 1.0], [1.0, 1.0]], // G1
+    [[1.0, 1.0], [2.0, 1.0]], // G2
+    [[0.0, 2.0], [1.0, 1.0]], // H
+    [[1.0, 2.0], [1.0, 1.0]], // I
+    [[2.0, 2.0], [1.0, 1.0]], // J
+    [[1.0, 1.0], [0.0, 0.0]], // K
+    [[1.0, 1.0], [1.0, 0.0]], // L
+    [[1.0, 1.0], [2.0, 0.0]], // M
+];
+
+const A1: u16 = 1;
+const A2: u16 = 1 << 1;
+const B: u16 = 1 << 2;
+const C: u16 = 1 << 3;
+const D1: u16 = 1 << 4;
+const D2: u16 = 1 << 5;
+const E: u16 = 1 << 6;
+const F: u16 = 1 << 7;
+const G1: u16 = 1 << 8;
+const G2: u16 = 1 << 9;
+const H: u16 = 1 << 10;
+const I: u16 = 1 << 11;
+const J: u16 = 1 << 12;
+const K: u16 = 1 << 13;
+const L: u16 = 1 << 14;
+const M: u16 = 1 << 15;
+
+const A: u16 = A1 | A2;
+const D: u16 = D1 | D2;
+const G: u16 = G1 | G2;
+
+/// маска сегментов символа; неизвестные символы пустые
+fn glyph(c: char) -> u16 {
+    match c.to_ascii_uppercase() {
+        '0' => A | B | C | D | E | F | J | K,
+        '1' => B | C | J,
+        '2' => A | B | G | E | D,
+        '3' => A | B | C | D | G2,
+        '4' => F | G | B | C,
+        '5' => A | F | G | C | D,
+        '6' => A | F | E | D | C | G,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' => A | B | C | E | F | G,
+        'B' => A | B | C | D | I | L | G2,
+        'C' => A | F | E | D,
+        'D' => A | B | C | D | I | L,
+        'E' => A | F | E | D | G1,
+        'F' => A | F | E | G1,
+        'G' => A | F | E | D | C | G2,
+        'H' => F | E | B | C | G,
+        'I' => A | I | L | D,
+        'J' => B | C | D | E,
+        'K' => F | E | G1 | J | M,
+        'L' => F | E | D,
+        'M' => F | E | B | C | H | J,
+        'N' => F | E | B | C | H | M,
+        'O' => A | B | C | D | E | F,
+        'P' => A | B | F | E | G,
+        'Q' => A | B | C | D | E | F | M,
+        'R' => A | B | F | E | G | M,
+        'S' => A | F | G | C | D,
+        'T' => A | I | L,
+        'U' => F | E | D | C | B,
+        'V' => F | E | K | J,
+        'W' => F | E | B | C | K | M,
+        'X' => H | J | K | M,
+        'Y' => H | J | L,
+        'Z' => A | J | K | D,
+        '-' => G,
+        '+' => G | I | L,
+        '=' => G | D,
+        '/' => J | K,
+        '\\' => H | M,
+        '*' => G | H | I | J | K | L | M,
+        '(' | '<' => J | M,
+        ')' | '>' => H | K,
+        '_' | '.' => D1,
+        ',' => K,
+        '|' | ':' => I | L,
+        '\'' => I,
+        _ => 0,
+    }
+}
@@ -0,0 +1,47 @@
+/// Заливка объектов в отладочных режимах
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Shading {
+    /// обычное освещение
+    #[default]
+    Lit,
+    /// линейная (логарифмическая) глубина в оттенках серого, ближе — темнее
+    Depth,
+    /// свой цвет у каждого объекта
+    FlatColor,
+    /// лицевые грани серые, обратные — пурпурные (поиск перевёрнутого обхода)
+    Backfaces,
+}
+
+/// Отладочные режимы отображения `Renderer`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DebugView {
+    pub shading: Shading,
+    /// рёбра треугольников поверх сцены
+    pub wireframe: bool,
+    /// нормали вершин отрезками (цвет — направление нормали)
+    pub normals: bool,
+    /// длина отрезка нормали в мировых единицах
+    pub normal_length: f32,
+}
+
+impl Default for DebugView {
+    fn default() -> Self {
+        Self {
+            shading: Shading::Lit,
+            wireframe: false,
+            normals: false,
+            normal_length: 0.1,
+        }
+    }
+}
+
+impl DebugView {
+    /// включить режим заливки или вернуться к `Lit`, если он уже включён
+    pub fn toggle_shading(&mut self, shading: Shading) {
+        self.shading = if self.shading == shading {
+            Shading::Lit
+        } else {
+            shading
+        };
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    f32::consts::PI,
+    fmt,
+    num::NonZeroU32,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use glam::Vec3;
+use utilities::texture::Texture;
+
+static NEXT_ENVIRONMENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Ошибка сборки кубической карты окружения
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvironmentError {
+    /// грань не квадратная или её размер отличается от первой грани
+    FaceSize {
+        face: usize,
+        width: u32,
+        height: u32,
+        expected: u32,
+    },
+}
+
+impl fmt::Display for EnvironmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvironmentError::FaceSize {
+                face,
+                width,
+                height,
+                expected,
+            } => write!(
+                f,
+                "cube map face {face} is {width}x{height}, expected {expected}x{expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EnvironmentError {}
+
+/// Окружение сцены: небо на фоне и рассеянное освещение от него (вместо `DirectionalLight::ambient`).
+/// Хранится как кубическая карта RGBA8; грани в порядке +X, -X, +Y, -Y, +Z, -Z
+#[derive(Clone, Debug)]
+pub struct Environment {
+    id: u64,
+    size: u32,
+    faces: [Vec<u8>; 6],
+    srgb: bool,
+    /// множитель яркости неба и фонового освещения
+    pub intensity: f32,
+    // сферические гармоники облучённости (9 коэффициентов) при intensity = 1
+    irradiance: [Vec3; 9],
+}
+
+impl Environment {
+    /// кубическая карта из шести квадратных граней одного размера
+    pub fn cube_map(faces: [Texture; 6]) -> Result<Self, EnvironmentError> {
+        let size = faces[0].width();
+        for (face, texture) in faces.iter().enumerate() {
+            if texture.width() != size || texture.height() != size {
+                return Err(EnvironmentError::FaceSize {
+                    face,
+                    width: texture.width(),
+                    height: texture.height(),
+                    expected: size,
+                });
+            }
+        }
+        let srgb = faces[0].is_srgb();
+        Ok(Self::from_faces(
+            size,
+            faces.map(|texture| texture.data().to_vec()),
+            srgb,
+        ))
+    }
+
+    /// Панорама 2:1 (долгота по горизонтали, центр кадра смотрит в -Z);
+    /// переводится в кубическую карту с гранью в половину высоты
+    pub fn equirectangular(texture: &Texture) -> Self {
+        let size = (texture.height() / 2).max(1);
+        let faces = std::array::from_fn(|face| {
+            let mut data = Vec::with_capacity((size * size * 4) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    let direction = face_direction(face, size, x, y);
+                    let u = 0.5 + direction.x.atan2(-direction.z) / (2.0 * PI);
+                    let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+                    data.extend(sample_bilinear(texture, u, v));
+                }
+            }
+            data
+        });
+        Self::from_faces(size, faces, texture.is_srgb())
+    }
+
+    /// Процедурное небо: линейные цвета в зените, у горизонта и под ним
+    pub fn gradient(zenith: [f32; 3], horizon: [f32; 3], nadir: [f32; 3]) -> Self {
+        const SIZE: u32 = 32;
+        let [zenith, horizon, nadir] = [zenith, horizon, nadir].map(Vec3::from);
+        let faces = std::array::from_fn(|face| {
+            let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let height = face_direction(face, SIZE, x, y).y;
+                    let color = if height >= 0.0 {
+                        horizon.lerp(zenith, height.sqrt())
+                    } else {
+                        horizon.lerp(nadir, (-height).sqrt())
+                    };
+                    data.extend(
+                        color
+                            .to_array()
+                            .map(|c| (linear_to_srgb(c) * 255.0).round() as u8),
+                    );
+                    data.push(255);
+                }
+            }
+            data
+        });
+        Self::from_faces(SIZE, faces, true)
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// размер грани в пикселях
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn faces(&self) -> &[Vec<u8>; 6] {
+        &self.faces
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        self.srgb
+    }
+
+    /// коэффициенты облучённости с учётом `intensity`, уже делённые на π (ламберт)
+    pub fn irradiance(&self) -> [Vec3; 9] {
+        self.irradiance.map(|c| c * self.intensity)
+    }
+
+    /// рассеянный свет, приходящий на поверхность с нормалью `normal` (то же, что в шейдере)
+    pub fn ambient(&self, normal: Vec3) -> Vec3 {
+        let basis = sh_basis(normal.normalize_or_zero());
+        let irradiance = self.irradiance();
+        (0..9)
+            .map(|i| irradiance[i] * basis[i])
+            .sum::<Vec3>()
+            .max(Vec3::ZERO)
+    }
+
+    fn from_faces(size: u32, faces: [Vec<u8>; 6], srgb: bool) -> Self {
+        let irradiance = project_irradiance(size, &faces, srgb);
+        Self {
+            id: NEXT_ENVIRONMENT_ID.fetch_add(1, Ordering::Relaxed),
+            size,
+            faces,
+            srgb,
+            intensity: 1.0,
+            irradiance,
+        }
+    }
+}
+
+/// Коэффициенты облучённости без окружения: одинаковый фоновый свет со всех сторон
+pub(crate) fn constant_irradiance(ambient: f32) -> [Vec3; 9] {
+    let mut irradiance = [Vec3::ZERO; 9];
+    irradiance[0] = Vec3::splat(ambient / SH_Y00);
+    irradiance
+}
+
+const SH_Y00: f32 = 0.282_095;
+
+/// Вещественные сферические гармоники до второго порядка
+fn sh_basis(n: Vec3) -> [f32; 9] {
+    [
+        SH_Y00,
+        0.488_603 * n.y,
+        0.488_603 * n.z,
+        0.488_603 * n.x,
+        1.092_548 * n.x * n.y,
+        1.092_548 * n.y * n.z,
+        0.315_392 * (3.0 * n.z * n.z - 1.0),
+        1.092_548 * n.x * n.z,
+        0.546_274 * (n.x * n.x - n.y * n.y),
+    ]
+}
+
+/// Проекция яркости граней на гармоники и свёртка с косинусом (Ramamoorthi, Hanrahan)
+fn project_irradiance(size: u32, faces: &[Vec<u8>; 6], srgb: bool) -> [Vec3; 9] {
+    let mut radiance = [Vec3::ZERO; 9];
+    let texel = 2.0 / size as f32;
+    for (face, data) in faces.iter().enumerate() {
+        for y in 0..size {
+            for x in 0..size {
+                let direction = face_direction(face, size, x, y);
+                let (s, t) = (x as f32 * texel - 1.0, y as f32 * texel - 1.0);
+                let solid_angle = texel_solid_angle(s, t, texel);
+                let offset = ((y * size + x) * 4) as usize;
+                let color = Vec3::from_array(std::array::from_fn(|c| {
+                    let value = data[offset + c] as f32 / 255.0;
+                    if srgb { srgb_to_linear(value) } else { value }
+                }));
+                let basis = sh_basis(direction.normalize());
+                for i in 0..9 {
+                    radiance[i] += color * basis[i] * solid_angle;
+                }
+            }
+        }
+    }
+    // свёртка с косинусом по полосам (π, 2π/3, π/4), затем деление на π
+    let band = [
+        1.0,
+        2.0 / 3.0,
+        2.0 / 3.0,
+        2.0 / 3.0,
+        0.25,
+        0.25,
+        0.25,
+        0.25,
+        0.25,
+    ];
+    std::array::from_fn(|i| radiance[i] * band[i])
+}
+
+/// Точный телесный угол текселя `[s, s + texel] x [t, t + texel]` на грани куба с полуребром 1
+fn texel_solid_angle(s: f32, t: f32, texel: f32) -> f32 {
+    let area = |x: f32, y: f32| (x * y).atan2((x * x + y * y + 1.0).sqrt());
+    let (s1, t1) = (s + texel, t + texel);
+    area(s, t) - area(s, t1) - area(s1, t) + area(s1, t1)
+}
+
+/// Направление (не нормализованное) на центр текселя грани; соглашение кубических карт WebGPU
+fn face_direction(face: usize, size: u32, x: u32, y: u32) -> Vec3 {
+    let s = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let t = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    match face {
+        0 => Vec3::new(1.0, -t, -s),
+        1 => Vec3::new(-1.0, -t, s),
+        2 => Vec3::new(s, 1.0, t),
+        3 => Vec3::new(s, -1.0, -t),
+        4 => Vec3::new(s, -t, 1.0),
+        _ => Vec3::new(-s, -t, -1.0),
+    }
+}
+
+/// Билинейная выборка RGBA8 с повтором по горизонтали и обрезкой по вертикали
+fn sample_bilinear(texture: &Texture, u: f32, v: f32) -> [u8; 4] {
+    let (width, height) = (texture.width(), texture.height());
+    let x = u * width as f32 - 0.5;
+    let y = (v * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let column = |x: f32| (x as i64).rem_euclid(width as i64) as u32;
+    let row = |y: f32| (y as u32).min(height - 1);
+    let texel =
+        |x: u32, y: u32, c: usize| texture.data()[((y * width + x) * 4) as usize + c] as f32;
+    let (left, right) = (column(x0), column(x0 + 1.0));
+    let (top, bottom) = (row(y0), row(y0 + 1.0));
+    std::array::from_fn(|c| {
+        let upper = texel(left, top, c) * (1.0 - fx) + texel(right, top, c) * fx;
+        let lower = texel(left, bottom, c) * (1.0 - fx) + texel(right, bottom, c) * fx;
+        (upper * (1.0 - fy) + lower * fy).round() as u8
+    })
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    let c = c.clamp(0.0, 1.0);
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Кубическая карта на GPU с сэмплером и буфером яркости (group 1 пайплайна неба)
+pub(crate) struct GpuEnvironment {
+    pub id: u64,
+    pub bind_group: wgpu::BindGroup,
+    params: wgpu::Buffer,
+}
+
+impl GpuEnvironment {
+    /// layout для group 1: texture_cube + sampler + яркость
+    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Environment BGL"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    pub fn upload(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        environment: &Environment,
+    ) -> Self {
+        let size = environment.size();
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Environment Cube Map"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: if environment.is_srgb() {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            },
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (layer, data) in environment.faces().iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(size * 4),
+                    rows_per_image: NonZeroU32::new(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Environment Cube View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Environment Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        // vec4 ради выравнивания uniform; x — яркость
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Environment Params"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Environment Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+        Self {
+            id: environment.id(),
+            bind_group,
+            params,
+        }
+    }
+
+    /// яркость неба на этот кадр
+    pub fn set_intensity(&self, queue: &wgpu::Queue, intensity: f32) {
+        let params = [intensity, 0.0, 0.0, 0.0];
+        let bytes: Vec<u8> = params.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        queue.write_buffer(&self.params, 0, &bytes);
+    }
+}
@@ -1,28 +1,178 @@
+pub mod bloom;
+mod buffer_cache;
+pub mod config;
+pub mod debug_draw;
+pub mod debug_view;
+pub mod environment;
+pub mod lod;
+mod picking;
+pub mod post_process;
 mod renderer;
+mod renderer_error;
 mod shaders;
+pub mod shadow;
+mod texture;
+pub mod tonemap;
+pub mod trail;
+pub mod transparency;
 
+pub use bloom::BloomConfig;
+pub use config::{Capabilities, ConfigFallback, RendererConfig};
+pub use debug_draw::DebugDraw;
+pub use debug_view::{DebugView, Shading};
+pub use environment::{Environment, EnvironmentError};
 pub use glam::*;
+pub use picking::Pick;
 pub use pollster::*;
-pub use renderer::Renderer;
-pub use shaders::{FRAGMENT_SHADER, VERTEX_SHADER};
+pub use post_process::{PostEffect, PostEffectId, PostEffectList};
+pub use renderer::{CullingStats, Renderer};
+pub use renderer_error::RendererError;
+pub use shaders::{
+    AUTO_EXPOSURE_SHADER, BLOOM_SHADER, DEBUG_FRAGMENT_SHADER, FRAGMENT_SHADER,
+    FULLSCREEN_VERTEX_SHADER, INSTANCED_SHADOW_VERTEX_SHADER, INSTANCED_VERTEX_SHADER,
+    LINE_VERTEX_SHADER, OIT_COMPOSITE_SHADER, POINT_FRAGMENT_SHADER, POINT_VERTEX_SHADER,
+    POST_EFFECT_HEADER, RIBBON_FRAGMENT_SHADER, SHADOW_VERTEX_SHADER, SKYBOX_SHADER,
+    TEXTURED_FRAGMENT_SHADER, TONE_MAP_FRAGMENT_SHADER, UNLIT_FRAGMENT_SHADER, VERTEX_SHADER,
+    VIGNETTE_EFFECT_SHADER,
+};
+pub use shadow::ShadowConfig;
+pub use tonemap::{Exposure, ToneMapper, ToneMapping};
+pub use trail::{Trail, TrailStyle};
+pub use transparency::Transparency;
 pub use utilities::prelude::*;
+pub use wgpu;
 pub use winit::*;
 
+use std::sync::{Mutex, PoisonError};
+
+/// Направленный свет (солнце): один на сцену
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirectionalLight {
+    /// направление на источник (нормализуется в шейдере)
+    pub direction: Vec3,
+    pub color: [f32; 3],
+    /// фоновое освещение, не зависящее от направления; без окружения сцены
+    pub ambient: f32,
+    pub cast_shadows: bool,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::new(0.3, 1.0, 0.4).normalize(),
+            color: [0.8, 0.8, 0.8],
+            ambient: 0.2,
+            cast_shadows: true,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Scene {
     objects: Vec<Object3D>,
+    // TLAS для `raycast`: строится при первом запросе, перестраивается после добавления
+    // объектов и подгоняется после `objects_mut`
+    bvh: Mutex<SceneBvhCache>,
+    instanced: Vec<InstancedObject>,
+    trails: Vec<Trail>,
+    light: DirectionalLight,
+    environment: Option<Environment>,
+}
+
+#[derive(Default)]
+struct SceneBvhCache {
+    bvh: Option<SceneBvh>,
+    refit: bool,
 }
 
 impl Scene {
     pub fn new() -> Self {
         Self::default()
     }
-    pub fn add_object(&mut self, obj: Object3D) {
+    /// добавить объект; возвращает его индекс (например, для `Trail::following`)
+    pub fn add_object(&mut self, obj: Object3D) -> usize {
+        self.bvh_cache().bvh = None;
         self.objects.push(obj);
+        self.objects.len() - 1
     }
     pub fn objects(&self) -> &[Object3D] {
         &self.objects
     }
+    /// меши объектов не меняются, поэтому TLAS после правок достаточно подогнать
+    pub fn objects_mut(&mut self) -> &mut [Object3D] {
+        self.bvh_cache().refit = true;
+        &mut self.objects
+    }
+    /// добавить инстансированный объект; возвращает индекс для `instanced_mut`
+    pub fn add_instanced(&mut self, obj: InstancedObject) -> usize {
+        self.instanced.push(obj);
+        self.instanced.len() - 1
+    }
+    pub fn instanced(&self) -> &[InstancedObject] {
+        &self.instanced
+    }
+    pub fn instanced_mut(&mut self) -> &mut [InstancedObject] {
+        &mut self.instanced
+    }
+    /// добавить след; возвращает индекс для `trails_mut`
+    pub fn add_trail(&mut self, trail: Trail) -> usize {
+        self.trails.push(trail);
+        self.trails.len() - 1
+    }
+    pub fn trails(&self) -> &[Trail] {
+        &self.trails
+    }
+    pub fn trails_mut(&mut self) -> &mut [Trail] {
+        &mut self.trails
+    }
+    pub fn light(&self) -> &DirectionalLight {
+        &self.light
+    }
+    pub fn light_mut(&mut self) -> &mut DirectionalLight {
+        &mut self.light
+    }
+    /// небо на фоне и рассеянное освещение от него вместо `DirectionalLight::ambient`
+    pub fn set_environment(&mut self, environment: Environment) {
+        self.environment = Some(environment);
+    }
+    pub fn clear_environment(&mut self) {
+        self.environment = None;
+    }
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+    pub fn environment_mut(&mut self) -> Option<&mut Environment> {
+        self.environment.as_mut()
+    }
+    /// ближайший объект под лучом (CPU, по BVH сцены и мешей); только треугольные объекты
+    pub fn raycast(&self, ray: &Ray) -> Option<SceneHit> {
+        let mut cache = self.bvh.lock().unwrap_or_else(PoisonError::into_inner);
+        let cache = &mut *cache;
+        match &mut cache.bvh {
+            Some(bvh) if cache.refit => bvh.refit(&self.objects),
+            Some(_) => {}
+            None => cache.bvh = Some(SceneBvh::new(&self.objects)),
+        }
+        cache.refit = false;
+        cache
+            .bvh
+            .as_ref()?
+            .raycast(&self.objects, ray, f32::INFINITY)
+    }
+    fn bvh_cache(&mut self) -> &mut SceneBvhCache {
+        self.bvh.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// продвинуть время следов и записать текущие позиции объектов, за которыми они следуют
+    pub fn update_trails(&mut self, delta_time: f32) {
+        for trail in &mut self.trails {
+            trail.advance(delta_time);
+            let target = trail.target.and_then(|i| self.objects.get(i));
+            if let Some(object) = target {
+                let position = object.model_matrix().transform_point3(Vec3::ZERO);
+                trail.push(position, trail.color);
+            }
+        }
+    }
 }
 pub struct Camera {
     pub position: Vec3,
@@ -46,10 +196,48 @@ impl Camera {
     }
 
     pub fn view_matrix(&self) -> Mat4 {
-        Mat4::look_at_rh(self.position, self.target, self.up)
+        camera::rh::view::look_at_mat4(self.position, self.target, self.up)
     }
 
     pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
-        Mat4::perspective_rh_gl(self.fov, aspect_ratio, self.near, self.far)
+        camera::rh::proj::opengl::perspective(self.fov, aspect_ratio, self.near, self.far)
+    }
+
+    /// луч из камеры через точку `(x, y)` кадра `width` x `height` (пиксели от левого верхнего
+    /// угла, как у курсора); начинается на ближней плоскости
+    pub fn ray_from_pixel(&self, x: f32, y: f32, width: f32, height: f32) -> Ray {
+        let ndc_x = 2.0 * x / width - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / height;
+        let inverse = (self.projection_matrix(width / height) * self.view_matrix()).inverse();
+        let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, -1.0));
+        let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 1.0));
+        Ray::new(near, far - near)
+    }
+
+    /// пирамида видимости в мировых координатах
+    pub fn frustum(&self, aspect_ratio: f32) -> Frustum {
+        Frustum::from_view_proj(self.projection_matrix(aspect_ratio) * self.view_matrix())
+    }
+
+    /// углы участка пирамиды видимости между расстояниями `near` и `far` вдоль взгляда:
+    /// сначала четыре ближних, затем четыре дальних
+    pub fn frustum_corners(&self, aspect_ratio: f32, near: f32, far: f32) -> [Vec3; 8] {
+        let forward = (self.target - self.position).normalize_or_zero();
+        let right = forward.cross(self.up).normalize_or_zero();
+        let up = right.cross(forward);
+        let tan = (self.fov / 2.0).tan();
+        let corner = |i: usize| {
+            let distance = if i < 4 { near } else { far };
+            let half_height = distance * tan;
+            let half_width = half_height * aspect_ratio;
+            let x = if i & 1 == 0 { -half_width } else { half_width };
+            let y = if i & 2 == 0 {
+                -half_height
+            } else {
+                half_height
+            };
+            self.position + forward * distance + right * x + up * y
+        };
+        std::array::from_fn(corner)
     }
 }
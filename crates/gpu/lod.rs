@@ -0,0 +1,24 @@
+use crate::{BoundingSphere, Camera, LodLevel};
+
+/// Диаметр сферы на экране в долях высоты кадра (1.0 — во весь экран)
+pub fn screen_size(camera: &Camera, sphere: &BoundingSphere) -> f32 {
+    let distance = camera.position.distance(sphere.center);
+    if distance <= sphere.radius {
+        return f32::INFINITY;
+    }
+    sphere.radius / (distance * (camera.fov / 2.0).tan())
+}
+
+/// Уровень детализации для размера на экране: 0 — основной меш, k — `lods[k - 1]`.
+/// Переход к соседнему уровню — только когда размер уходит за порог дальше, чем на
+/// долю `hysteresis`, поэтому объект на границе не мигает между уровнями.
+pub fn select_lod(lods: &[LodLevel], screen_size: f32, current: usize, hysteresis: f32) -> usize {
+    let mut level = current.min(lods.len());
+    while level < lods.len() && screen_size < lods[level].screen_size * (1.0 - hysteresis) {
+        level += 1;
+    }
+    while level > 0 && screen_size > lods[level - 1].screen_size * (1.0 + hysteresis) {
+        level -= 1;
+    }
+    level
+}
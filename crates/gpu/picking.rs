@@ -0,0 +1,327 @@
+use std::{num::NonZeroU32, sync::mpsc};
+
+use glam::{Mat4, Vec3};
+use utilities::{bounds::Frustum, traits::Object};
+
+use crate::{
+    Instance, Mesh, Scene, Topology, Vertex,
+    buffer_cache::{InstanceBuffers, MeshCache, UniformPool},
+    shaders::ID_SHADER,
+};
+
+/// Что оказалось под пикселем в `Renderer::pick`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pick {
+    /// индекс в `Scene::objects`
+    Object(usize),
+    /// индекс группы в `Scene::instanced` и номер экземпляра в ней
+    Instance { object: usize, instance: usize },
+}
+
+/// Буфер идентификаторов: треугольные объекты и экземпляры рисуются номером вместо цвета.
+/// Проекция растягивает выбранный пиксель на цель 1×1, поэтому растеризуется только он,
+/// а меши берутся из кэша рендерера. Точный по пикселю, но ждёт GPU — только по щелчку.
+pub(crate) struct IdPass {
+    pipeline: wgpu::RenderPipeline,
+    instanced_pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    ids: wgpu::Texture,
+    depth: wgpu::Texture,
+    readback: wgpu::Buffer,
+    uniforms: UniformPool,
+    instance_buffers: InstanceBuffers,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct IdUniforms {
+    mvp: [[f32; 4]; 4],
+    id: u32,
+    _padding: [u32; 3],
+}
+
+impl IdUniforms {
+    fn as_byte_slice(uniforms: &[IdUniforms]) -> &[u8] {
+        let len = std::mem::size_of_val(uniforms);
+        unsafe { std::slice::from_raw_parts(uniforms.as_ptr() as *const u8, len) }
+    }
+}
+
+/// матрица, растягивающая пиксель `(x, y)` кадра `width × height` на весь NDC:
+/// применяется поверх view_proj, глубину не меняет
+pub(crate) fn pixel_matrix(x: u32, y: u32, width: u32, height: u32) -> Mat4 {
+    let (width, height) = (width as f32, height as f32);
+    // центр пикселя в NDC; y в NDC направлен вверх
+    let center_x = (2.0 * x as f32 + 1.0) / width - 1.0;
+    let center_y = 1.0 - (2.0 * y as f32 + 1.0) / height;
+    // перенос в clip space умножается на w, то есть сдвигает NDC
+    Mat4::from_scale(Vec3::new(width, height, 1.0))
+        * Mat4::from_translation(Vec3::new(-center_x, -center_y, 0.0))
+}
+
+impl IdPass {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+    const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("ID Pass BGL"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ID Pass Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("ID Shader"),
+            source: wgpu::ShaderSource::Wgsl(ID_SHADER.into()),
+        });
+        let pipeline = |label, entry_point, buffers: &[wgpu::VertexBufferLayout]| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point,
+                    buffers,
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: Self::FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: Self::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+            })
+        };
+
+        let texture = |label, format, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        Self {
+            pipeline: pipeline("ID Pipeline", "vs_main", &[Vertex::desc()]),
+            instanced_pipeline: pipeline(
+                "Instanced ID Pipeline",
+                "vs_instanced",
+                &[Vertex::desc(), Instance::desc()],
+            ),
+            layout,
+            ids: texture(
+                "ID Texture",
+                Self::FORMAT,
+                wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            ),
+            depth: texture(
+                "ID Depth Texture",
+                Self::DEPTH_FORMAT,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            ),
+            // строка копирования выравнивается до COPY_BYTES_PER_ROW_ALIGNMENT
+            readback: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("ID Readback Buffer"),
+                size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            uniforms: UniformPool::new("ID Uniform Buffer", std::mem::size_of::<IdUniforms>()),
+            instance_buffers: InstanceBuffers::default(),
+        }
+    }
+
+    /// объект или экземпляр в единственном пикселе цели; `view_proj` растягивает нужный
+    /// пиксель кадра на весь NDC (`pixel_matrix`)
+    pub fn pick(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        meshes: &mut MeshCache,
+        scene: &Scene,
+        view_proj: Mat4,
+    ) -> Option<Pick> {
+        // всё, что не задевает пиксель, отсекается ещё на CPU
+        let frustum = Frustum::from_view_proj(view_proj);
+        let triangles =
+            |mesh: &Mesh| mesh.topology == Topology::TriangleList && !mesh.indices.is_empty();
+        self.uniforms.reset();
+
+        // номер в буфере: объекты с 1, за ними инстансированные группы
+        let mut objects = Vec::new();
+        for (index, obj) in scene.objects().iter().enumerate() {
+            if !triangles(obj.mesh()) || !frustum.intersects_sphere(&obj.world_bounding_sphere()) {
+                continue;
+            }
+            let uniforms = IdUniforms {
+                mvp: (view_proj * obj.model_matrix()).to_cols_array_2d(),
+                id: index as u32 + 1,
+                _padding: [0; 3],
+            };
+            let slot = self.uniforms.push(
+                device,
+                queue,
+                &self.layout,
+                IdUniforms::as_byte_slice(&[uniforms]),
+            );
+            objects.push((meshes.get(device, obj.mesh()), slot));
+        }
+
+        // экземпляры тоже отсекаются; номер в буфере переводится обратно через `visible`
+        let mut groups = Vec::new();
+        for (index, obj) in scene.instanced().iter().enumerate() {
+            if !triangles(obj.mesh()) {
+                continue;
+            }
+            let (visible, instances): (Vec<usize>, Vec<Instance>) = obj
+                .instances()
+                .iter()
+                .enumerate()
+                .filter(|(_, instance)| {
+                    frustum.intersects_sphere(&obj.instance_bounding_sphere(instance))
+                })
+                .map(|(i, instance)| (i, *instance))
+                .unzip();
+            if instances.is_empty() {
+                continue;
+            }
+            let uniforms = IdUniforms {
+                mvp: (view_proj * obj.model_matrix()).to_cols_array_2d(),
+                id: (scene.objects().len() + index) as u32 + 1,
+                _padding: [0; 3],
+            };
+            let slot = self.uniforms.push(
+                device,
+                queue,
+                &self.layout,
+                IdUniforms::as_byte_slice(&[uniforms]),
+            );
+            self.instance_buffers
+                .write(device, queue, groups.len(), &instances);
+            groups.push((index, meshes.get(device, obj.mesh()), slot, visible));
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("ID Encoder"),
+        });
+        {
+            let color_view = self
+                .ids
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            let depth_view = self
+                .depth
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("ID Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            rpass.set_pipeline(&self.pipeline);
+            for (mesh, slot) in &objects {
+                rpass.set_bind_group(0, self.uniforms.bind_group(*slot), &[]);
+                rpass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                rpass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                rpass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            }
+            rpass.set_pipeline(&self.instanced_pipeline);
+            for (group, (_, mesh, slot, visible)) in groups.iter().enumerate() {
+                rpass.set_bind_group(0, self.uniforms.bind_group(*slot), &[]);
+                rpass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                rpass.set_vertex_buffer(1, self.instance_buffers.get(group).slice(..));
+                rpass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                rpass.draw_indexed(0..mesh.index_count, 0, 0..visible.len() as u32);
+            }
+        }
+
+        encoder.copy_texture_to_buffer(
+            self.ids.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback.slice(..8);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver.recv().ok()?.ok()?;
+        let [id, instance] = {
+            let bytes = slice.get_mapped_range();
+            [0, 4].map(|offset| {
+                u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize
+            })
+        };
+        self.readback.unmap();
+
+        let index = id.checked_sub(1)?;
+        match index.checked_sub(scene.objects().len()) {
+            None => Some(Pick::Object(index)),
+            Some(object) => {
+                let (_, _, _, visible) = groups.iter().find(|group| group.0 == object)?;
+                Some(Pick::Instance {
+                    object,
+                    instance: *visible.get(instance)?,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,549 @@
+use std::time::Instant;
+
+use crate::{
+    renderer_error::RendererError,
+    shaders::{FULLSCREEN_VERTEX_SHADER, POST_EFFECT_HEADER},
+    tonemap::HDR_FORMAT,
+};
+
+/// Идентификатор эффекта в стеке постобработки
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PostEffectId(u64);
+
+/// Полноэкранный эффект. `shader` — WGSL с
+/// `@fragment fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32>`;
+/// перед ним подставляется `POST_EFFECT_HEADER` (`input_texture`, `input_sampler`, `frame`).
+/// `params` приходят в `@group(0) @binding(3) var<uniform>`, дополненные нулями до vec4.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostEffect {
+    pub label: String,
+    pub shader: String,
+    pub params: Vec<f32>,
+    pub enabled: bool,
+}
+
+impl PostEffect {
+    pub fn new(label: impl Into<String>, shader: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            shader: shader.into(),
+            params: Vec::new(),
+            enabled: true,
+        }
+    }
+
+    pub fn with_params(mut self, params: &[f32]) -> Self {
+        self.params = params.to_vec();
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// полный текст шейдера с общим началом
+    pub fn source(&self) -> String {
+        format!("{POST_EFFECT_HEADER}\n{}", self.shader)
+    }
+
+    /// параметры, дополненные до целого числа vec4 (минимум один) — содержимое uniform-буфера
+    pub fn params_bytes(&self) -> Vec<u8> {
+        let len = self.params.len().div_ceil(4).max(1) * 4;
+        let mut params = self.params.clone();
+        params.resize(len, 0.0);
+        params
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect()
+    }
+}
+
+/// Эффекты в порядке выполнения с постоянными id: вставка на место, удаление и перенос
+/// не меняют id остальных
+#[derive(Clone, Debug)]
+pub struct PostEffectList<T> {
+    entries: Vec<(PostEffectId, T)>,
+    next_id: u64,
+}
+
+impl<T> Default for PostEffectList<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<T> PostEffectList<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// вставить на место `index` (не дальше конца); возвращает новый id
+    pub fn insert(&mut self, index: usize, value: T) -> PostEffectId {
+        let id = PostEffectId(self.next_id);
+        self.next_id += 1;
+        self.entries
+            .insert(index.min(self.entries.len()), (id, value));
+        id
+    }
+
+    pub fn remove(&mut self, id: PostEffectId) -> Option<T> {
+        let index = self.position(id)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// переставить на место `index` (не дальше конца); false — нет такого id
+    pub fn move_to(&mut self, id: PostEffectId, index: usize) -> bool {
+        let Some(from) = self.position(id) else {
+            return false;
+        };
+        let entry = self.entries.remove(from);
+        self.entries.insert(index.min(self.entries.len()), entry);
+        true
+    }
+
+    pub fn get(&self, id: PostEffectId) -> Option<&T> {
+        self.position(id).map(|index| &self.entries[index].1)
+    }
+
+    pub fn get_mut(&mut self, id: PostEffectId) -> Option<&mut T> {
+        self.position(id).map(|index| &mut self.entries[index].1)
+    }
+
+    /// id в порядке выполнения
+    pub fn ids(&self) -> Vec<PostEffectId> {
+        self.entries.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// значения в порядке выполнения
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entries.iter_mut().map(|(_, value)| value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// список с теми же id и порядком; отброшенные `f` значения выпадают
+    pub fn filter_map<U>(&self, mut f: impl FnMut(&T) -> Option<U>) -> PostEffectList<U> {
+        PostEffectList {
+            entries: self
+                .entries
+                .iter()
+                .filter_map(|(id, value)| Some((*id, f(value)?)))
+                .collect(),
+            next_id: self.next_id,
+        }
+    }
+
+    fn position(&self, id: PostEffectId) -> Option<usize> {
+        self.entries.iter().position(|(entry, _)| *entry == id)
+    }
+}
+
+/// `FrameInfo` в WGSL
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct FrameInfo {
+    resolution: [f32; 2],
+    time: f32,
+    delta_time: f32,
+}
+
+impl FrameInfo {
+    fn as_byte_slice(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
+
+/// Собранный эффект: пайплайн, буфер параметров и bind group на каждый возможный вход
+struct EffectPass {
+    effect: PostEffect,
+    pipeline: wgpu::RenderPipeline,
+    params: wgpu::Buffer,
+    // вход: HDR-кадр, первая и вторая промежуточные текстуры
+    bind_groups: [wgpu::BindGroup; 3],
+}
+
+/// Стек постобработки: упорядоченный список эффектов над HDR-кадром после bloom и до
+/// тональной компрессии. Каждый эффект читает результат предыдущего (две текстуры по очереди)
+pub(crate) struct PostProcessStack {
+    layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    vs_module: wgpu::ShaderModule,
+    sampler: wgpu::Sampler,
+    frame: wgpu::Buffer,
+    // промежуточные текстуры размером с кадр
+    targets: [(wgpu::Texture, wgpu::TextureView); 2],
+    effects: PostEffectList<EffectPass>,
+    start: Instant,
+}
+
+impl PostProcessStack {
+    pub fn new(device: &wgpu::Device, size: (u32, u32)) -> Self {
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Effect BGL"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                uniform_entry(2),
+                uniform_entry(3),
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Effect Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fullscreen Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(FULLSCREEN_VERTEX_SHADER.into()),
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Effect Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let frame = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Post Effect Frame Info"),
+            size: std::mem::size_of::<FrameInfo>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            layout,
+            pipeline_layout,
+            vs_module,
+            sampler,
+            frame,
+            targets: [target(device, size), target(device, size)],
+            effects: PostEffectList::new(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Тот же стек на новом устройстве (после его потери): эффекты собираются заново
+    pub fn recreate(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        hdr: &wgpu::TextureView,
+        size: (u32, u32),
+    ) -> Self {
+        let mut stack = Self::new(device, size);
+        stack.start = self.start;
+        // шейдеры уже проверены при добавлении
+        let effects = self
+            .effects
+            .filter_map(|pass| stack.build(device, queue, hdr, pass.effect.clone()).ok());
+        stack.effects = effects;
+        stack
+    }
+
+    /// новый HDR-кадр после ресайза или смены MSAA
+    pub fn set_input(&mut self, device: &wgpu::Device, hdr: &wgpu::TextureView, size: (u32, u32)) {
+        self.targets = [target(device, size), target(device, size)];
+        let bind_groups: Vec<_> = self
+            .effects
+            .iter()
+            .map(|pass| self.bind_groups(device, hdr, &pass.params))
+            .collect();
+        for (pass, bind_groups) in self.effects.iter_mut().zip(bind_groups) {
+            pass.bind_groups = bind_groups;
+        }
+    }
+
+    /// Собрать эффект и вставить его на место `index` (не дальше конца стека)
+    pub fn insert(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        hdr: &wgpu::TextureView,
+        index: usize,
+        effect: PostEffect,
+    ) -> Result<PostEffectId, RendererError> {
+        let pass = self.build(device, queue, hdr, effect)?;
+        Ok(self.effects.insert(index, pass))
+    }
+
+    pub fn remove(&mut self, id: PostEffectId) -> Option<PostEffect> {
+        self.effects.remove(id).map(|pass| pass.effect)
+    }
+
+    /// переставить эффект на место `index`; false — нет такого эффекта
+    pub fn move_to(&mut self, id: PostEffectId, index: usize) -> bool {
+        self.effects.move_to(id, index)
+    }
+
+    pub fn get(&self, id: PostEffectId) -> Option<&PostEffect> {
+        self.effects.get(id).map(|pass| &pass.effect)
+    }
+
+    /// эффекты в порядке выполнения
+    pub fn ids(&self) -> Vec<PostEffectId> {
+        self.effects.ids()
+    }
+
+    pub fn set_enabled(&mut self, id: PostEffectId, enabled: bool) -> bool {
+        match self.effects.get_mut(id) {
+            Some(pass) => {
+                pass.effect.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Новые параметры эффекта; буфер пересоздаётся, только если их стало больше
+    pub fn set_params(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        hdr: &wgpu::TextureView,
+        id: PostEffectId,
+        params: &[f32],
+    ) -> bool {
+        let Some(pass) = self.effects.get(id) else {
+            return false;
+        };
+        let effect = pass.effect.clone().with_params(params);
+        let bytes = effect.params_bytes();
+        let resized = (bytes.len() as wgpu::BufferAddress > pass.params.size()).then(|| {
+            let buffer = params_buffer(device, &effect.label, bytes.len());
+            let bind_groups = self.bind_groups(device, hdr, &buffer);
+            (buffer, bind_groups)
+        });
+        let pass = self.effects.get_mut(id).expect("effect exists");
+        pass.effect = effect;
+        if let Some((buffer, bind_groups)) = resized {
+            pass.params = buffer;
+            pass.bind_groups = bind_groups;
+        }
+        queue.write_buffer(&pass.params, 0, &bytes);
+        true
+    }
+
+    /// Добавить включённые эффекты в энкодер; результат копируется обратно в `hdr`
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        hdr: &wgpu::Texture,
+        delta_time: f32,
+    ) {
+        let enabled: Vec<_> = self
+            .effects
+            .iter()
+            .filter(|pass| pass.effect.enabled)
+            .collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let size = hdr.size();
+        let frame = FrameInfo {
+            resolution: [size.width as f32, size.height as f32],
+            time: self.start.elapsed().as_secs_f32(),
+            delta_time,
+        };
+        queue.write_buffer(&self.frame, 0, frame.as_byte_slice());
+
+        // вход первого эффекта — HDR-кадр, дальше промежуточные текстуры по очереди
+        let mut input = 0;
+        let mut output = 0;
+        for pass in enabled {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&pass.effect.label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.targets[output].1,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            rpass.set_pipeline(&pass.pipeline);
+            rpass.set_bind_group(0, &pass.bind_groups[input], &[]);
+            rpass.draw(0..3, 0..1);
+            input = output + 1;
+            output = 1 - output;
+        }
+        encoder.copy_texture_to_texture(
+            self.targets[input - 1].0.as_image_copy(),
+            hdr.as_image_copy(),
+            size,
+        );
+    }
+
+    /// Пайплайн эффекта; ошибки WGSL и несовпадение с раскладкой возвращаются, а не паникуют
+    fn build(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        hdr: &wgpu::TextureView,
+        effect: PostEffect,
+    ) -> Result<EffectPass, RendererError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&effect.label),
+            source: wgpu::ShaderSource::Wgsl(effect.source().into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&effect.label),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &self.vs_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fs_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(RendererError::InvalidShader {
+                label: effect.label,
+                message: match error {
+                    wgpu::Error::Validation { description, .. } => description,
+                    other => other.to_string(),
+                },
+            });
+        }
+
+        let bytes = effect.params_bytes();
+        let params = params_buffer(device, &effect.label, bytes.len());
+        queue.write_buffer(&params, 0, &bytes);
+        let bind_groups = self.bind_groups(device, hdr, &params);
+        Ok(EffectPass {
+            effect,
+            pipeline,
+            params,
+            bind_groups,
+        })
+    }
+
+    fn bind_groups(
+        &self,
+        device: &wgpu::Device,
+        hdr: &wgpu::TextureView,
+        params: &wgpu::Buffer,
+    ) -> [wgpu::BindGroup; 3] {
+        [hdr, &self.targets[0].1, &self.targets[1].1].map(|input| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post Effect Bind Group"),
+                layout: &self.layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(input),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: self.frame.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: params.as_entire_binding(),
+                    },
+                ],
+            })
+        })
+    }
+}
+
+fn uniform_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+/// Буфер параметров не меньше 256 байт: структура в шейдере может быть больше
+/// переданных значений, а слишком короткий буфер — ошибка при отрисовке
+fn params_buffer(device: &wgpu::Device, label: &str, size: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: size.max(256) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// промежуточная HDR-текстура размером с кадр
+fn target(
+    device: &wgpu::Device,
+    (width, height): (u32, u32),
+) -> (wgpu::Texture, wgpu::TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Post Effect Target"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: HDR_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    (texture, view)
+}
@@ -1,29 +1,74 @@
 use crate::{
-    Camera, Scene, Vertex,
-    shaders::{FRAGMENT_SHADER, VERTEX_SHADER},
+    BoundingSphere, Camera, DirectionalLight, Frustum, Instance, Mesh, Scene, Texture, Topology,
+    TrailStyle, Vertex,
+    bloom::{BloomConfig, BloomPass},
+    buffer_cache::{GpuMesh, GpuVertices, InstanceBuffers, MeshCache, UniformPool},
+    config::{
+        Capabilities, ConfigFallback, FALLBACK_DEPTH_FORMAT, RendererConfig, present_mode,
+        present_mode_supported,
+    },
+    debug_draw::DebugDraw,
+    debug_view::{DebugView, Shading},
+    environment::{self, Environment, GpuEnvironment},
+    lod,
+    picking::{self, IdPass, Pick},
+    post_process::{PostEffect, PostEffectId, PostProcessStack},
+    renderer_error::RendererError,
+    shaders::{
+        DEBUG_FRAGMENT_SHADER, FRAGMENT_SHADER, INSTANCED_SHADOW_VERTEX_SHADER,
+        INSTANCED_VERTEX_SHADER, LINE_VERTEX_SHADER, POINT_FRAGMENT_SHADER, POINT_VERTEX_SHADER,
+        RIBBON_FRAGMENT_SHADER, SHADOW_VERTEX_SHADER, SKYBOX_SHADER, TEXTURED_FRAGMENT_SHADER,
+        UNLIT_FRAGMENT_SHADER, VERTEX_SHADER,
+    },
+    shadow::{self, Cascade, MAX_CASCADES, ShadowConfig, ShadowMap},
+    texture::GpuTexture,
+    tonemap::{HDR_FORMAT, ToneMapPass, ToneMapping},
+    transparency::{self, OitPass, Transparency},
+};
+use glam::{Mat3, Mat4, Vec3};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    iter,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
 };
-use glam::Mat4;
-use std::iter;
 use utilities::traits::Object;
 use wgpu::util::DeviceExt;
 use winit::window::Window;
 
-/// Uniforms для передачи MVP
+/// Uniforms объекта: MVP, model и матрица нормалей для освещения в мировых координатах
 #[repr(C)]
 #[derive(Copy, Clone)]
 struct Uniforms {
     mvp: [[f32; 4]; 4],
+    model: [[f32; 4]; 4],
+    normal_matrix: [[f32; 4]; 4],
+    receive_shadows: f32,
+    opacity: f32,
+    // выравнивание структуры WGSL до 16 байт
+    _padding: [f32; 2],
 }
 impl Uniforms {
     #[allow(dead_code)]
     fn new() -> Self {
-        Self {
-            mvp: Mat4::IDENTITY.to_cols_array_2d(),
-        }
+        Self::from_mat4(Mat4::IDENTITY)
     }
+    /// только MVP (геометрия уже в мировых координатах или без освещения)
     fn from_mat4(m: Mat4) -> Self {
+        Self::object(m, Mat4::IDENTITY, false, 1.0)
+    }
+    fn object(mvp: Mat4, model: Mat4, receive_shadows: bool, opacity: f32) -> Self {
         Self {
-            mvp: m.to_cols_array_2d(),
+            mvp: mvp.to_cols_array_2d(),
+            model: model.to_cols_array_2d(),
+            normal_matrix: model.inverse().transpose().to_cols_array_2d(),
+            receive_shadows: if receive_shadows { 1.0 } else { 0.0 },
+            opacity,
+            _padding: [0.0; 2],
         }
     }
     fn as_byte_slice(uniforms: &[Uniforms]) -> &[u8] {
@@ -32,47 +77,215 @@ impl Uniforms {
     }
 }
 
+/// Uniforms линий и точек: MVP, размер вьюпорта, толщина/размер в пикселях и непрозрачность
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct SizedUniforms {
+    mvp: [[f32; 4]; 4],
+    viewport: [f32; 2],
+    size: f32,
+    opacity: f32,
+}
+impl SizedUniforms {
+    fn as_byte_slice(uniforms: &[SizedUniforms]) -> &[u8] {
+        let len = std::mem::size_of_val(uniforms);
+        unsafe { std::slice::from_raw_parts(uniforms.as_ptr() as *const u8, len) }
+    }
+}
+
+/// Параметры отладочной заливки объекта (group 1)
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct DebugUniforms {
+    color: [f32; 4],
+    near: f32,
+    far: f32,
+    mode: u32,
+    _padding: u32,
+}
+impl DebugUniforms {
+    fn as_byte_slice(uniforms: &[DebugUniforms]) -> &[u8] {
+        let len = std::mem::size_of_val(uniforms);
+        unsafe { std::slice::from_raw_parts(uniforms.as_ptr() as *const u8, len) }
+    }
+}
+
+/// Направленный свет и каскады теней кадра (group 1 освещённых пайплайнов)
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct LightUniforms {
+    // xyz — направление на источник
+    direction: [f32; 4],
+    color: [f32; 4],
+    camera_position: [f32; 4],
+    camera_forward: [f32; 4],
+    cascades: [[[f32; 4]; 4]; MAX_CASCADES],
+    splits: [f32; 4],
+    texel_sizes: [f32; 4],
+    // число каскадов, смещение глубины, смещение по нормали, радиус PCF
+    shadow: [f32; 4],
+    // сферические гармоники облучённости от окружения (или постоянный фон)
+    irradiance: [[f32; 4]; 9],
+}
+impl LightUniforms {
+    fn new(
+        light: &DirectionalLight,
+        environment: Option<&Environment>,
+        camera: &Camera,
+        cascades: &[Cascade],
+        shadows: &ShadowConfig,
+    ) -> Self {
+        let irradiance = match environment {
+            Some(environment) => environment.irradiance(),
+            None => environment::constant_irradiance(light.ambient),
+        };
+        let mut uniforms = Self {
+            direction: light.direction.extend(0.0).to_array(),
+            color: [light.color[0], light.color[1], light.color[2], 1.0],
+            camera_position: camera.position.extend(1.0).to_array(),
+            camera_forward: (camera.target - camera.position)
+                .normalize_or_zero()
+                .extend(0.0)
+                .to_array(),
+            cascades: [Mat4::IDENTITY.to_cols_array_2d(); MAX_CASCADES],
+            splits: [0.0; 4],
+            texel_sizes: [0.0; 4],
+            shadow: [
+                cascades.len() as f32,
+                shadows.depth_bias,
+                shadows.normal_offset,
+                shadows.pcf_radius as f32,
+            ],
+            irradiance: irradiance.map(|c| c.extend(0.0).to_array()),
+        };
+        for (i, cascade) in cascades.iter().enumerate() {
+            uniforms.cascades[i] = cascade.view_proj.to_cols_array_2d();
+            uniforms.splits[i] = cascade.split;
+            uniforms.texel_sizes[i] = cascade.texel_size;
+        }
+        uniforms
+    }
+    fn as_byte_slice(uniforms: &[LightUniforms]) -> &[u8] {
+        let len = std::mem::size_of_val(uniforms);
+        unsafe { std::slice::from_raw_parts(uniforms.as_ptr() as *const u8, len) }
+    }
+}
+
+/// Раскладки bind group всех пайплайнов
+struct BindGroupLayouts {
+    // MVP объекта или линий (group 0)
+    uniform: wgpu::BindGroupLayout,
+    // свет и карта теней (group 1 освещённых пайплайнов)
+    light: wgpu::BindGroupLayout,
+    // albedo (group 2 текстурного пайплайна)
+    texture: wgpu::BindGroupLayout,
+    // параметры отладочной заливки (group 1 отладочного пайплайна)
+    debug: wgpu::BindGroupLayout,
+    // кубическая карта окружения (group 1 пайплайна неба)
+    environment: wgpu::BindGroupLayout,
+}
+
+/// Ошибки устройства, пойманные `on_uncaptured_error`; `render` возвращает их как `RendererError`
+#[derive(Default)]
+struct DeviceErrors {
+    lost: AtomicBool,
+    // первая ошибка с прошлой проверки; следующие обычно её следствия
+    error: Mutex<Option<RendererError>>,
+}
+
+impl DeviceErrors {
+    fn push(&self, error: wgpu::Error) {
+        match RendererError::from_device(error) {
+            RendererError::DeviceLost => self.lost.store(true, Ordering::Relaxed),
+            error => {
+                let mut first = self.error.lock().unwrap_or_else(|e| e.into_inner());
+                first.get_or_insert(error);
+            }
+        }
+    }
+
+    /// забрать накопленную ошибку; потеря устройства важнее остальных
+    fn take(&self) -> Option<RendererError> {
+        let error = self.error.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if self.lost.swap(false, Ordering::Relaxed) {
+            Some(RendererError::DeviceLost)
+        } else {
+            error
+        }
+    }
+}
+
 pub struct Renderer {
     surface: wgpu::Surface,
+    // адаптер хранится для пересоздания устройства после его потери
+    adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
+    device_errors: Arc<DeviceErrors>,
     config: wgpu::SurfaceConfiguration,
-    // Пайплайн и биндинги
-    render_pipeline: wgpu::RenderPipeline,
-    uniform_bind_group_layout: wgpu::BindGroupLayout,
-    // MSAA и глубина
-    msaa_texture_view: wgpu::TextureView,
-    depth_texture_view: wgpu::TextureView,
-    sample_count: u32,
+    // фактические настройки после проверки возможностей адаптера и сделанные замены
+    settings: RendererConfig,
+    fallbacks: Vec<ConfigFallback>,
+    supported_sample_counts: Vec<u32>,
+    present_modes: Vec<wgpu::PresentMode>,
+    // Пайплайны и биндинги
+    pipelines: Pipelines,
+    layouts: BindGroupLayouts,
+    shadow_map: ShadowMap,
+    // Загруженные текстуры по id
+    textures: HashMap<u64, GpuTexture>,
+    // буферы мешей и переиспользуемые между кадрами uniform-буферы и экземпляры
+    meshes: MeshCache,
+    uniforms: UniformPool,
+    debug_uniforms: UniformPool,
+    instance_buffers: InstanceBuffers,
+    // окружение сцены на GPU; загружается заново при смене id
+    environment: Option<GpuEnvironment>,
+    // HDR-кадр, MSAA и глубина
+    attachments: Attachments,
+    bloom: BloomPass,
+    post_process: PostProcessStack,
+    tone_map: ToneMapPass,
+    // цели WBOIT; нет в режиме `Transparency::Sorted`
+    oit: Option<OitPass>,
+    culling_stats: CullingStats,
+    // текущий уровень детализации объектов сцены (по индексу) — для гистерезиса
+    lod_levels: Vec<usize>,
+    // индекс выделенного объекта сцены
+    selection: Option<usize>,
+    id_pass: IdPass,
+    last_frame: Instant,
+    // накопленная за кадр отладочная графика
+    debug: DebugDraw,
+    debug_view: DebugView,
 }
 
 impl Renderer {
-    pub async fn new(window: &Window) -> Self {
+    /// рендерер с настройками по умолчанию (`RendererConfig::default`)
+    pub async fn new(window: &Window) -> Result<Self, RendererError> {
+        Self::with_config(window, RendererConfig::default()).await
+    }
+
+    pub async fn with_config(
+        window: &Window,
+        settings: RendererConfig,
+    ) -> Result<Self, RendererError> {
         let size = window.inner_size();
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
+            backends: settings.backends,
             dx12_shader_compiler: Default::default(),
         });
-        let surface = unsafe { instance.create_surface(window) }.unwrap();
+        let surface =
+            unsafe { instance.create_surface(window) }.map_err(RendererError::CreateSurface)?;
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
+                power_preference: settings.power_preference,
                 compatible_surface: Some(&surface),
                 force_fallback_adapter: false,
             })
             .await
-            .expect("No adapter");
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("Device"),
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                },
-                None,
-            )
-            .await
-            .expect("Failed to create device");
+            .ok_or(RendererError::NoAdapter)?;
+        let (device, queue, device_errors) = request_device(&adapter).await?;
         // capabilities / format
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps
@@ -80,263 +293,734 @@ impl Renderer {
             .iter()
             .copied()
             .find(|f| f.describe().srgb)
-            .unwrap_or(surface_caps.formats[0]);
+            .or(surface_caps.formats.first().copied())
+            .ok_or(RendererError::IncompatibleSurface)?;
+
+        // Проверка настроек по возможностям адаптера
+        let depth_info = settings.depth_format.describe();
+        let depth_format_supported = depth_info.sample_type == wgpu::TextureSampleType::Depth
+            && device.features().contains(depth_info.required_features);
+        let adapter_formats = device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+        let format_flags = |format: wgpu::TextureFormat| {
+            if !adapter_formats {
+                format.describe().guaranteed_format_features.flags
+            } else {
+                adapter.get_texture_format_features(format).flags
+            }
+        };
+        let (color_flags, depth_flags) = (
+            format_flags(HDR_FORMAT),
+            format_flags(if depth_format_supported {
+                settings.depth_format
+            } else {
+                FALLBACK_DEPTH_FORMAT
+            }),
+        );
+        let supported_sample_counts: Vec<u32> = [1, 2, 4, 8]
+            .into_iter()
+            .filter(|&count| {
+                count == 1
+                    || (color_flags.sample_count_supported(count)
+                        && color_flags
+                            .contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE)
+                        && depth_flags.sample_count_supported(count))
+            })
+            .collect();
+        let (settings, fallbacks) = settings.resolve(&Capabilities {
+            depth_format_supported,
+            sample_counts: supported_sample_counts.clone(),
+            present_modes: surface_caps.present_modes.clone(),
+            max_texture_dimension: device.limits().max_texture_dimension_2d,
+        });
+        for fallback in &fallbacks {
+            log::warn!("{fallback}");
+        }
+
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: settings.present_mode,
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
             view_formats: vec![],
         };
         surface.configure(&device, &config);
 
-        // Настройка MSAA
-        let sample_count = 4; // 4x MSAA
+        let targets = Targets {
+            format: HDR_FORMAT,
+            depth_format: settings.depth_format,
+            sample_count: settings.sample_count,
+        };
+        let attachments = create_attachments(&device, &config, targets);
+        let bloom = BloomPass::new(&device, &attachments.hdr, (config.width, config.height));
+        let post_process = PostProcessStack::new(&device, (config.width, config.height));
+        let tone_map = ToneMapPass::new(&device, config.format, &attachments.hdr);
+        let oit = oit_pass(
+            &device,
+            targets,
+            settings.transparency,
+            (config.width, config.height),
+        );
 
-        // Создание мультисемплированной текстуры
-        let msaa_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("MSAA Texture"),
-            size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: config.format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
-        let msaa_texture_view = msaa_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let layouts = bind_group_layouts(&device);
 
-        // Создание текстуры глубины
-        let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("Depth Texture"),
-            size: wgpu::Extent3d {
-                width: config.width,
-                height: config.height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
-        let depth_texture_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let pipelines = Pipelines::new(&device, &layouts, targets);
+        let id_pass = IdPass::new(&device);
 
-        // uniform bind group layout (group 0 binding 0)
-        let uniform_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Uniform BGL"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            });
-        // pipeline layout uses uniform layout
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Pipeline Layout"),
-            bind_group_layouts: &[&uniform_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-        // shader modules
-        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Vertex Shader"),
-            source: wgpu::ShaderSource::Wgsl(VERTEX_SHADER.into()),
-        });
-        let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Fragment Shader"),
-            source: wgpu::ShaderSource::Wgsl(FRAGMENT_SHADER.into()),
-        });
-        // render pipeline с поддержкой MSAA и глубины
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &vs_module,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &fs_module,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                cull_mode: Some(wgpu::Face::Back),
-                ..Default::default()
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: sample_count,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-        });
+        let shadow_map = ShadowMap::new(&device, &settings.shadows);
 
-        Self {
+        Ok(Self {
             surface,
+            adapter,
             device,
             queue,
+            device_errors,
             config,
-            render_pipeline,
-            uniform_bind_group_layout,
-            msaa_texture_view,
-            depth_texture_view,
-            sample_count,
+            settings,
+            fallbacks,
+            supported_sample_counts,
+            present_modes: surface_caps.present_modes,
+            pipelines,
+            layouts,
+            shadow_map,
+            textures: HashMap::new(),
+            meshes: MeshCache::default(),
+            uniforms: uniform_pool(),
+            debug_uniforms: debug_uniform_pool(),
+            instance_buffers: InstanceBuffers::default(),
+            environment: None,
+            attachments,
+            bloom,
+            post_process,
+            tone_map,
+            oit,
+            culling_stats: CullingStats::default(),
+            lod_levels: Vec::new(),
+            selection: None,
+            id_pass,
+            last_frame: Instant::now(),
+            debug: DebugDraw::new(),
+            debug_view: DebugView::default(),
+        })
+    }
+
+    /// Пересоздать устройство и всё, что ему принадлежит, после его потери.
+    /// Текстуры сцены загружаются заново, буферы мешей — по мере отрисовки.
+    fn recover_device(&mut self, scene: &Scene) -> Result<(), RendererError> {
+        let (device, queue, device_errors) = pollster::block_on(request_device(&self.adapter))?;
+        self.layouts = bind_group_layouts(&device);
+        self.device = device;
+        self.queue = queue;
+        self.device_errors = device_errors;
+        self.surface.configure(&self.device, &self.config);
+
+        let targets = self.targets();
+        self.pipelines = Pipelines::new(&self.device, &self.layouts, targets);
+        self.id_pass = IdPass::new(&self.device);
+        self.attachments = create_attachments(&self.device, &self.config, targets);
+        self.bloom = BloomPass::new(&self.device, &self.attachments.hdr, self.size());
+        self.post_process = self.post_process.recreate(
+            &self.device,
+            &self.queue,
+            &self.attachments.hdr,
+            self.size(),
+        );
+        self.tone_map = ToneMapPass::new(&self.device, self.config.format, &self.attachments.hdr);
+        self.oit = oit_pass(
+            &self.device,
+            targets,
+            self.settings.transparency,
+            self.size(),
+        );
+        self.shadow_map = ShadowMap::new(&self.device, &self.settings.shadows);
+
+        self.textures.clear();
+        self.meshes = MeshCache::default();
+        self.uniforms = uniform_pool();
+        self.debug_uniforms = debug_uniform_pool();
+        self.instance_buffers = InstanceBuffers::default();
+        for texture in scene.objects().iter().filter_map(|obj| obj.texture()) {
+            self.upload_texture(texture);
+        }
+        self.environment = None;
+        Ok(())
+    }
+
+    /// загрузить текстуру на GPU, если её ещё нет; возвращает id
+    fn upload_texture(&mut self, texture: &Texture) -> u64 {
+        self.textures.entry(texture.id()).or_insert_with(|| {
+            GpuTexture::upload(&self.device, &self.queue, &self.layouts.texture, texture)
+        });
+        texture.id()
+    }
+
+    /// загрузить кубическую карту окружения, если сцена сменила его
+    fn upload_environment(&mut self, environment: &Environment) {
+        if self.environment.as_ref().map(|gpu| gpu.id) != Some(environment.id()) {
+            self.environment = Some(GpuEnvironment::upload(
+                &self.device,
+                &self.queue,
+                &self.layouts.environment,
+                environment,
+            ));
+        }
+    }
+
+    /// фактические настройки (после замены неподдерживаемых значений)
+    pub fn settings(&self) -> &RendererConfig {
+        &self.settings
+    }
+
+    /// настройки из `RendererConfig`, заменённые при создании из-за возможностей адаптера
+    pub fn fallbacks(&self) -> &[ConfigFallback] {
+        &self.fallbacks
+    }
+
+    /// допустимые для `set_sample_count` значения, по возрастанию
+    pub fn supported_sample_counts(&self) -> &[u32] {
+        &self.supported_sample_counts
+    }
+
+    /// сменить MSAA на лету: пересобирает пайплайны и вложения.
+    /// Неподдерживаемое значение игнорируется, тогда возвращает false.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> bool {
+        if !self.supported_sample_counts.contains(&sample_count) {
+            return false;
+        }
+        if sample_count != self.settings.sample_count {
+            self.settings.sample_count = sample_count;
+            let targets = self.targets();
+            self.pipelines = Pipelines::new(&self.device, &self.layouts, targets);
+            self.rebuild_attachments();
+        }
+        true
+    }
+
+    /// сменить режим презентации на лету; неподдерживаемый игнорируется (возвращает false)
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) -> bool {
+        if !present_mode_supported(present_mode, &self.present_modes) {
+            return false;
+        }
+        self.settings.present_mode = present_mode;
+        self.config.present_mode = present_mode;
+        self.surface.configure(&self.device, &self.config);
+        true
+    }
+
+    /// сменить настройки теней на лету; карта пересоздаётся при смене разрешения или числа каскадов
+    pub fn set_shadow_config(&mut self, mut shadows: ShadowConfig) {
+        shadows.resolution = shadows
+            .resolution
+            .clamp(1, self.device.limits().max_texture_dimension_2d);
+        let old = self.settings.shadows;
+        self.settings.shadows = shadows;
+        if old.resolution != shadows.resolution || old.cascade_count() != shadows.cascade_count() {
+            self.shadow_map = ShadowMap::new(&self.device, &shadows);
+        }
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.set_present_mode(present_mode(vsync));
+    }
+
+    pub fn set_clear_color(&mut self, clear_color: wgpu::Color) {
+        self.settings.clear_color = clear_color;
+    }
+
+    /// оператор и экспозиция тональной компрессии; действуют со следующего `render`
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping) {
+        self.settings.tone_mapping = tone_mapping;
+    }
+
+    /// порог, интенсивность и радиус bloom; действуют со следующего `render`
+    pub fn set_bloom(&mut self, bloom: BloomConfig) {
+        self.settings.bloom = bloom;
+    }
+
+    /// режим смешивания полупрозрачных объектов; действует со следующего `render`
+    pub fn set_transparency(&mut self, transparency: Transparency) {
+        if transparency != self.settings.transparency {
+            self.settings.transparency = transparency;
+            self.oit = oit_pass(&self.device, self.targets(), transparency, self.size());
+        }
+    }
+
+    /// отсекать объекты и экземпляры вне пирамиды камеры; действует со следующего `render`
+    pub fn set_frustum_culling(&mut self, frustum_culling: bool) {
+        self.settings.frustum_culling = frustum_culling;
+    }
+
+    /// сколько объектов и экземпляров прошло отсечение в последнем кадре
+    pub fn culling_stats(&self) -> CullingStats {
+        self.culling_stats
+    }
+
+    /// выделить объект сцены (индекс, как у `Scene::add_object`); рисуется каркасом поверх сцены
+    pub fn set_selection(&mut self, selection: Option<usize>) {
+        self.selection = selection;
+    }
+
+    pub fn selection(&self) -> Option<usize> {
+        self.selection
+    }
+
+    /// Объект или экземпляр сцены под пикселем `(x, y)` (от левого верхнего угла) по буферу
+    /// идентификаторов на GPU — альтернатива `Scene::raycast`, точная до пикселя, но
+    /// ждущая GPU. Учитываются только треугольные объекты с основным мешом.
+    pub fn pick(&mut self, scene: &Scene, camera: &Camera, x: u32, y: u32) -> Option<Pick> {
+        let (width, height) = self.size();
+        if x >= width || y >= height {
+            return None;
+        }
+        let aspect = width as f32 / height as f32;
+        let view_proj = picking::pixel_matrix(x, y, width, height)
+            * camera.projection_matrix(aspect)
+            * camera.view_matrix();
+        self.id_pass.pick(
+            &self.device,
+            &self.queue,
+            &mut self.meshes,
+            scene,
+            view_proj,
+        )
+    }
+
+    /// добавить эффект в конец стека постобработки; ошибка — если шейдер не собрался
+    pub fn add_post_effect(&mut self, effect: PostEffect) -> Result<PostEffectId, RendererError> {
+        self.insert_post_effect(usize::MAX, effect)
+    }
+
+    /// вставить эффект на место `index` в порядке выполнения
+    pub fn insert_post_effect(
+        &mut self,
+        index: usize,
+        effect: PostEffect,
+    ) -> Result<PostEffectId, RendererError> {
+        self.post_process.insert(
+            &self.device,
+            &self.queue,
+            &self.attachments.hdr,
+            index,
+            effect,
+        )
+    }
+
+    pub fn remove_post_effect(&mut self, id: PostEffectId) -> Option<PostEffect> {
+        self.post_process.remove(id)
+    }
+
+    /// переставить эффект на место `index` в порядке выполнения; false — нет такого эффекта
+    pub fn move_post_effect(&mut self, id: PostEffectId, index: usize) -> bool {
+        self.post_process.move_to(id, index)
+    }
+
+    pub fn post_effect(&self, id: PostEffectId) -> Option<&PostEffect> {
+        self.post_process.get(id)
+    }
+
+    /// эффекты в порядке выполнения
+    pub fn post_effects(&self) -> Vec<PostEffectId> {
+        self.post_process.ids()
+    }
+
+    /// включить или выключить эффект; false — нет такого эффекта
+    pub fn set_post_effect_enabled(&mut self, id: PostEffectId, enabled: bool) -> bool {
+        self.post_process.set_enabled(id, enabled)
+    }
+
+    /// новые параметры эффекта; false — нет такого эффекта
+    pub fn set_post_effect_params(&mut self, id: PostEffectId, params: &[f32]) -> bool {
+        self.post_process
+            .set_params(&self.device, &self.queue, &self.attachments.hdr, id, params)
+    }
+
+    fn targets(&self) -> Targets {
+        Targets {
+            format: HDR_FORMAT,
+            depth_format: self.settings.depth_format,
+            sample_count: self.settings.sample_count,
         }
     }
 
+    /// отладочная графика текущего кадра; рисуется и очищается в `render`
+    pub fn debug_draw(&mut self) -> &mut DebugDraw {
+        &mut self.debug
+    }
+
+    pub fn debug_view(&self) -> &DebugView {
+        &self.debug_view
+    }
+
+    /// отладочные режимы отображения; действуют со следующего `render`
+    pub fn debug_view_mut(&mut self) -> &mut DebugView {
+        &mut self.debug_view
+    }
+
     /// Перенастроить surface при ресайзе
     pub fn resize(&mut self, width: u32, height: u32) {
         if width > 0 && height > 0 {
             self.config.width = width;
             self.config.height = height;
             self.surface.configure(&self.device, &self.config);
-
-            // Пересоздание MSAA текстуры
-            let msaa_texture = self.device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("MSAA Texture"),
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: self.sample_count,
-                dimension: wgpu::TextureDimension::D2,
-                format: self.config.format,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            });
-            self.msaa_texture_view =
-                msaa_texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-            // Пересоздание текстуры глубины
-            let depth_texture = self.device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("Depth Texture"),
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: self.sample_count,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Depth32Float,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            });
-            self.depth_texture_view =
-                depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.rebuild_attachments();
         }
     }
 
-    /// Рендерить сцену с камерой
-    pub fn render(&mut self, scene: &Scene, camera: &Camera) {
+    /// вложения под текущий размер и MSAA; тональная компрессия читает новый HDR-кадр
+    fn rebuild_attachments(&mut self) {
+        self.attachments = create_attachments(&self.device, &self.config, self.targets());
+        self.bloom
+            .set_input(&self.device, &self.attachments.hdr, self.size());
+        self.post_process
+            .set_input(&self.device, &self.attachments.hdr, self.size());
+        self.tone_map.set_input(&self.device, &self.attachments.hdr);
+        self.oit = oit_pass(
+            &self.device,
+            self.targets(),
+            self.settings.transparency,
+            self.size(),
+        );
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.config.width, self.config.height)
+    }
+
+    /// Рендерить сцену с камерой. Потерянный surface перенастраивается, кадр пропускается.
+    /// Ошибки устройства возвращаются после кадра (или перед ним, если случились между
+    /// кадрами); все они не фатальны: потерянное устройство уже пересоздано
+    /// (`RendererError::DeviceLost`), а после нехватки памяти и ошибок валидации можно
+    /// рисовать следующий кадр.
+    pub fn render(&mut self, scene: &Scene, camera: &Camera) -> Result<(), RendererError> {
+        self.check_device(scene)?;
         let frame = match self.surface.get_current_texture() {
             Ok(frame) => frame,
-            Err(e) => {
-                eprintln!("Failed to acquire next surface texture: {:?}", e);
-                return;
+            Err(error) => {
+                // окно изменилось или swap chain потерян: перенастроить
+                if matches!(
+                    error,
+                    wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated
+                ) {
+                    self.surface.configure(&self.device, &self.config);
+                }
+                return RendererError::from_surface(error).map_or(Ok(()), Err);
             }
         };
         let view = frame
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
+        self.uniforms.reset();
+        self.debug_uniforms.reset();
 
-        // Вспомогательная структура для GPU-ресурсов
-        struct ObjGpu {
-            vertex_buffer: wgpu::Buffer,
-            index_buffer: wgpu::Buffer,
-            bind_group: wgpu::BindGroup,
-            index_count: u32,
-        }
-        let mut objs_gpu: Vec<ObjGpu> = Vec::with_capacity(scene.objects().len());
+        let mut objs_gpu: Vec<ObjGpu> =
+            Vec::with_capacity(scene.objects().len() + scene.instanced().len());
         let aspect = self.config.width as f32 / self.config.height as f32;
         let view_mat = camera.view_matrix();
         let proj_mat = camera.projection_matrix(aspect);
 
-        // Подготовка GPU-ресурсов для всех объектов
-        for obj in scene.objects() {
-            let vertices = obj.vertices();
-            let indices = obj.indices();
-
-            // vertex buffer
-            let vertex_buffer = self
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Object Vertex Buffer"),
-                    contents: Vertex::as_byte_slice(vertices),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
+        let view_proj = proj_mat * view_mat;
 
-            // index buffer
-            let index_buffer = self
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Object Index Buffer"),
-                    contents: unsafe {
-                        std::slice::from_raw_parts(
-                            indices.as_ptr() as *const u8,
-                            std::mem::size_of_val(indices),
-                        )
-                    },
-                    usage: wgpu::BufferUsages::INDEX,
-                });
+        // Каскады теней и пирамиды отсечения: камеры и камер света
+        let light = scene.light();
+        let cascades = if self.settings.shadows.enabled && light.cast_shadows {
+            shadow::cascades(camera, aspect, light.direction, &self.settings.shadows)
+        } else {
+            Vec::new()
+        };
+        let culling = self.settings.frustum_culling.then(|| {
+            let light_frusta: Vec<Frustum> = cascades
+                .iter()
+                .map(|cascade| Frustum::from_view_proj_zero_to_one(cascade.view_proj))
+                .collect();
+            (Frustum::from_view_proj(view_proj), light_frusta)
+        });
+        // (видна камере, видна хотя бы одному каскаду)
+        let visibility = |sphere: &BoundingSphere| match &culling {
+            Some((frustum, light_frusta)) => (
+                frustum.intersects_sphere(sphere),
+                light_frusta
+                    .iter()
+                    .any(|frustum| frustum.intersects_sphere(sphere)),
+            ),
+            None => (true, true),
+        };
+        let mut stats = CullingStats::default();
 
+        // Небо: обратная матрица проекции и поворота камеры (без переноса)
+        let skybox = scene.environment().map(|environment| {
+            self.upload_environment(environment);
+            let gpu = self.environment.as_ref().expect("environment uploaded");
+            gpu.set_intensity(&self.queue, environment.intensity);
+            let rotation = Mat4::from_mat3(Mat3::from_mat4(view_mat));
+            self.object_uniforms(Uniforms::from_mat4((proj_mat * rotation).inverse()))
+        });
+
+        let mut overlay_segments: Vec<Vertex> = Vec::new();
+        // объекты, отбрасывающие тень: индекс в objs_gpu и матрица model
+        let mut shadow_casters: Vec<(usize, Mat4)> = Vec::new();
+        // вне камеры, но отбрасывают тень в кадр: только для прохода теней
+        let mut shadow_only: Vec<ObjGpu> = Vec::new();
+        let mut shadow_only_casters: Vec<(usize, Mat4)> = Vec::new();
+        // полупрозрачные объекты с глубиной вдоль взгляда
+        let mut transparent: Vec<(f32, ObjGpu)> = Vec::new();
+
+        // Подготовка GPU-ресурсов для всех объектов
+        self.lod_levels.resize(scene.objects().len(), 0);
+        for (index, obj) in scene.objects().iter().enumerate() {
             // uniform MVP = projection * view * model
+            let mvp = proj_mat * view_mat * obj.model_matrix();
+            let sphere = obj.world_bounding_sphere();
+            // уровень детализации по размеру на экране
+            let mesh = match obj.lods() {
+                [] => obj.mesh(),
+                lods => {
+                    let level = lod::select_lod(
+                        lods,
+                        lod::screen_size(camera, &sphere),
+                        self.lod_levels[index],
+                        self.settings.lod_hysteresis,
+                    );
+                    self.lod_levels[index] = level;
+                    level.checked_sub(1).map_or(obj.mesh(), |i| &lods[i].mesh)
+                }
+            };
+            // полупрозрачные объекты (кроме отладочной заливки) идут в отдельный проход
+            // с глубиной центра для сортировки и тень не отбрасывают
+            let opacity = obj.opacity();
+            let depth = (opacity < 1.0
+                && (obj.topology() != Topology::TriangleList
+                    || self.debug_view.shading == Shading::Lit))
+                .then(|| transparency::view_depth(camera, sphere.center));
+
+            let (visible, casts_visible_shadow) = visibility(&sphere);
+            let shadow_caster =
+                obj.cast_shadows() && depth.is_none() && obj.topology() == Topology::TriangleList;
+            if visible {
+                stats.drawn += 1;
+            } else {
+                stats.culled += 1;
+                if !(shadow_caster && casts_visible_shadow) {
+                    continue;
+                }
+            }
+
+            let obj_gpu = match obj.topology() {
+                Topology::TriangleList => {
+                    if mesh.indices.is_empty() {
+                        continue;
+                    }
+
+                    // текстура загружается на GPU один раз
+                    let texture_id = obj.texture().map(|texture| self.upload_texture(texture));
+
+                    if self.debug_view.wireframe && visible {
+                        let edges = wireframe_vertices(mesh, WIREFRAME_COLOR);
+                        objs_gpu.push(ObjGpu {
+                            bind_group: self.sized_uniforms(mvp, 1.0, 1.0),
+                            draw: Draw::Lines(self.vertices("Wireframe Buffer", &edges)),
+                        });
+                    }
+                    if self.debug_view.normals && visible {
+                        overlay_segments
+                            .extend(normal_vertices(obj, self.debug_view.normal_length));
+                    }
+
+                    let debug = (self.debug_view.shading != Shading::Lit)
+                        .then(|| self.debug_uniforms(camera, index));
+                    if shadow_caster && visible {
+                        shadow_casters.push((objs_gpu.len(), obj.model_matrix()));
+                    } else if shadow_caster {
+                        shadow_only_casters.push((shadow_only.len(), obj.model_matrix()));
+                    }
+                    let uniforms =
+                        Uniforms::object(mvp, obj.model_matrix(), obj.receive_shadows(), opacity);
+                    ObjGpu {
+                        bind_group: self.object_uniforms(uniforms),
+                        draw: Draw::Indexed {
+                            mesh: self.meshes.get(&self.device, mesh),
+                            texture_id,
+                            instances: None,
+                            debug,
+                        },
+                    }
+                }
+                // разложенные вершины кэшируются вместе с мешем, каждый кадр — только uniform
+                Topology::LineList | Topology::LineStrip => {
+                    let segments = self.meshes.vertices(&self.device, mesh);
+                    if segments.count == 0 {
+                        continue;
+                    }
+                    ObjGpu {
+                        bind_group: self.sized_uniforms(mvp, obj.line_width(), opacity),
+                        draw: Draw::Lines(segments),
+                    }
+                }
+                Topology::PointList => {
+                    let points = self.meshes.vertices(&self.device, mesh);
+                    if points.count == 0 {
+                        continue;
+                    }
+                    ObjGpu {
+                        bind_group: self.sized_uniforms(mvp, obj.line_width(), opacity),
+                        draw: Draw::Points(points),
+                    }
+                }
+            };
+            match depth {
+                _ if !visible => shadow_only.push(obj_gpu),
+                Some(depth) => transparent.push((depth, obj_gpu)),
+                None => objs_gpu.push(obj_gpu),
+            }
+        }
+        // от дальних к ближним
+        transparent.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+        // Инстансинг: один draw call на объект, экземпляры во втором вершинном буфере
+        let mut instance_slots = 0;
+        for obj in scene.instanced() {
+            if obj.mesh().indices.is_empty() || obj.instances().is_empty() {
+                continue;
+            }
+
+            // экземпляры отсекаются по отдельности: остаются видимые камере
+            // и отбрасывающие тень в кадр
             let model = obj.model_matrix();
-            let mvp = proj_mat * view_mat * model;
-            let uniforms = Uniforms::from_mat4(mvp);
-            let uniform_buffer =
-                self.device
-                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                        label: Some("Uniform Buffer"),
-                        contents: Uniforms::as_byte_slice(&[uniforms]),
-                        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-                    });
+            let instances: Cow<[Instance]> = if culling.is_some() {
+                let visible_instances = obj.instances().iter().filter(|instance| {
+                    let (visible, casts_visible_shadow) =
+                        visibility(&obj.instance_bounding_sphere(instance));
+                    if visible {
+                        stats.drawn += 1;
+                    } else {
+                        stats.culled += 1;
+                    }
+                    visible || (obj.cast_shadows() && casts_visible_shadow)
+                });
+                Cow::Owned(visible_instances.copied().collect())
+            } else {
+                stats.drawn += obj.instances().len();
+                Cow::Borrowed(obj.instances())
+            };
+            if instances.is_empty() {
+                continue;
+            }
 
-            let uniform_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Uniform Bind Group"),
-                layout: &self.uniform_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                }],
+            let bind_group = self.object_uniforms(Uniforms::object(
+                proj_mat * view_mat * model,
+                model,
+                obj.receive_shadows(),
+                1.0,
+            ));
+            if obj.cast_shadows() {
+                shadow_casters.push((objs_gpu.len(), model));
+            }
+            let slot = instance_slots;
+            instance_slots += 1;
+            self.instance_buffers
+                .write(&self.device, &self.queue, slot, &instances);
+
+            objs_gpu.push(ObjGpu {
+                bind_group,
+                draw: Draw::Indexed {
+                    mesh: self.meshes.get(&self.device, obj.mesh()),
+                    texture_id: None,
+                    instances: Some((slot, instances.len() as u32)),
+                    debug: None,
+                },
             });
+        }
+        self.instance_buffers.truncate(instance_slots);
 
+        // Нормали вершин уже в мировых координатах
+        if !overlay_segments.is_empty() {
             objs_gpu.push(ObjGpu {
-                vertex_buffer,
-                index_buffer,
-                bind_group: uniform_bind_group,
-                index_count: indices.len() as u32,
+                bind_group: self.sized_uniforms(view_proj, 1.0, 1.0),
+                draw: Draw::Lines(self.vertices("Normal Buffer", &overlay_segments)),
             });
         }
 
+        // Следы в мировых координатах
+        for trail in scene.trails() {
+            match trail.style {
+                TrailStyle::Line { width } => {
+                    let segments = trail.line_vertices();
+                    if segments.is_empty() {
+                        continue;
+                    }
+                    objs_gpu.push(ObjGpu {
+                        bind_group: self.sized_uniforms(view_proj, width, 1.0),
+                        draw: Draw::Lines(self.vertices("Trail Buffer", &segments)),
+                    });
+                }
+                TrailStyle::Ribbon { width } => {
+                    let vertices = trail.ribbon_vertices(camera.position, width);
+                    if vertices.is_empty() {
+                        continue;
+                    }
+                    objs_gpu.push(ObjGpu {
+                        bind_group: self.object_uniforms(Uniforms::from_mat4(view_proj)),
+                        draw: Draw::Ribbon(self.vertices("Ribbon Buffer", &vertices)),
+                    });
+                }
+            }
+        }
+
+        // Объекты вне камеры — в конце списка, только для прохода теней
+        let drawn = objs_gpu.len();
+        shadow_casters.extend(
+            shadow_only_casters
+                .into_iter()
+                .map(|(index, model)| (drawn + index, model)),
+        );
+        objs_gpu.extend(shadow_only);
+        self.culling_stats = stats;
+
+        // Каскады теней: MVP каждого отбрасывающего тень объекта для каждого каскада
+        let shadow_bind_groups: Vec<Vec<usize>> = cascades
+            .iter()
+            .map(|cascade| {
+                shadow_casters
+                    .iter()
+                    .map(|(_, model)| {
+                        self.object_uniforms(Uniforms::from_mat4(cascade.view_proj * *model))
+                    })
+                    .collect()
+            })
+            .collect();
+        let light_bind_group = self.light_bind_group(light, scene.environment(), camera, &cascades);
+
+        // Отладочные линии: с проверкой глубины и поверх сцены
+        let forward = (camera.target - camera.position).normalize_or_zero();
+        let right = forward.cross(camera.up).normalize_or_zero();
+        let up = right.cross(forward);
+        let (depth_lines, mut overlay_lines) = self.debug.segment_vertices(right, up);
+        // выделенный объект — каркасом поверх сцены
+        if let Some(selected) = self.selection.and_then(|index| scene.objects().get(index)) {
+            let model = selected.model_matrix();
+            overlay_lines.extend(
+                wireframe_vertices(selected.mesh(), SELECTION_COLOR)
+                    .into_iter()
+                    .map(|mut vertex| {
+                        vertex.position = model
+                            .transform_point3(Vec3::from(vertex.position))
+                            .to_array();
+                        vertex
+                    }),
+            );
+        }
+        let debug_bind_group = self.sized_uniforms(view_proj, self.debug.line_width, 1.0);
+        let debug_buffers = [
+            (&self.pipelines.line, depth_lines),
+            (&self.pipelines.overlay_line, overlay_lines),
+        ]
+        .into_iter()
+        .filter(|(_, vertices)| !vertices.is_empty())
+        .map(|(pipeline, vertices)| (pipeline, self.vertices("Debug Line Buffer", &vertices)))
+        .collect::<Vec<_>>();
+
         // Создание командного энкодера и прохода рендеринга
         let mut encoder = self
             .device
@@ -344,24 +1028,58 @@ impl Renderer {
                 label: Some("Render Encoder"),
             });
 
+        // Проход теней: глубина из камеры света, по слою карты на каскад
+        for (layer, bind_groups) in self.shadow_map.layers.iter().zip(&shadow_bind_groups) {
+            let mut spass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: layer,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            for ((index, _), bind_group) in shadow_casters.iter().zip(bind_groups) {
+                let Draw::Indexed {
+                    mesh, instances, ..
+                } = &objs_gpu[*index].draw
+                else {
+                    continue;
+                };
+                spass.set_bind_group(0, self.uniforms.bind_group(*bind_group), &[]);
+                match instances {
+                    Some((slot, _)) => {
+                        spass.set_pipeline(&self.pipelines.instanced_shadow);
+                        spass.set_vertex_buffer(1, self.instance_buffers.get(*slot).slice(..));
+                    }
+                    None => spass.set_pipeline(&self.pipelines.shadow),
+                }
+                let instance_count = instances.map_or(1, |(_, count)| count);
+                draw_mesh(&mut spass, mesh, instance_count);
+            }
+        }
+
+        // Сцена рисуется в HDR-кадр (с MSAA — в мультисемплированную текстуру с разрешением в него)
+        let (color_view, resolve_target) = match &self.attachments.msaa {
+            Some(msaa_view) => (msaa_view, Some(&self.attachments.hdr)),
+            None => (&self.attachments.hdr, None),
+        };
         {
             let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Main Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &self.msaa_texture_view, // Рендеринг в MSAA текстуру
-                    resolve_target: Some(&view),   // Разрешение в основную текстуру
+                    view: color_view,
+                    resolve_target,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.1,
-                            b: 0.12,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(self.settings.clear_color),
                         store: true,
                     },
                 })],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &self.depth_texture_view,
+                    view: &self.attachments.depth,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: true,
@@ -370,19 +1088,1064 @@ impl Renderer {
                 }),
             });
 
-            rpass.set_pipeline(&self.render_pipeline);
+            // Отрисовка непрозрачных объектов
+            for obj_gpu in &objs_gpu[..drawn] {
+                self.draw_object(&mut rpass, obj_gpu, &light_bind_group, Pass::Opaque);
+            }
 
-            // Отрисовка всех объектов
-            for obj_gpu in objs_gpu.iter() {
-                rpass.set_bind_group(0, &obj_gpu.bind_group, &[]);
-                rpass.set_vertex_buffer(0, obj_gpu.vertex_buffer.slice(..));
-                rpass.set_index_buffer(obj_gpu.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                rpass.draw_indexed(0..obj_gpu.index_count, 0, 0..1);
+            // Небо после геометрии: рисуется только там, где глубина осталась дальней
+            if let (Some(slot), Some(environment)) = (skybox, &self.environment) {
+                rpass.set_pipeline(&self.pipelines.skybox);
+                rpass.set_bind_group(0, self.uniforms.bind_group(slot), &[]);
+                rpass.set_bind_group(1, &environment.bind_group, &[]);
+                rpass.draw(0..3, 0..1);
             }
         }
 
+        // WBOIT: треугольники накапливаются без сортировки, остальное смешивается по порядку
+        let (accumulated, blended): (Vec<_>, Vec<_>) = transparent
+            .iter()
+            .map(|(_, obj_gpu)| obj_gpu)
+            .partition(|obj_gpu| {
+                self.oit.is_some() && matches!(obj_gpu.draw, Draw::Indexed { .. })
+            });
+        let oit = self.oit.as_ref().filter(|_| !accumulated.is_empty());
+        if let Some(oit) = oit {
+            let mut apass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("OIT Accumulate Pass"),
+                color_attachments: &oit.color_attachments(),
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.attachments.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            for obj_gpu in accumulated {
+                self.draw_object(&mut apass, obj_gpu, &light_bind_group, Pass::Accumulate);
+            }
+        }
+
+        // Прозрачные объекты поверх непрозрачной сцены без записи глубины, затем отладочная графика
+        {
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Transparent Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: color_view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.attachments.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            if let Some(oit) = oit {
+                oit.composite(&mut rpass);
+            }
+            for obj_gpu in blended {
+                self.draw_object(&mut rpass, obj_gpu, &light_bind_group, Pass::Blended);
+            }
+
+            // Отладочная графика после сцены
+            rpass.set_bind_group(0, self.uniforms.bind_group(debug_bind_group), &[]);
+            for (pipeline, segments) in &debug_buffers {
+                rpass.set_pipeline(pipeline);
+                rpass.set_vertex_buffer(0, segments.buffer.slice(..));
+                rpass.draw(0..6, 0..segments.count / 2);
+            }
+        }
+
+        // Постобработка HDR-кадра: bloom, стек эффектов, затем тональная компрессия в surface
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        if self.settings.bloom.enabled {
+            self.bloom.encode(
+                &self.queue,
+                &mut encoder,
+                &self.attachments.hdr,
+                &self.settings.bloom,
+            );
+        }
+        self.post_process.encode(
+            &self.queue,
+            &mut encoder,
+            &self.attachments.hdr_texture,
+            delta_time,
+        );
+        self.tone_map.encode(
+            &self.queue,
+            &mut encoder,
+            &view,
+            &self.settings.tone_mapping,
+            delta_time,
+            self.size(),
+        );
+
         // Отправка команд и презентация кадра
         self.queue.submit(iter::once(encoder.finish()));
         frame.present();
+        self.debug.end_frame();
+        self.meshes.end_frame();
+        self.check_device(scene)
+    }
+
+    /// вернуть ошибку устройства, пойманную с прошлой проверки; потерянное — пересоздать
+    fn check_device(&mut self, scene: &Scene) -> Result<(), RendererError> {
+        match self.device_errors.take() {
+            None => Ok(()),
+            Some(RendererError::DeviceLost) => {
+                log::warn!("GPU device lost, recreating");
+                self.recover_device(scene)?;
+                Err(RendererError::DeviceLost)
+            }
+            Some(error) => Err(error),
+        }
+    }
+
+    /// Выбрать пайплайн по виду объекта и проходу и нарисовать его
+    fn draw_object<'a>(
+        &'a self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        obj_gpu: &'a ObjGpu,
+        light_bind_group: &'a wgpu::BindGroup,
+        pass: Pass,
+    ) {
+        let transparent = &self.pipelines.transparent;
+        rpass.set_bind_group(0, self.uniforms.bind_group(obj_gpu.bind_group), &[]);
+        match &obj_gpu.draw {
+            Draw::Indexed {
+                mesh,
+                texture_id,
+                instances,
+                debug,
+            } => {
+                let texture = texture_id.and_then(|id| self.textures.get(&id));
+                match (instances, debug, texture) {
+                    (Some((slot, _)), _, _) => {
+                        rpass.set_pipeline(&self.pipelines.instanced);
+                        rpass.set_bind_group(1, light_bind_group, &[]);
+                        rpass.set_vertex_buffer(1, self.instance_buffers.get(*slot).slice(..));
+                    }
+                    (None, Some(slot), _) => {
+                        rpass.set_pipeline(&self.pipelines.debug);
+                        rpass.set_bind_group(1, self.debug_uniforms.bind_group(*slot), &[]);
+                    }
+                    (None, None, Some(texture)) => {
+                        rpass.set_pipeline(match pass {
+                            Pass::Opaque => &self.pipelines.textured,
+                            Pass::Blended => &transparent.textured,
+                            Pass::Accumulate => &transparent.accumulate_textured,
+                        });
+                        rpass.set_bind_group(1, light_bind_group, &[]);
+                        rpass.set_bind_group(2, &texture.bind_group, &[]);
+                    }
+                    (None, None, None) => {
+                        rpass.set_pipeline(match pass {
+                            Pass::Opaque => &self.pipelines.render,
+                            Pass::Blended => &transparent.render,
+                            Pass::Accumulate => &transparent.accumulate_render,
+                        });
+                        rpass.set_bind_group(1, light_bind_group, &[]);
+                    }
+                }
+                let instance_count = instances.map_or(1, |(_, count)| count);
+                draw_mesh(rpass, mesh, instance_count);
+            }
+            Draw::Lines(segments) => {
+                rpass.set_pipeline(match pass {
+                    Pass::Opaque => &self.pipelines.line,
+                    _ => &transparent.line,
+                });
+                rpass.set_vertex_buffer(0, segments.buffer.slice(..));
+                rpass.draw(0..6, 0..segments.count / 2);
+            }
+            Draw::Points(points) => {
+                rpass.set_pipeline(match pass {
+                    Pass::Opaque => &self.pipelines.point,
+                    _ => &transparent.point,
+                });
+                rpass.set_vertex_buffer(0, points.buffer.slice(..));
+                rpass.draw(0..6, 0..points.count);
+            }
+            Draw::Ribbon(vertices) => {
+                rpass.set_pipeline(&self.pipelines.ribbon);
+                rpass.set_vertex_buffer(0, vertices.buffer.slice(..));
+                rpass.draw(0..vertices.count, 0..1);
+            }
+        }
+    }
+
+    /// вершины, собранные на этот кадр (отладочные линии, следы)
+    fn vertices(&self, label: &str, vertices: &[Vertex]) -> Arc<GpuVertices> {
+        Arc::new(GpuVertices::new(&self.device, label, vertices))
+    }
+
+    /// uniform линий и точек: MVP, вьюпорт, размер в пикселях и непрозрачность; слот group 0
+    fn sized_uniforms(&mut self, mvp: Mat4, size: f32, opacity: f32) -> usize {
+        let uniforms = SizedUniforms {
+            mvp: mvp.to_cols_array_2d(),
+            viewport: [self.config.width as f32, self.config.height as f32],
+            size,
+            opacity,
+        };
+        self.uniforms.push(
+            &self.device,
+            &self.queue,
+            &self.layouts.uniform,
+            SizedUniforms::as_byte_slice(&[uniforms]),
+        )
+    }
+
+    /// параметры отладочной заливки объекта с индексом `index`; слот group 1
+    fn debug_uniforms(&mut self, camera: &Camera, index: usize) -> usize {
+        let uniforms = DebugUniforms {
+            color: object_color(index),
+            near: camera.near,
+            far: camera.far,
+            mode: match self.debug_view.shading {
+                Shading::Depth => 0,
+                Shading::FlatColor => 1,
+                Shading::Lit | Shading::Backfaces => 2,
+            },
+            _padding: 0,
+        };
+        self.debug_uniforms.push(
+            &self.device,
+            &self.queue,
+            &self.layouts.debug,
+            DebugUniforms::as_byte_slice(&[uniforms]),
+        )
+    }
+
+    /// свет и каскады кадра вместе с картой теней (group 1 освещённых пайплайнов)
+    fn light_bind_group(
+        &self,
+        light: &DirectionalLight,
+        environment: Option<&Environment>,
+        camera: &Camera,
+        cascades: &[Cascade],
+    ) -> wgpu::BindGroup {
+        let uniforms =
+            LightUniforms::new(light, environment, camera, cascades, &self.settings.shadows);
+        let uniform_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Light Uniform Buffer"),
+                contents: LightUniforms::as_byte_slice(&[uniforms]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Bind Group"),
+            layout: &self.layouts.light,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.shadow_map.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.shadow_map.sampler),
+                },
+            ],
+        })
+    }
+
+    /// uniform объекта; слот group 0
+    fn object_uniforms(&mut self, uniforms: Uniforms) -> usize {
+        self.uniforms.push(
+            &self.device,
+            &self.queue,
+            &self.layouts.uniform,
+            Uniforms::as_byte_slice(&[uniforms]),
+        )
     }
 }
+
+/// Как рисовать подготовленный объект
+enum Draw {
+    // треугольники меша из кэша; экземпляры — слот буфера экземпляров и их количество
+    Indexed {
+        mesh: Arc<GpuMesh>,
+        texture_id: Option<u64>,
+        instances: Option<(usize, u32)>,
+        // слот параметров отладочной заливки вместо обычного пайплайна
+        debug: Option<usize>,
+    },
+    // квад из 6 вершин на каждый отрезок / точку
+    Lines(Arc<GpuVertices>),
+    Points(Arc<GpuVertices>),
+    // готовые треугольники ленты
+    Ribbon(Arc<GpuVertices>),
+}
+
+/// Подготовленный к кадру объект: слот uniform (group 0) и что рисовать
+struct ObjGpu {
+    bind_group: usize,
+    draw: Draw,
+}
+
+/// нарисовать меш из кэша; пайплайн и bind group уже выставлены
+fn draw_mesh<'a>(rpass: &mut wgpu::RenderPass<'a>, mesh: &'a GpuMesh, instance_count: u32) {
+    rpass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+    rpass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+    rpass.draw_indexed(0..mesh.index_count, 0, 0..instance_count);
+}
+
+/// пул uniform group 0: общий для объектов (`Uniforms`) и линий (`SizedUniforms`)
+fn uniform_pool() -> UniformPool {
+    let size = std::mem::size_of::<Uniforms>().max(std::mem::size_of::<SizedUniforms>());
+    UniformPool::new("Uniform Buffer", size)
+}
+
+fn debug_uniform_pool() -> UniformPool {
+    UniformPool::new("Debug Uniform Buffer", std::mem::size_of::<DebugUniforms>())
+}
+
+/// Проход, в котором рисуется объект: от него зависит выбор пайплайна
+#[derive(Copy, Clone, PartialEq)]
+enum Pass {
+    Opaque,
+    // полупрозрачные с альфа-смешиванием после сортировки
+    Blended,
+    // накопление weighted blended OIT
+    Accumulate,
+}
+
+/// Различимый цвет объекта: оттенок шагает на золотое сечение
+fn object_color(index: usize) -> [f32; 4] {
+    let hue = (index as f32 * 0.618_034).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let [r, g, b] = match hue as u32 {
+        0 => [1.0, x, 0.0],
+        1 => [x, 1.0, 0.0],
+        2 => [0.0, 1.0, x],
+        3 => [0.0, x, 1.0],
+        4 => [x, 0.0, 1.0],
+        _ => [1.0, 0.0, x],
+    };
+    // приглушить, чтобы освещение оставалось заметным
+    [0.2 + 0.7 * r, 0.2 + 0.7 * g, 0.2 + 0.7 * b, 1.0]
+}
+
+const WIREFRAME_COLOR: [f32; 3] = [0.1, 1.0, 0.4];
+const SELECTION_COLOR: [f32; 3] = [1.0, 0.6, 0.1];
+
+/// Рёбра треугольников парами вершин (общие рёбра соседних граней — дважды)
+fn wireframe_vertices(mesh: &Mesh, color: [f32; 3]) -> Vec<Vertex> {
+    mesh.indices
+        .chunks_exact(3)
+        .flat_map(|t| [t[0], t[1], t[1], t[2], t[2], t[0]])
+        .map(|i| Vertex {
+            position: mesh.vertices[i as usize].position,
+            color,
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Нормали вершин отрезками в мировых координатах; цвет — направление (n * 0.5 + 0.5)
+fn normal_vertices(obj: &impl Object, length: f32) -> Vec<Vertex> {
+    let model = obj.model_matrix();
+    let normal_matrix = model.inverse().transpose();
+    obj.vertices()
+        .iter()
+        .flat_map(|vertex| {
+            let position = model.transform_point3(Vec3::from(vertex.position));
+            let normal = normal_matrix
+                .transform_vector3(Vec3::from(vertex.normal))
+                .normalize_or_zero();
+            let color = (normal * 0.5 + 0.5).to_array();
+            [position, position + normal * length].map(|position| Vertex {
+                position: position.to_array(),
+                color,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Форматы вложений и MSAA, под которые собираются пайплайны
+#[derive(Copy, Clone)]
+struct Targets {
+    format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    sample_count: u32,
+}
+
+/// Все пайплайны рендерера; пересобираются при смене MSAA
+struct Pipelines {
+    render: wgpu::RenderPipeline,
+    textured: wgpu::RenderPipeline,
+    instanced: wgpu::RenderPipeline,
+    line: wgpu::RenderPipeline,
+    point: wgpu::RenderPipeline,
+    // ленты следов: без освещения и отсечения граней
+    ribbon: wgpu::RenderPipeline,
+    // отладочные линии поверх сцены, без проверки глубины
+    overlay_line: wgpu::RenderPipeline,
+    // отладочная заливка (глубина, цвет объекта, обратные грани), без отсечения граней
+    debug: wgpu::RenderPipeline,
+    // только глубина в карту теней
+    shadow: wgpu::RenderPipeline,
+    instanced_shadow: wgpu::RenderPipeline,
+    // окружение на дальней плоскости, без записи глубины
+    skybox: wgpu::RenderPipeline,
+    transparent: TransparentPipelines,
+}
+
+/// Пайплайны полупрозрачных объектов: те же шейдеры, но без записи глубины
+struct TransparentPipelines {
+    render: wgpu::RenderPipeline,
+    textured: wgpu::RenderPipeline,
+    line: wgpu::RenderPipeline,
+    point: wgpu::RenderPipeline,
+    accumulate_render: wgpu::RenderPipeline,
+    accumulate_textured: wgpu::RenderPipeline,
+}
+
+impl Pipelines {
+    fn new(device: &wgpu::Device, layouts: &BindGroupLayouts, targets: Targets) -> Self {
+        // shader modules
+        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(VERTEX_SHADER.into()),
+        });
+        let instanced_vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Instanced Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(INSTANCED_VERTEX_SHADER.into()),
+        });
+        let line_vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Line Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(LINE_VERTEX_SHADER.into()),
+        });
+        let point_vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Point Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(POINT_VERTEX_SHADER.into()),
+        });
+        let unlit_fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Unlit Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(UNLIT_FRAGMENT_SHADER.into()),
+        });
+        let point_fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Point Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(POINT_FRAGMENT_SHADER.into()),
+        });
+        let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(FRAGMENT_SHADER.into()),
+        });
+        let textured_fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Textured Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(TEXTURED_FRAGMENT_SHADER.into()),
+        });
+        let ribbon_fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ribbon Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(RIBBON_FRAGMENT_SHADER.into()),
+        });
+        let debug_fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Debug Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(DEBUG_FRAGMENT_SHADER.into()),
+        });
+        let shadow_vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADOW_VERTEX_SHADER.into()),
+        });
+        let instanced_shadow_vs_module =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Instanced Shadow Vertex Shader"),
+                source: wgpu::ShaderSource::Wgsl(INSTANCED_SHADOW_VERTEX_SHADER.into()),
+            });
+        let skybox_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Skybox Shader"),
+            source: wgpu::ShaderSource::Wgsl(SKYBOX_SHADER.into()),
+        });
+
+        // pipeline layout uses uniform layout
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pipeline Layout"),
+            bind_group_layouts: &[&layouts.uniform],
+            push_constant_ranges: &[],
+        });
+        let lit_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lit Pipeline Layout"),
+            bind_group_layouts: &[&layouts.uniform, &layouts.light],
+            push_constant_ranges: &[],
+        });
+        let textured_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Textured Pipeline Layout"),
+                bind_group_layouts: &[&layouts.uniform, &layouts.light, &layouts.texture],
+                push_constant_ranges: &[],
+            });
+        let debug_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Debug Pipeline Layout"),
+                bind_group_layouts: &[&layouts.uniform, &layouts.debug],
+                push_constant_ranges: &[],
+            });
+        let skybox_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Skybox Pipeline Layout"),
+                bind_group_layouts: &[&layouts.uniform, &layouts.environment],
+                push_constant_ranges: &[],
+            });
+
+        let render_desc = PipelineDesc {
+            label: "Render Pipeline",
+            layout: &lit_pipeline_layout,
+            vs_module: &vs_module,
+            fs_module: &fs_module,
+            buffers: &[Vertex::desc()],
+            cull_mode: Some(wgpu::Face::Back),
+            depth_test: true,
+            blend: Blend::Replace,
+        };
+        let render = create_pipeline(device, &render_desc, targets);
+        let textured_desc = PipelineDesc {
+            label: "Textured Render Pipeline",
+            layout: &textured_pipeline_layout,
+            vs_module: &vs_module,
+            fs_module: &textured_fs_module,
+            buffers: &[Vertex::desc()],
+            cull_mode: Some(wgpu::Face::Back),
+            depth_test: true,
+            blend: Blend::Replace,
+        };
+        let textured = create_pipeline(device, &textured_desc, targets);
+        let instanced = create_pipeline(
+            device,
+            &PipelineDesc {
+                label: "Instanced Render Pipeline",
+                layout: &lit_pipeline_layout,
+                vs_module: &instanced_vs_module,
+                fs_module: &fs_module,
+                buffers: &[Vertex::desc(), Instance::desc()],
+                cull_mode: Some(wgpu::Face::Back),
+                depth_test: true,
+                blend: Blend::Replace,
+            },
+            targets,
+        );
+        // линии и точки — квады, повёрнутые к экрану, поэтому без отсечения граней
+        let line_desc = PipelineDesc {
+            label: "Line Render Pipeline",
+            layout: &pipeline_layout,
+            vs_module: &line_vs_module,
+            fs_module: &unlit_fs_module,
+            buffers: &[segment_layout()],
+            cull_mode: None,
+            depth_test: true,
+            blend: Blend::Replace,
+        };
+        let line = create_pipeline(device, &line_desc, targets);
+        let ribbon = create_pipeline(
+            device,
+            &PipelineDesc {
+                label: "Ribbon Render Pipeline",
+                layout: &pipeline_layout,
+                vs_module: &vs_module,
+                fs_module: &ribbon_fs_module,
+                buffers: &[Vertex::desc()],
+                cull_mode: None,
+                depth_test: true,
+                blend: Blend::Replace,
+            },
+            targets,
+        );
+        let overlay_line = create_pipeline(
+            device,
+            &PipelineDesc {
+                label: "Overlay Line Render Pipeline",
+                layout: &pipeline_layout,
+                vs_module: &line_vs_module,
+                fs_module: &unlit_fs_module,
+                buffers: &[segment_layout()],
+                cull_mode: None,
+                depth_test: false,
+                blend: Blend::Replace,
+            },
+            targets,
+        );
+        let debug = create_pipeline(
+            device,
+            &PipelineDesc {
+                label: "Debug Render Pipeline",
+                layout: &debug_pipeline_layout,
+                vs_module: &vs_module,
+                fs_module: &debug_fs_module,
+                buffers: &[Vertex::desc()],
+                cull_mode: None,
+                depth_test: true,
+                blend: Blend::Replace,
+            },
+            targets,
+        );
+        let point_desc = PipelineDesc {
+            label: "Point Render Pipeline",
+            layout: &pipeline_layout,
+            vs_module: &point_vs_module,
+            fs_module: &point_fs_module,
+            buffers: &[wgpu::VertexBufferLayout {
+                step_mode: wgpu::VertexStepMode::Instance,
+                ..Vertex::desc()
+            }],
+            cull_mode: None,
+            depth_test: true,
+            blend: Blend::Replace,
+        };
+        let point = create_pipeline(device, &point_desc, targets);
+
+        // полупрозрачные объекты: те же описания с другим смешиванием
+        let blended = |desc: &PipelineDesc, blend| {
+            create_pipeline(device, &PipelineDesc { blend, ..*desc }, targets)
+        };
+        let transparent = TransparentPipelines {
+            render: blended(&render_desc, Blend::Alpha),
+            textured: blended(&textured_desc, Blend::Alpha),
+            line: blended(&line_desc, Blend::Alpha),
+            point: blended(&point_desc, Blend::Alpha),
+            accumulate_render: blended(&render_desc, Blend::Accumulate),
+            accumulate_textured: blended(&textured_desc, Blend::Accumulate),
+        };
+
+        Self {
+            render,
+            textured,
+            instanced,
+            line,
+            point,
+            ribbon,
+            overlay_line,
+            debug,
+            shadow: create_shadow_pipeline(
+                device,
+                "Shadow Pipeline",
+                &pipeline_layout,
+                &shadow_vs_module,
+                &[Vertex::desc()],
+            ),
+            instanced_shadow: create_shadow_pipeline(
+                device,
+                "Instanced Shadow Pipeline",
+                &pipeline_layout,
+                &instanced_shadow_vs_module,
+                &[Vertex::desc(), Instance::desc()],
+            ),
+            skybox: create_skybox_pipeline(
+                device,
+                &skybox_pipeline_layout,
+                &skybox_module,
+                targets,
+            ),
+            transparent,
+        }
+    }
+}
+
+/// Чем отличаются пайплайны между собой
+struct PipelineDesc<'a> {
+    label: &'a str,
+    layout: &'a wgpu::PipelineLayout,
+    vs_module: &'a wgpu::ShaderModule,
+    fs_module: &'a wgpu::ShaderModule,
+    buffers: &'a [wgpu::VertexBufferLayout<'a>],
+    cull_mode: Option<wgpu::Face>,
+    // false — рисовать поверх всего, не записывая глубину
+    depth_test: bool,
+    blend: Blend,
+}
+
+/// Итог отсечения по пирамиде камеры за кадр; экземпляры считаются по отдельности.
+/// Отсечённые объекты всё ещё могут рисоваться в карту теней.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CullingStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+/// Как фрагменты пайплайна попадают в цель
+#[derive(Copy, Clone, PartialEq)]
+enum Blend {
+    // замена цвета и запись глубины (непрозрачные)
+    Replace,
+    // альфа-смешивание без записи глубины
+    Alpha,
+    // накопление WBOIT в две цели (`fs_accumulate`) без записи глубины
+    Accumulate,
+}
+
+/// Отрезок линии — два `Vertex` подряд, по экземпляру на отрезок
+fn segment_layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+    use std::mem::{offset_of, size_of};
+    const VERTEX: wgpu::BufferAddress = size_of::<Vertex>() as wgpu::BufferAddress;
+    const COLOR: wgpu::BufferAddress = offset_of!(Vertex, color) as wgpu::BufferAddress;
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] = [
+        wgpu::VertexAttribute {
+            offset: 0,
+            shader_location: 0,
+            format: wgpu::VertexFormat::Float32x3,
+        },
+        wgpu::VertexAttribute {
+            offset: COLOR,
+            shader_location: 1,
+            format: wgpu::VertexFormat::Float32x3,
+        },
+        wgpu::VertexAttribute {
+            offset: VERTEX,
+            shader_location: 2,
+            format: wgpu::VertexFormat::Float32x3,
+        },
+        wgpu::VertexAttribute {
+            offset: VERTEX + COLOR,
+            shader_location: 3,
+            format: wgpu::VertexFormat::Float32x3,
+        },
+    ];
+    wgpu::VertexBufferLayout {
+        array_stride: VERTEX * 2,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &ATTRIBUTES,
+    }
+}
+
+/// Раскладки bind group: MVP (group 0), свет, текстура и отладочная заливка
+fn bind_group_layouts(device: &wgpu::Device) -> BindGroupLayouts {
+    let uniform_entry = |visibility| wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility,
+        // размер не фиксирован: Uniforms и SizedUniforms
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    };
+    // uniform bind group layout (group 0 binding 0); фрагментному шейдеру нужен receive_shadows
+    let uniform = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Uniform BGL"),
+        entries: &[uniform_entry(
+            wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+        )],
+    });
+    // свет, массив карт теней и сэмплер сравнения (group 1 binding 0/1/2)
+    let light = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Light BGL"),
+        entries: &[
+            uniform_entry(wgpu::ShaderStages::FRAGMENT),
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2Array,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                count: None,
+            },
+        ],
+    });
+    // texture bind group layout (group 2 binding 0/1)
+    let texture = GpuTexture::bind_group_layout(device);
+    // параметры отладочной заливки (group 1 binding 0)
+    let debug = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Debug BGL"),
+        entries: &[uniform_entry(wgpu::ShaderStages::FRAGMENT)],
+    });
+    // кубическая карта окружения для неба (group 1 binding 0/1/2)
+    let environment = GpuEnvironment::bind_group_layout(device);
+
+    BindGroupLayouts {
+        uniform,
+        light,
+        texture,
+        debug,
+        environment,
+    }
+}
+
+/// Устройство с обработчиком ошибок, который отмечает потерю устройства и нехватку памяти
+async fn request_device(
+    adapter: &wgpu::Adapter,
+) -> Result<(wgpu::Device, wgpu::Queue, Arc<DeviceErrors>), RendererError> {
+    // без этой возможности доступны только гарантированные WebGPU режимы MSAA (1x и 4x)
+    let features = adapter.features() & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+    let (device, queue) = adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Device"),
+                features,
+                limits: wgpu::Limits::default(),
+            },
+            None,
+        )
+        .await
+        .map_err(RendererError::RequestDevice)?;
+
+    let errors = Arc::new(DeviceErrors::default());
+    let handler = errors.clone();
+    device.on_uncaptured_error(Box::new(move |error| handler.push(error)));
+    Ok((device, queue, errors))
+}
+
+/// Цели основного прохода под размер surface
+struct Attachments {
+    // нет при sample_count == 1
+    msaa: Option<wgpu::TextureView>,
+    // разрешённый HDR-кадр — вход постобработки
+    hdr_texture: wgpu::Texture,
+    hdr: wgpu::TextureView,
+    depth: wgpu::TextureView,
+}
+
+fn create_attachments(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+    targets: Targets,
+) -> Attachments {
+    let texture = |label, format, sample_count, usage| {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        })
+    };
+    let view =
+        |texture: &wgpu::Texture| texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let attachment = wgpu::TextureUsages::RENDER_ATTACHMENT;
+    // стек постобработки копирует результат обратно в HDR-кадр
+    let hdr_texture = texture(
+        "HDR Texture",
+        targets.format,
+        1,
+        attachment | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    );
+    Attachments {
+        msaa: (targets.sample_count > 1).then(|| {
+            view(&texture(
+                "MSAA Texture",
+                targets.format,
+                targets.sample_count,
+                attachment,
+            ))
+        }),
+        hdr: view(&hdr_texture),
+        hdr_texture,
+        depth: view(&texture(
+            "Depth Texture",
+            targets.depth_format,
+            targets.sample_count,
+            attachment,
+        )),
+    }
+}
+
+/// цели WBOIT, если выбран этот режим
+fn oit_pass(
+    device: &wgpu::Device,
+    targets: Targets,
+    transparency: Transparency,
+    size: (u32, u32),
+) -> Option<OitPass> {
+    (transparency == Transparency::WeightedBlended)
+        .then(|| OitPass::new(device, targets.sample_count, targets.depth_format, size))
+}
+
+/// Пайплайн (всегда TriangleList: линии и точки разворачиваются в квады) с MSAA и глубиной
+fn create_pipeline(
+    device: &wgpu::Device,
+    desc: &PipelineDesc,
+    targets: Targets,
+) -> wgpu::RenderPipeline {
+    let color_target = |format, blend| {
+        Some(wgpu::ColorTargetState {
+            format,
+            blend: Some(blend),
+            write_mask: wgpu::ColorWrites::ALL,
+        })
+    };
+    let color_targets = match desc.blend {
+        Blend::Replace => vec![color_target(targets.format, wgpu::BlendState::REPLACE)],
+        Blend::Alpha => vec![color_target(
+            targets.format,
+            wgpu::BlendState::ALPHA_BLENDING,
+        )],
+        // сумма взвешенного цвета и произведение (1 - alpha)
+        Blend::Accumulate => {
+            let add = wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            };
+            let reveal = wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::OneMinusSrc,
+                operation: wgpu::BlendOperation::Add,
+            };
+            vec![
+                color_target(
+                    OitPass::ACCUM_FORMAT,
+                    wgpu::BlendState {
+                        color: add,
+                        alpha: add,
+                    },
+                ),
+                color_target(
+                    OitPass::REVEAL_FORMAT,
+                    wgpu::BlendState {
+                        color: reveal,
+                        alpha: reveal,
+                    },
+                ),
+            ]
+        }
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(desc.label),
+        layout: Some(desc.layout),
+        vertex: wgpu::VertexState {
+            module: desc.vs_module,
+            entry_point: "vs_main",
+            buffers: desc.buffers,
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: desc.fs_module,
+            entry_point: if desc.blend == Blend::Accumulate {
+                "fs_accumulate"
+            } else {
+                "fs_main"
+            },
+            targets: &color_targets,
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: desc.cull_mode,
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: targets.depth_format,
+            depth_write_enabled: desc.depth_test && desc.blend == Blend::Replace,
+            depth_compare: if desc.depth_test {
+                wgpu::CompareFunction::Less
+            } else {
+                wgpu::CompareFunction::Always
+            },
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: targets.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+/// Пайплайн прохода теней: только глубина, без MSAA и отсечения граней
+/// (тонкие и незамкнутые меши тоже отбрасывают тень)
+fn create_shadow_pipeline(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::PipelineLayout,
+    vs_module: &wgpu::ShaderModule,
+    buffers: &[wgpu::VertexBufferLayout],
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: vs_module,
+            entry_point: "vs_main",
+            buffers,
+        },
+        fragment: None,
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            ..Default::default()
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: ShadowMap::FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+/// Пайплайн неба: полноэкранный треугольник на глубине 1 с проверкой `LessEqual`
+/// без записи глубины — закрашивает только пиксели, куда не попала геометрия
+fn create_skybox_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    module: &wgpu::ShaderModule,
+    targets: Targets,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Skybox Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: targets.format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: targets.depth_format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: targets.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+/// Ошибка создания или работы `Renderer`
+#[derive(Debug)]
+pub enum RendererError {
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// нет адаптера, совместимого с окном и выбранными бэкендами
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    /// адаптер не может выводить в это окно (нет ни одного формата surface)
+    IncompatibleSurface,
+    /// не хватило памяти GPU
+    OutOfMemory,
+    /// устройство GPU потеряно (сброс драйвера, отключение видеокарты); `render` уже
+    /// пересоздал его, следующий кадр рисуется как обычно
+    DeviceLost,
+    /// ошибка валидации wgpu: команды кадра с ней не выполнились
+    Validation(String),
+    /// шейдер эффекта не собрался или не подходит к раскладке
+    InvalidShader {
+        label: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for RendererError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RendererError::CreateSurface(e) => write!(f, "failed to create surface: {e}"),
+            RendererError::NoAdapter => write!(f, "no compatible graphics adapter found"),
+            RendererError::RequestDevice(e) => write!(f, "failed to create device: {e}"),
+            RendererError::IncompatibleSurface => {
+                write!(f, "surface is not supported by the adapter")
+            }
+            RendererError::OutOfMemory => write!(f, "out of GPU memory"),
+            RendererError::DeviceLost => write!(f, "GPU device was lost and has been recreated"),
+            RendererError::Validation(description) => {
+                write!(f, "wgpu validation error: {description}")
+            }
+            RendererError::InvalidShader { label, message } => {
+                write!(f, "invalid shader '{label}': {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RendererError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RendererError::CreateSurface(e) => Some(e),
+            RendererError::RequestDevice(e) => Some(e),
+            RendererError::NoAdapter
+            | RendererError::IncompatibleSurface
+            | RendererError::OutOfMemory
+            | RendererError::DeviceLost
+            | RendererError::Validation(_)
+            | RendererError::InvalidShader { .. } => None,
+        }
+    }
+}
+
+impl RendererError {
+    /// Ошибка из `on_uncaptured_error`. Отдельного события потери устройства в wgpu 0.15
+    /// нет: она приходит ошибкой валидации с текстом `DeviceError::Lost` из wgpu-core
+    pub fn from_device(error: wgpu::Error) -> Self {
+        match error {
+            wgpu::Error::OutOfMemory { .. } => RendererError::OutOfMemory,
+            wgpu::Error::Validation { description, .. }
+                if description.contains(&wgpu_core::device::DeviceError::Lost.to_string()) =>
+            {
+                RendererError::DeviceLost
+            }
+            wgpu::Error::Validation { description, .. } => RendererError::Validation(description),
+        }
+    }
+
+    /// Ошибка кадра по ошибке surface; `None` — кадр просто пропускается
+    /// (`Lost`/`Outdated` — surface перенастраивается, `Timeout` — повтор в следующем кадре)
+    pub fn from_surface(error: wgpu::SurfaceError) -> Option<Self> {
+        match error {
+            wgpu::SurfaceError::Lost
+            | wgpu::SurfaceError::Outdated
+            | wgpu::SurfaceError::Timeout => None,
+            wgpu::SurfaceError::OutOfMemory => Some(RendererError::OutOfMemory),
+        }
+    }
+}
@@ -1,12 +1,18 @@
+/// Вершинный шейдер: позиция и нормаль переводятся в мировые координаты для освещения и теней
 pub const VERTEX_SHADER: &str = r#"
 struct VertexInput {
     @location(0) position: vec3<f32>,
-    @location(1) color: vec3<f32>,
-    @location(2) normal: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+    @location(4) tangent: vec4<f32>,
 };
 
 struct Uniforms {
     mvp: mat4x4<f32>,
+    model: mat4x4<f32>,
+    normal_matrix: mat4x4<f32>,
+    receive_shadows: f32,
 };
 
 @group(0) @binding(0)
@@ -17,6 +23,7 @@ struct VertexOutput {
     @location(0) color: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) world_pos: vec3<f32>,
+    @location(3) uv: vec2<f32>,
 };
 
 @vertex
@@ -24,36 +31,973 @@ fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
     output.position = uniforms.mvp * vec4<f32>(input.position, 1.0);
     output.color = input.color;
-    output.normal = input.normal;
-    output.world_pos = input.position;
+    output.normal = (uniforms.normal_matrix * vec4<f32>(input.normal, 0.0)).xyz;
+    output.world_pos = (uniforms.model * vec4<f32>(input.position, 1.0)).xyz;
+    output.uv = input.uv;
+    return output;
+}
+"#;
+
+/// Вершинный шейдер инстансинга: трансформация и цвет экземпляра во втором буфере
+pub const INSTANCED_VERTEX_SHADER: &str = r#"
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+    @location(4) tangent: vec4<f32>,
+};
+
+struct InstanceInput {
+    @location(5) transform_0: vec4<f32>,
+    @location(6) transform_1: vec4<f32>,
+    @location(7) transform_2: vec4<f32>,
+    @location(8) transform_3: vec4<f32>,
+    @location(9) color: vec3<f32>,
+};
+
+struct Uniforms {
+    mvp: mat4x4<f32>,
+    model: mat4x4<f32>,
+    normal_matrix: mat4x4<f32>,
+    receive_shadows: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) world_pos: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(input: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let transform = mat4x4<f32>(
+        instance.transform_0,
+        instance.transform_1,
+        instance.transform_2,
+        instance.transform_3,
+    );
+    let local = transform * vec4<f32>(input.position, 1.0);
+
+    var output: VertexOutput;
+    output.position = uniforms.mvp * local;
+    output.color = input.color * instance.color;
+    let normal = transform * vec4<f32>(input.normal, 0.0);
+    output.normal = (uniforms.normal_matrix * normal).xyz;
+    output.world_pos = (uniforms.model * local).xyz;
+    output.uv = input.uv;
+    return output;
+}
+"#;
+
+// Общая часть освещённых фрагментных шейдеров: uniforms объекта, направленный свет
+// (group 1) и выборка каскадной карты теней с PCF
+macro_rules! lighting_wgsl {
+    () => {
+        r#"
+struct FragmentInput {
+    @location(0) color: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) world_pos: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+};
+
+struct Uniforms {
+    mvp: mat4x4<f32>,
+    model: mat4x4<f32>,
+    normal_matrix: mat4x4<f32>,
+    receive_shadows: f32,
+    opacity: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct Light {
+    // xyz — направление на источник
+    direction: vec4<f32>,
+    color: vec4<f32>,
+    camera_position: vec4<f32>,
+    camera_forward: vec4<f32>,
+    cascades: array<mat4x4<f32>, 4>,
+    // дальняя граница каждого каскада вдоль взгляда
+    splits: vec4<f32>,
+    // размер текселя каждого каскада в мировых единицах
+    texel_sizes: vec4<f32>,
+    // x — число каскадов (0 — без теней), y — смещение глубины,
+    // z — смещение по нормали в текселях, w — радиус PCF
+    shadow: vec4<f32>,
+    // облучённость от окружения: 9 сферических гармоник, уже делённые на π
+    irradiance: array<vec4<f32>, 9>,
+};
+
+@group(1) @binding(0)
+var<uniform> light: Light;
+@group(1) @binding(1)
+var shadow_map: texture_depth_2d_array;
+@group(1) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+// 1 — освещено, 0 — в тени
+fn shadow_factor(world_pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    let count = u32(light.shadow.x);
+    let depth = dot(world_pos - light.camera_position.xyz, light.camera_forward.xyz);
+    var cascade = count;
+    for (var i = 0u; i < count; i = i + 1u) {
+        if (depth < light.splits[i]) {
+            cascade = i;
+            break;
+        }
+    }
+    if (cascade >= count) {
+        return 1.0;
+    }
+
+    let position = world_pos + normal * light.shadow.z * light.texel_sizes[cascade];
+    let clip = light.cascades[cascade] * vec4<f32>(position, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0) {
+        return 1.0;
+    }
+
+    let reference = ndc.z - light.shadow.y;
+    let texel = 1.0 / f32(textureDimensions(shadow_map).x);
+    let radius = i32(light.shadow.w);
+    var lit = 0.0;
+    var taps = 0.0;
+    for (var x = -radius; x <= radius; x = x + 1) {
+        for (var y = -radius; y <= radius; y = y + 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            lit = lit + textureSampleCompareLevel(
+                shadow_map, shadow_sampler, uv + offset, i32(cascade), reference
+            );
+            taps = taps + 1.0;
+        }
+    }
+    return lit / taps;
+}
+
+// рассеянный свет окружения по нормали
+fn ambient(n: vec3<f32>) -> vec3<f32> {
+    let sh = light.irradiance;
+    let color = sh[0].rgb * 0.282095
+        + sh[1].rgb * 0.488603 * n.y
+        + sh[2].rgb * 0.488603 * n.z
+        + sh[3].rgb * 0.488603 * n.x
+        + sh[4].rgb * 1.092548 * n.x * n.y
+        + sh[5].rgb * 1.092548 * n.y * n.z
+        + sh[6].rgb * 0.315392 * (3.0 * n.z * n.z - 1.0)
+        + sh[7].rgb * 1.092548 * n.x * n.z
+        + sh[8].rgb * 0.546274 * (n.x * n.x - n.y * n.y);
+    return max(color, vec3<f32>(0.0));
+}
+
+// ламберт + освещение от окружения с учётом тени
+fn shade(albedo: vec3<f32>, input: FragmentInput) -> vec3<f32> {
+    let n = normalize(input.normal);
+    let diff = max(dot(n, normalize(light.direction.xyz)), 0.0);
+    var shadow = 1.0;
+    if (uniforms.receive_shadows > 0.5 && diff > 0.0) {
+        shadow = shadow_factor(input.world_pos, n);
+    }
+    return albedo * (ambient(n) + light.color.rgb * diff * shadow);
+}
+
+struct AccumulateOutput {
+    @location(0) accum: vec4<f32>,
+    @location(1) reveal: f32,
+};
+
+// вклад фрагмента в weighted blended OIT: ближние к камере слои весят больше
+fn accumulate(color: vec3<f32>, alpha: f32, world_pos: vec3<f32>) -> AccumulateOutput {
+    let distance = length(world_pos - light.camera_position.xyz);
+    let weight = alpha * clamp(
+        10.0 / (1e-5 + pow(distance / 5.0, 2.0) + pow(distance / 200.0, 6.0)), 1e-2, 3e3
+    );
+    var output: AccumulateOutput;
+    output.accum = vec4<f32>(color * alpha, alpha) * weight;
+    output.reveal = alpha;
+    return output;
+}
+"#
+    };
+}
+
+pub const FRAGMENT_SHADER: &str = concat!(
+    lighting_wgsl!(),
+    r#"
+@fragment
+fn fs_main(input: FragmentInput) -> @location(0) vec4<f32> {
+    return vec4<f32>(shade(input.color, input), uniforms.opacity);
+}
+
+@fragment
+fn fs_accumulate(input: FragmentInput) -> AccumulateOutput {
+    return accumulate(shade(input.color, input), uniforms.opacity, input.world_pos);
+}
+"#
+);
+
+/// Фрагментный шейдер текстурного пайплайна: albedo = текстура * цвет вершины
+pub const TEXTURED_FRAGMENT_SHADER: &str = concat!(
+    lighting_wgsl!(),
+    r#"
+@group(2) @binding(0)
+var t_albedo: texture_2d<f32>;
+@group(2) @binding(1)
+var s_albedo: sampler;
+
+@fragment
+fn fs_main(input: FragmentInput) -> @location(0) vec4<f32> {
+    let texel = textureSample(t_albedo, s_albedo, input.uv);
+    let albedo = texel.rgb * input.color;
+    return vec4<f32>(shade(albedo, input), texel.a * uniforms.opacity);
+}
+
+@fragment
+fn fs_accumulate(input: FragmentInput) -> AccumulateOutput {
+    let texel = textureSample(t_albedo, s_albedo, input.uv);
+    let albedo = texel.rgb * input.color;
+    return accumulate(shade(albedo, input), texel.a * uniforms.opacity, input.world_pos);
+}
+"#
+);
+
+/// Толстые линии: каждый отрезок — экземпляр квада из 6 вершин, расширенного в экранном пространстве
+pub const LINE_VERTEX_SHADER: &str = r#"
+struct SegmentInput {
+    @location(0) start: vec3<f32>,
+    @location(1) start_color: vec3<f32>,
+    @location(2) end: vec3<f32>,
+    @location(3) end_color: vec3<f32>,
+};
+
+struct Uniforms {
+    mvp: mat4x4<f32>,
+    viewport: vec2<f32>,
+    size: f32,
+    opacity: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+// отсечение по ближней плоскости, иначе проекция точки за камерой переворачивается
+fn clip_near(p: vec4<f32>, other: vec4<f32>) -> vec4<f32> {
+    let near = 1e-4;
+    if (p.w >= near) {
+        return p;
+    }
+    let t = (near - p.w) / (other.w - p.w);
+    return mix(p, other, t);
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, segment: SegmentInput) -> VertexOutput {
+    // x — какой конец отрезка, y — сторона от оси
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var output: VertexOutput;
+    output.color = mix(segment.start_color, segment.end_color, corner.x);
+
+    let raw_start = uniforms.mvp * vec4<f32>(segment.start, 1.0);
+    let raw_end = uniforms.mvp * vec4<f32>(segment.end, 1.0);
+    if (raw_start.w < 1e-4 && raw_end.w < 1e-4) {
+        // целиком за камерой — выносим за пределы глубины
+        output.position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        return output;
+    }
+    let start = clip_near(raw_start, raw_end);
+    let end = clip_near(raw_end, raw_start);
+
+    // направление отрезка в пикселях
+    let screen_start = start.xy / start.w * uniforms.viewport;
+    let screen_end = end.xy / end.w * uniforms.viewport;
+    let delta = screen_end - screen_start;
+    var dir = vec2<f32>(1.0, 0.0);
+    if (dot(delta, delta) > 1e-12) {
+        dir = normalize(delta);
+    }
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    // полширины в NDC: size / 2 пикселей * 2 / viewport
+    let offset = normal * corner.y * uniforms.size / uniforms.viewport;
+    var position = mix(start, end, corner.x);
+    // чуть ближе к камере, чтобы линии на поверхностях не мерцали
+    position = vec4<f32>(position.xy + offset * position.w, position.z - 1e-5 * position.w, position.w);
+    output.position = position;
     return output;
 }
 "#;
 
-pub const FRAGMENT_SHADER: &str = r#"
+/// Точки постоянного размера в пикселях: экземпляр квада на каждую вершину
+pub const POINT_VERTEX_SHADER: &str = r#"
+// раскладка `Vertex`: цвет на location 2
+struct PointInput {
+    @location(0) position: vec3<f32>,
+    @location(2) color: vec3<f32>,
+};
+
+struct Uniforms {
+    mvp: mat4x4<f32>,
+    viewport: vec2<f32>,
+    size: f32,
+    opacity: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    @location(1) corner: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, point: PointInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var position = uniforms.mvp * vec4<f32>(point.position, 1.0);
+    let offset = corner * uniforms.size / uniforms.viewport;
+    position = vec4<f32>(position.xy + offset * position.w, position.zw);
+
+    var output: VertexOutput;
+    output.position = position;
+    output.color = point.color;
+    output.corner = corner;
+    return output;
+}
+"#;
+
+// Uniforms линий и точек (group 0) для фрагментных шейдеров
+macro_rules! sized_uniforms_wgsl {
+    () => {
+        r#"
+struct Uniforms {
+    mvp: mat4x4<f32>,
+    viewport: vec2<f32>,
+    size: f32,
+    opacity: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+"#
+    };
+}
+
+/// Цвет вершины без освещения (линии)
+pub const UNLIT_FRAGMENT_SHADER: &str = concat!(
+    sized_uniforms_wgsl!(),
+    r#"
+@fragment
+fn fs_main(@location(0) color: vec3<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(color, uniforms.opacity);
+}
+"#
+);
+
+/// Цвет вершины без освещения для треугольников из `VERTEX_SHADER` (ленты следов):
+/// wgpu требует, чтобы все выходы вершинного шейдера были входами фрагментного
+pub const RIBBON_FRAGMENT_SHADER: &str = r#"
 struct FragmentInput {
     @location(0) color: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) world_pos: vec3<f32>,
+    @location(3) uv: vec2<f32>,
 };
 
 @fragment
 fn fs_main(input: FragmentInput) -> @location(0) vec4<f32> {
-    // направление на источник света
+    return vec4<f32>(input.color, 1.0);
+}
+"#;
+
+/// Круглые точки: углы квада отбрасываются
+pub const POINT_FRAGMENT_SHADER: &str = concat!(
+    sized_uniforms_wgsl!(),
+    r#"
+@fragment
+fn fs_main(@location(0) color: vec3<f32>, @location(1) corner: vec2<f32>) -> @location(0) vec4<f32> {
+    if (dot(corner, corner) > 1.0) {
+        discard;
+    }
+    return vec4<f32>(color, uniforms.opacity);
+}
+"#
+);
+
+/// Отладочная заливка (см. `Shading`); параметры объекта в group 1
+pub const DEBUG_FRAGMENT_SHADER: &str = r#"
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @builtin(front_facing) front_facing: bool,
+    @location(0) color: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) world_pos: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+};
+
+struct DebugParams {
+    color: vec4<f32>,
+    near: f32,
+    far: f32,
+    // 0 — глубина, 1 — цвет объекта, 2 — обратные грани
+    mode: u32,
+    _padding: u32,
+};
+
+@group(1) @binding(0)
+var<uniform> params: DebugParams;
+
+@fragment
+fn fs_main(input: FragmentInput) -> @location(0) vec4<f32> {
     let light_dir = normalize(vec3<f32>(0.3, 1.0, 0.4));
+    let shade = 0.3 + 0.7 * max(dot(normalize(input.normal), light_dir), 0.0);
 
-    // нормаль
-    let n = normalize(input.normal);
+    var color = vec3<f32>(0.0);
+    switch params.mode {
+        case 0u: {
+            // глубина OpenGL-проекции -> расстояние вдоль взгляда, логарифмическая шкала
+            let z = input.position.z;
+            let view_z = 2.0 * params.near * params.far
+                / (params.far + params.near - z * (params.far - params.near));
+            let depth = log(max(view_z, params.near) / params.near) / log(params.far / params.near);
+            color = vec3<f32>(clamp(depth, 0.0, 1.0));
+        }
+        case 1u: {
+            color = params.color.rgb * shade;
+        }
+        default: {
+            if (input.front_facing) {
+                color = vec3<f32>(0.7) * shade;
+            } else {
+                color = vec3<f32>(1.0, 0.0, 1.0);
+            }
+        }
+    }
+    return vec4<f32>(color, 1.0);
+}
+"#;
+
+/// Проход теней: только глубина в пространстве света (mvp = каскад * model)
+pub const SHADOW_VERTEX_SHADER: &str = r#"
+struct Uniforms {
+    mvp: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return uniforms.mvp * vec4<f32>(position, 1.0);
+}
+"#;
+
+/// Проход теней для инстансинга: трансформация экземпляра до mvp
+pub const INSTANCED_SHADOW_VERTEX_SHADER: &str = r#"
+struct Uniforms {
+    mvp: mat4x4<f32>,
+};
 
-    // ламберт
-    let diff = max(dot(n, light_dir), 0.0);
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
 
-    // амбиент
-    let ambient = 0.2;
+struct InstanceInput {
+    @location(5) transform_0: vec4<f32>,
+    @location(6) transform_1: vec4<f32>,
+    @location(7) transform_2: vec4<f32>,
+    @location(8) transform_3: vec4<f32>,
+};
 
-    // итог
-    let lit = input.color * (ambient + 0.8 * diff);
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    instance: InstanceInput,
+) -> @builtin(position) vec4<f32> {
+    let transform = mat4x4<f32>(
+        instance.transform_0,
+        instance.transform_1,
+        instance.transform_2,
+        instance.transform_3,
+    );
+    return uniforms.mvp * transform * vec4<f32>(position, 1.0);
+}
+"#;
+
+/// Полноэкранный треугольник без вершинного буфера (3 вершины); uv от левого верхнего угла
+pub const FULLSCREEN_VERTEX_SHADER: &str = r#"
+struct FullscreenOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var output: FullscreenOutput;
+    output.position = vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+    output.uv = vec2<f32>(corner.x, 1.0 - corner.y);
+    return output;
+}
+"#;
+
+/// Небо: полноэкранный треугольник на дальней плоскости (глубина 1), направление взгляда
+/// восстанавливается обратной матрицей проекции и поворота камеры (mvp в group 0)
+pub const SKYBOX_SHADER: &str = r#"
+struct Uniforms {
+    mvp: mat4x4<f32>,
+};
+
+struct SkyboxOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+@group(1) @binding(0)
+var environment_map: texture_cube<f32>;
+@group(1) @binding(1)
+var environment_sampler: sampler;
+@group(1) @binding(2)
+var<uniform> intensity: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> SkyboxOutput {
+    let corner = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var output: SkyboxOutput;
+    output.ndc = corner * 2.0 - 1.0;
+    output.position = vec4<f32>(output.ndc, 1.0, 1.0);
+    return output;
+}
+
+@fragment
+fn fs_main(input: SkyboxOutput) -> @location(0) vec4<f32> {
+    let far = uniforms.mvp * vec4<f32>(input.ndc, 1.0, 1.0);
+    let direction = normalize(far.xyz / far.w);
+    let color = textureSample(environment_map, environment_sampler, direction).rgb;
+    return vec4<f32>(color * intensity.x, 1.0);
+}
+"#;
+
+/// Сведение weighted blended OIT: средний цвет слоёв с покрытием 1 - reveal
+pub const OIT_COMPOSITE_SHADER: &str = r#"
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var accum_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var reveal_texture: texture_2d<f32>;
+
+@fragment
+fn fs_main(input: FragmentInput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(input.position.xy);
+    let reveal = textureLoad(reveal_texture, pixel, 0).r;
+    // ни одного прозрачного слоя — кадр не трогаем
+    if (reveal >= 1.0) {
+        discard;
+    }
+    let accum = textureLoad(accum_texture, pixel, 0);
+    let average = accum.rgb / max(accum.a, 1e-5);
+    return vec4<f32>(average, 1.0 - reveal);
+}
+"#;
+
+/// Параметры тональной компрессии — общие для шейдеров экспозиции и `TONE_MAP_FRAGMENT_SHADER`
+macro_rules! tone_map_params_wgsl {
+    () => {
+        r#"
+struct ToneMapParams {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    // доля пути к новой яркости за кадр
+    adaptation: f32,
+    // множитель экспозиции: вручную или компенсация при автоэкспозиции
+    exposure: f32,
+    auto_exposure: u32,
+    tone_mapper: u32,
+    pixel_count: u32,
+    _padding: u32,
+};
+
+struct Exposure {
+    luminance: f32,
+};
+"#
+    };
+}
+
+/// Автоэкспозиция: гистограмма логарифма яркости HDR-кадра и её среднее с адаптацией во времени
+pub const AUTO_EXPOSURE_SHADER: &str = concat!(
+    tone_map_params_wgsl!(),
+    r#"
+@group(0) @binding(0)
+var hdr: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: ToneMapParams;
+@group(0) @binding(2)
+var<storage, read_write> histogram: array<atomic<u32>, 256>;
+@group(0) @binding(3)
+var<storage, read_write> exposure: Exposure;
+
+var<workgroup> local_bins: array<atomic<u32>, 256>;
+var<workgroup> weighted: array<f32, 256>;
+
+// корзина 0 — почти чёрные пиксели, 1..255 — логарифм яркости в заданном диапазоне
+fn luminance_bin(color: vec3<f32>) -> u32 {
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    if (luminance < 0.0001) {
+        return 0u;
+    }
+    let t = clamp((log2(luminance) - params.min_log_luminance) / params.log_luminance_range, 0.0, 1.0);
+    return u32(t * 254.0 + 1.0);
+}
+
+@compute @workgroup_size(16, 16)
+fn build_histogram(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(local_invocation_index) index: u32,
+) {
+    atomicStore(&local_bins[index], 0u);
+    workgroupBarrier();
+    let size = vec2<u32>(textureDimensions(hdr));
+    if (id.x < size.x && id.y < size.y) {
+        let color = textureLoad(hdr, vec2<i32>(id.xy), 0).rgb;
+        atomicAdd(&local_bins[luminance_bin(color)], 1u);
+    }
+    workgroupBarrier();
+    atomicAdd(&histogram[index], atomicLoad(&local_bins[index]));
+}
+
+@compute @workgroup_size(256)
+fn average_luminance(@builtin(local_invocation_index) index: u32) {
+    let count = atomicLoad(&histogram[index]);
+    weighted[index] = f32(count) * f32(index);
+    // гистограмма очищается для следующего кадра
+    atomicStore(&histogram[index], 0u);
+    workgroupBarrier();
+    for (var stride = 128u; stride > 0u; stride = stride >> 1u) {
+        if (index < stride) {
+            weighted[index] = weighted[index] + weighted[index + stride];
+        }
+        workgroupBarrier();
+    }
+    if (index == 0u) {
+        // тёмные пиксели в среднее не входят; совсем тёмный кадр экспозицию не меняет
+        let lit = f32(params.pixel_count) - f32(count);
+        if (lit < 1.0) {
+            return;
+        }
+        let mean_bin = weighted[0] / lit;
+        // середина корзины
+        let log_luminance = (mean_bin - 0.5) / 254.0 * params.log_luminance_range + params.min_log_luminance;
+        let goal = exp2(log_luminance);
+        exposure.luminance = exposure.luminance + (goal - exposure.luminance) * params.adaptation;
+    }
+}
+"#
+);
+
+/// Тональная компрессия HDR-кадра в цель surface: экспозиция и оператор ACES, Reinhard или AgX
+pub const TONE_MAP_FRAGMENT_SHADER: &str = concat!(
+    tone_map_params_wgsl!(),
+    r#"
+@group(0) @binding(0)
+var hdr: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: ToneMapParams;
+@group(0) @binding(2)
+var<storage, read> exposure: Exposure;
 
-    return vec4<f32>(lit, 1.0);
+// аппроксимация ACES (K. Narkowicz)
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    return clamp((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn reinhard(x: vec3<f32>) -> vec3<f32> {
+    return x / (1.0 + x);
+}
+
+// полиномиальная аппроксимация контрастной кривой AgX
+fn agx_contrast(x: vec3<f32>) -> vec3<f32> {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232;
+}
+
+fn agx(color: vec3<f32>) -> vec3<f32> {
+    let inset = mat3x3<f32>(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104,
+    );
+    let outset = mat3x3<f32>(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116,
+    );
+    let min_ev = -12.47393;
+    let max_ev = 4.026069;
+    var x = inset * color;
+    x = clamp(log2(max(x, vec3<f32>(1e-10))), vec3<f32>(min_ev), vec3<f32>(max_ev));
+    x = (x - min_ev) / (max_ev - min_ev);
+    x = outset * agx_contrast(x);
+    // кривая даёт значения для дисплея, surface ждёт линейные
+    return pow(max(x, vec3<f32>(0.0)), vec3<f32>(2.2));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>, @location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    var scale = params.exposure;
+    if (params.auto_exposure != 0u) {
+        // средняя яркость приводится к среднему серому
+        scale = scale * 0.18 / max(exposure.luminance, 0.0001);
+    }
+    let color = textureLoad(hdr, vec2<i32>(position.xy), 0).rgb * scale;
+    switch params.tone_mapper {
+        case 0u: {
+            return vec4<f32>(aces(color), 1.0);
+        }
+        case 1u: {
+            return vec4<f32>(reinhard(color), 1.0);
+        }
+        default: {
+            return vec4<f32>(agx(color), 1.0);
+        }
+    }
+}
+"#
+);
+
+/// Bloom: цепочка уменьшения (13 выборок, на первом шаге — порог и среднее Кариса против
+/// вспышек) и увеличения тент-фильтром, затем смешивание с HDR-кадром.
+/// Вершины — `FULLSCREEN_VERTEX_SHADER`
+pub const BLOOM_SHADER: &str = r#"
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    // радиус тент-фильтра в долях экрана
+    radius: f32,
+    // нормировка суммы уровней при смешивании
+    scale: f32,
+};
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: BloomParams;
+
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn tap(uv: vec2<f32>, offset: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source, source_sampler, uv + offset, 0.0).rgb;
+}
+
+// мягкий порог яркости; при нулевом пороге свет не отбрасывается
+fn prefilter(color: vec3<f32>) -> vec3<f32> {
+    if (params.threshold <= 0.0) {
+        return color;
+    }
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - params.threshold + params.knee, 0.0, 2.0 * params.knee);
+    soft = soft * soft / (4.0 * params.knee + 0.0001);
+    let contribution = max(soft, brightness - params.threshold) / max(brightness, 0.0001);
+    return color * contribution;
+}
+
+fn karis_weight(color: vec3<f32>) -> f32 {
+    return 1.0 / (1.0 + luma(color));
+}
+
+fn downsample_13(uv: vec2<f32>, karis: bool) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source));
+    let x = texel.x;
+    let y = texel.y;
+    let a = tap(uv, vec2<f32>(-2.0 * x, 2.0 * y));
+    let b = tap(uv, vec2<f32>(0.0, 2.0 * y));
+    let c = tap(uv, vec2<f32>(2.0 * x, 2.0 * y));
+    let d = tap(uv, vec2<f32>(-2.0 * x, 0.0));
+    let e = tap(uv, vec2<f32>(0.0, 0.0));
+    let f = tap(uv, vec2<f32>(2.0 * x, 0.0));
+    let g = tap(uv, vec2<f32>(-2.0 * x, -2.0 * y));
+    let h = tap(uv, vec2<f32>(0.0, -2.0 * y));
+    let i = tap(uv, vec2<f32>(2.0 * x, -2.0 * y));
+    let j = tap(uv, vec2<f32>(-x, y));
+    let k = tap(uv, vec2<f32>(x, y));
+    let l = tap(uv, vec2<f32>(-x, -y));
+    let m = tap(uv, vec2<f32>(x, -y));
+
+    // пять перекрывающихся квадратов 2x2: четыре угловых и центральный
+    var groups = array<vec3<f32>, 5>(
+        (a + b + d + e) * 0.25,
+        (b + c + e + f) * 0.25,
+        (d + e + g + h) * 0.25,
+        (e + f + h + i) * 0.25,
+        (j + k + l + m) * 0.25,
+    );
+    var weights = array<f32, 5>(0.125, 0.125, 0.125, 0.125, 0.5);
+    var sum = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var n = 0; n < 5; n = n + 1) {
+        var weight = weights[n];
+        if (karis) {
+            weight = weight * karis_weight(groups[n]);
+        }
+        sum = sum + groups[n] * weight;
+        total = total + weight;
+    }
+    return sum / total;
+}
+
+fn tent(uv: vec2<f32>) -> vec3<f32> {
+    let size = vec2<f32>(textureDimensions(source));
+    let x = params.radius;
+    let y = params.radius * size.x / size.y;
+    var sum = tap(uv, vec2<f32>(0.0, 0.0)) * 4.0;
+    sum = sum + (tap(uv, vec2<f32>(0.0, y)) + tap(uv, vec2<f32>(-x, 0.0)) + tap(uv, vec2<f32>(x, 0.0)) + tap(uv, vec2<f32>(0.0, -y))) * 2.0;
+    sum = sum + tap(uv, vec2<f32>(-x, y)) + tap(uv, vec2<f32>(x, y)) + tap(uv, vec2<f32>(-x, -y)) + tap(uv, vec2<f32>(x, -y));
+    return sum / 16.0;
+}
+
+@fragment
+fn downsample_prefilter(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(prefilter(downsample_13(uv, true)), 1.0);
+}
+
+@fragment
+fn downsample(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample_13(uv, false), 1.0);
+}
+
+// складывается с целевым уровнем аддитивным смешиванием
+@fragment
+fn upsample(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(tent(uv), 1.0);
+}
+
+// смешивается с HDR-кадром через константу смешивания (интенсивность)
+@fragment
+fn composite(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(tent(uv) * params.scale, 1.0);
+}
+"#;
+
+/// Начало каждого шейдера `PostEffect`: входной HDR-кадр, сэмплер и сведения о кадре.
+/// Параметры эффекта шейдер объявляет сам в `@group(0) @binding(3)`
+pub const POST_EFFECT_HEADER: &str = r#"
+struct FrameInfo {
+    resolution: vec2<f32>,
+    // секунды с создания рендерера и с прошлого кадра
+    time: f32,
+    delta_time: f32,
+};
+
+@group(0) @binding(0)
+var input_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var input_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> frame: FrameInfo;
+"#;
+
+/// Пример эффекта: затемнение к краям кадра. Параметры: сила и радиус начала затемнения
+pub const VIGNETTE_EFFECT_SHADER: &str = r#"
+struct Params {
+    strength: f32,
+    radius: f32,
+};
+
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, uv).rgb;
+    let aspect = frame.resolution.x / frame.resolution.y;
+    let distance = length((uv - 0.5) * vec2<f32>(aspect, 1.0));
+    let shade = 1.0 - params.strength * smoothstep(params.radius, params.radius + 0.5, distance);
+    return vec4<f32>(color * shade, 1.0);
+}
+"#;
+
+/// Буфер идентификаторов для выбора мышью: номер объекта + 1 (0 — фон) и номер экземпляра
+pub const ID_SHADER: &str = r#"
+struct Uniforms {
+    mvp: mat4x4<f32>,
+    id: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) @interpolate(flat) instance: u32,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>) -> VertexOutput {
+    var output: VertexOutput;
+    output.position = uniforms.mvp * vec4<f32>(position, 1.0);
+    output.instance = 0u;
+    return output;
+}
+
+@vertex
+fn vs_instanced(
+    @location(0) position: vec3<f32>,
+    @location(5) transform_0: vec4<f32>,
+    @location(6) transform_1: vec4<f32>,
+    @location(7) transform_2: vec4<f32>,
+    @location(8) transform_3: vec4<f32>,
+    @builtin(instance_index) instance: u32,
+) -> VertexOutput {
+    let transform = mat4x4<f32>(transform_0, transform_1, transform_2, transform_3);
+    var output: VertexOutput;
+    output.position = uniforms.mvp * transform * vec4<f32>(position, 1.0);
+    output.instance = instance;
+    return output;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec2<u32> {
+    return vec2<u32>(uniforms.id, input.instance);
 }
 "#;
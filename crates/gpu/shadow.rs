@@ -0,0 +1,220 @@
+use glam::{
+    Mat4, Vec3,
+    camera::rh::{proj::directx, view::look_at_mat4},
+};
+
+use crate::Camera;
+
+/// Больше каскадов шейдер не поддерживает
+pub const MAX_CASCADES: usize = 4;
+
+/// Настройки каскадных теней направленного света
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadowConfig {
+    pub enabled: bool,
+    /// сторона карты теней одного каскада в текселях
+    pub resolution: u32,
+    /// число каскадов, 1..=4
+    pub cascades: u32,
+    /// дальше этого расстояния от камеры тени не рисуются
+    pub max_distance: f32,
+    /// 0 — равномерное разбиение, 1 — логарифмическое
+    pub split_lambda: f32,
+    /// смещение глубины при сравнении (в единицах глубины карты, 0..1)
+    pub depth_bias: f32,
+    /// смещение точки вдоль нормали в текселях каскада — против «акне»
+    pub normal_offset: f32,
+    /// радиус PCF в текселях: 0 — одна выборка, 1 — 3x3, 2 — 5x5
+    pub pcf_radius: u32,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            resolution: 2048,
+            cascades: 3,
+            max_distance: 50.0,
+            split_lambda: 0.6,
+            depth_bias: 0.0005,
+            normal_offset: 1.5,
+            pcf_radius: 1,
+        }
+    }
+}
+
+impl ShadowConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn with_cascades(mut self, cascades: u32) -> Self {
+        self.cascades = cascades;
+        self
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    pub fn with_bias(mut self, depth_bias: f32, normal_offset: f32) -> Self {
+        self.depth_bias = depth_bias;
+        self.normal_offset = normal_offset;
+        self
+    }
+
+    pub fn with_pcf_radius(mut self, pcf_radius: u32) -> Self {
+        self.pcf_radius = pcf_radius;
+        self
+    }
+
+    /// тени выключены
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// число каскадов в допустимых пределах
+    pub fn cascade_count(&self) -> usize {
+        (self.cascades as usize).clamp(1, MAX_CASCADES)
+    }
+}
+
+/// Каскад: матрица света и дальняя граница его участка пирамиды камеры
+#[derive(Copy, Clone, Debug)]
+pub struct Cascade {
+    /// мир -> клип света (ортографическая, глубина 0..1)
+    pub view_proj: Mat4,
+    /// дальняя граница каскада — расстояние вдоль направления взгляда
+    pub split: f32,
+    /// размер текселя карты в мировых единицах
+    pub texel_size: f32,
+}
+
+/// Разбить пирамиду камеры на каскады и подобрать для каждого ортографическую камеру света.
+/// `light_direction` — направление на источник. Каскад описан сферой и привязан к сетке
+/// текселей, поэтому тени не дрожат при повороте и движении камеры.
+pub fn cascades(
+    camera: &Camera,
+    aspect: f32,
+    light_direction: Vec3,
+    config: &ShadowConfig,
+) -> Vec<Cascade> {
+    let count = config.cascade_count();
+    let near = camera.near;
+    let far = config.max_distance.min(camera.far).max(near);
+    let direction = light_direction.normalize_or_zero();
+    let up = if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    let rotation = look_at_mat4(Vec3::ZERO, -direction, up);
+    let resolution = config.resolution.max(1) as f32;
+
+    let mut start = near;
+    (1..=count)
+        .map(|i| {
+            let t = i as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(t);
+            let uniform = near + (far - near) * t;
+            let end = config.split_lambda * logarithmic + (1.0 - config.split_lambda) * uniform;
+            let corners = camera.frustum_corners(aspect, start, end);
+            start = end;
+
+            let center = corners.iter().copied().sum::<Vec3>() / corners.len() as f32;
+            let radius = corners
+                .iter()
+                .map(|corner| corner.distance(center))
+                .fold(0.0, f32::max);
+            // округление, чтобы размер каскада не менялся от кадра к кадру
+            let radius = (radius * 16.0).ceil() / 16.0;
+            let texel_size = radius * 2.0 / resolution;
+
+            let mut snapped = rotation.transform_point3(center);
+            snapped.x = (snapped.x / texel_size).floor() * texel_size;
+            snapped.y = (snapped.y / texel_size).floor() * texel_size;
+            let center = rotation.inverse().transform_point3(snapped);
+
+            // камера света отодвинута на max_distance: тени от объектов вне участка тоже попадают
+            let back = far;
+            let view = look_at_mat4(center + direction * (radius + back), center, up);
+            let projection =
+                directx::orthographic(-radius, radius, -radius, radius, 0.0, radius * 2.0 + back);
+            Cascade {
+                view_proj: projection * view,
+                split: end,
+                texel_size,
+            }
+        })
+        .collect()
+}
+
+/// Карта теней: массив глубин, слой на каскад, и сэмплер сравнения с билинейной фильтрацией
+pub(crate) struct ShadowMap {
+    /// весь массив — для выборки в шейдере
+    pub view: wgpu::TextureView,
+    /// по слою на каскад — цели прохода теней
+    pub layers: Vec<wgpu::TextureView>,
+    pub sampler: wgpu::Sampler,
+}
+
+impl ShadowMap {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    pub fn new(device: &wgpu::Device, config: &ShadowConfig) -> Self {
+        let count = config.cascade_count() as u32;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map"),
+            size: wgpu::Extent3d {
+                width: config.resolution,
+                height: config.resolution,
+                depth_or_array_layers: count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Shadow Map View"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let layers = (0..count)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Shadow Cascade View"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: std::num::NonZeroU32::new(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        Self {
+            view,
+            layers,
+            sampler,
+        }
+    }
+}
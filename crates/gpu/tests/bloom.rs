@@ -0,0 +1,25 @@
+use gpu::bloom::{MAX_BLOOM_MIPS, mip_count};
+
+#[test]
+fn mip_chain_fits_frame() {
+    // 1920x1080: уровни от 960x540 и дальше, не больше максимума
+    assert_eq!(mip_count(1920, 1080), MAX_BLOOM_MIPS);
+    // маленькое окно: цепочка короче, но хотя бы один уровень
+    assert_eq!(mip_count(32, 64), 4);
+    assert_eq!(mip_count(1, 1), 1);
+}
+
+#[test]
+fn small_targets_keep_two_pixel_mips() {
+    // последний уровень (сторона / 2^уровней) не меньше 2 пикселей, пока это возможно
+    for size in 4..=512 {
+        let count = mip_count(size, size * 2);
+        assert!(size >> count >= 2, "{size}: {count} mips");
+        assert!(count == MAX_BLOOM_MIPS || size >> (count + 1) < 2);
+    }
+    // меньше 4 пикселей — один уровень, но не ноль
+    for size in 0..4 {
+        assert_eq!(mip_count(size, size), 1);
+    }
+    assert_eq!(mip_count(4096, 3), 1);
+}
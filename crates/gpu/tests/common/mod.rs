@@ -0,0 +1,13 @@
+use gpu::{Camera, Vec3};
+
+/// камера из `position` на начало координат: 60°, ближняя 0.1, дальняя 100
+pub fn camera(position: Vec3) -> Camera {
+    Camera::new(
+        position,
+        Vec3::ZERO,
+        Vec3::Y,
+        60f32.to_radians(),
+        0.1,
+        100.0,
+    )
+}
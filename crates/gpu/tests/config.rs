@@ -0,0 +1,130 @@
+use gpu::{
+    Capabilities, ConfigFallback, Exposure, RendererConfig, ShadowConfig, ToneMapper, ToneMapping,
+    wgpu,
+};
+
+/// адаптер без MSAA 8x, с Fifo и Immediate, и текстурами до 4096
+fn capabilities() -> Capabilities {
+    Capabilities {
+        depth_format_supported: true,
+        sample_counts: vec![1, 2, 4],
+        present_modes: vec![wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate],
+        max_texture_dimension: 4096,
+    }
+}
+
+#[test]
+fn builder_overrides_defaults() {
+    let config = RendererConfig::new()
+        .with_sample_count(1)
+        .with_vsync(false)
+        .with_backends(wgpu::Backends::VULKAN)
+        .with_depth_format(wgpu::TextureFormat::Depth24Plus);
+
+    assert_eq!(config.sample_count, 1);
+    assert!(!config.vsync());
+    assert_eq!(config.present_mode, wgpu::PresentMode::AutoNoVsync);
+    assert_eq!(config.backends, wgpu::Backends::VULKAN);
+    assert_eq!(config.depth_format, wgpu::TextureFormat::Depth24Plus);
+    // не заданное остаётся по умолчанию
+    assert_eq!(config.clear_color, RendererConfig::default().clear_color);
+    assert!(RendererConfig::default().vsync());
+}
+
+#[test]
+fn tone_mapping_builder() {
+    let tone_mapping = ToneMapping::new()
+        .with_operator(ToneMapper::AgX)
+        .with_exposure(Exposure::auto());
+    let config = RendererConfig::new().with_tone_mapping(tone_mapping);
+
+    assert_eq!(config.tone_mapping.operator, ToneMapper::AgX);
+    assert!(config.tone_mapping.exposure.is_auto());
+    assert!(!RendererConfig::default().tone_mapping.exposure.is_auto());
+    // операторы перебираются по кругу
+    assert_eq!(ToneMapper::Aces.next().next().next(), ToneMapper::Aces);
+}
+
+#[test]
+fn supported_settings_are_kept() {
+    let config = RendererConfig::new()
+        .with_sample_count(2)
+        .with_present_mode(wgpu::PresentMode::Immediate);
+    let (resolved, fallbacks) = config.resolve(&capabilities());
+    assert_eq!(resolved, config);
+    assert!(fallbacks.is_empty());
+    // AutoVsync / AutoNoVsync wgpu выбирает сам — подходят для любого surface
+    let (resolved, fallbacks) = config.with_vsync(false).resolve(&capabilities());
+    assert_eq!(resolved.present_mode, wgpu::PresentMode::AutoNoVsync);
+    assert!(fallbacks.is_empty());
+}
+
+#[test]
+fn msaa_falls_back_to_nearest_supported_count() {
+    let resolve = |sample_count| {
+        RendererConfig::new()
+            .with_sample_count(sample_count)
+            .resolve(&capabilities())
+    };
+    let (resolved, fallbacks) = resolve(8);
+    assert_eq!(resolved.sample_count, 4);
+    assert_eq!(
+        fallbacks,
+        [ConfigFallback::SampleCount {
+            requested: 8,
+            used: 4
+        }]
+    );
+    // между поддерживаемыми — вниз, а не вверх
+    assert_eq!(resolve(3).0.sample_count, 2);
+    assert_eq!(resolve(0).0.sample_count, 1);
+
+    // без MSAA у адаптера остаётся только 1
+    let single = Capabilities {
+        sample_counts: vec![1],
+        ..capabilities()
+    };
+    assert_eq!(RendererConfig::new().resolve(&single).0.sample_count, 1);
+}
+
+#[test]
+fn unsupported_formats_and_modes_fall_back() {
+    let config = RendererConfig::new()
+        .with_depth_format(wgpu::TextureFormat::Depth24PlusStencil8)
+        .with_present_mode(wgpu::PresentMode::Mailbox)
+        .with_shadows(ShadowConfig {
+            resolution: 16384,
+            ..ShadowConfig::default()
+        });
+    let capabilities = Capabilities {
+        depth_format_supported: false,
+        ..capabilities()
+    };
+    let (resolved, fallbacks) = config.resolve(&capabilities);
+    assert_eq!(resolved.depth_format, wgpu::TextureFormat::Depth32Float);
+    assert_eq!(resolved.present_mode, wgpu::PresentMode::Fifo);
+    assert_eq!(resolved.shadows.resolution, 4096);
+    assert_eq!(
+        fallbacks,
+        [
+            ConfigFallback::DepthFormat {
+                requested: wgpu::TextureFormat::Depth24PlusStencil8,
+                used: wgpu::TextureFormat::Depth32Float,
+            },
+            ConfigFallback::PresentMode {
+                requested: wgpu::PresentMode::Mailbox,
+                used: wgpu::PresentMode::Fifo,
+            },
+            ConfigFallback::ShadowResolution {
+                requested: 16384,
+                used: 4096,
+            },
+        ]
+    );
+    // остальное не трогается
+    assert_eq!(resolved.sample_count, config.sample_count);
+    assert_eq!(
+        resolved.shadows.cascade_count(),
+        config.shadows.cascade_count()
+    );
+}
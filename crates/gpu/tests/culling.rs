@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use gpu::{BoundingSphere, Camera, Mat4, Mesh, Object, Object3D, Vec3, Vertex};
+
+mod common;
+use common::camera;
+
+#[test]
+fn camera_frustum_matches_view_direction() {
+    let camera = Camera::new(
+        Vec3::new(0.0, 2.0, 10.0),
+        Vec3::new(0.0, 2.0, 0.0),
+        Vec3::Y,
+        60f32.to_radians(),
+        0.1,
+        50.0,
+    );
+    let frustum = camera.frustum(16.0 / 9.0);
+    assert!(frustum.contains_point(camera.target));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 2.0, 12.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 2.0, -45.0)));
+    // шире по горизонтали, чем по вертикали
+    assert!(frustum.contains_point(Vec3::new(7.0, 2.0, 0.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 9.0, 0.0)));
+    assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(0.0, 9.0, 0.0), 3.0)));
+}
+
+#[test]
+fn objects_behind_the_camera_are_rejected() {
+    let camera = camera(Vec3::new(0.0, 0.0, 10.0));
+    let frustum = camera.frustum(1.0);
+    let v = |x: f32, y: f32| Vertex {
+        position: [x, y, 0.0],
+        ..Default::default()
+    };
+    let triangle = Arc::new(Mesh::new(
+        vec![v(-1.0, -1.0), v(1.0, -1.0), v(0.0, 1.0)],
+        vec![0, 1, 2],
+    ));
+    let triangle_at = |z: f32| {
+        Object3D::from_mesh(
+            triangle.clone(),
+            Mat4::from_translation(Vec3::new(0.0, 0.0, z)),
+        )
+    };
+    // перед камерой, за ней и на ближней плоскости
+    let visible: Vec<bool> = [0.0, 15.0, 10.0]
+        .map(|z| frustum.intersects_sphere(&triangle_at(z).world_bounding_sphere()))
+        .to_vec();
+    assert_eq!(visible, [true, false, true]);
+    // за дальней плоскостью
+    assert!(!frustum.intersects_sphere(&triangle_at(-95.0).world_bounding_sphere()));
+}
@@ -0,0 +1,38 @@
+use gpu::{DebugDraw, Mat4, Vec3};
+
+#[test]
+fn shapes_emit_expected_segments() {
+    let mut debug = DebugDraw::new();
+    debug.aabb(Vec3::ZERO, Vec3::ONE, [1.0, 0.0, 0.0]);
+    assert_eq!(debug.lines().len(), 12);
+
+    debug.clear();
+    debug.axes(Mat4::IDENTITY, 1.0);
+    // три стрелки: древко и четыре штриха наконечника
+    assert_eq!(debug.lines().len(), 15);
+
+    debug.clear();
+    debug.polyline(&[Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z], [1.0; 3]);
+    assert_eq!(debug.lines().len(), 3);
+}
+
+#[test]
+fn lifetime_counts_frames() {
+    let mut debug = DebugDraw::new();
+    debug.line(Vec3::ZERO, Vec3::X, [1.0; 3]);
+    debug.lifetime = 3;
+    debug.depth_test = false;
+    debug.line(Vec3::ZERO, Vec3::Y, [1.0; 3]);
+    debug.text3d(Vec3::ZERO, "t = 1.5", 0.1, [1.0; 3]);
+
+    debug.end_frame();
+    assert_eq!(debug.lines().len(), 1);
+    assert!(!debug.lines()[0].depth_test);
+    assert_eq!(debug.texts().len(), 1);
+
+    debug.end_frame();
+    assert_eq!(debug.lines().len(), 1);
+    debug.end_frame();
+    assert!(debug.lines().is_empty());
+    assert!(debug.texts().is_empty());
+}
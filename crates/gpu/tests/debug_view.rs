@@ -0,0 +1,14 @@
+use gpu::{DebugView, Shading};
+
+#[test]
+fn toggling_active_shading_returns_to_lit() {
+    let mut view = DebugView::default();
+    view.toggle_shading(Shading::Depth);
+    assert_eq!(view.shading, Shading::Depth);
+
+    view.toggle_shading(Shading::Backfaces);
+    assert_eq!(view.shading, Shading::Backfaces);
+
+    view.toggle_shading(Shading::Backfaces);
+    assert_eq!(view.shading, Shading::Lit);
+}
@@ -0,0 +1,51 @@
+use gpu::{Environment, EnvironmentError, Scene, Texture, Vec3};
+
+#[test]
+fn uniform_sky_lights_every_side_equally() {
+    // одноцветная панорама: облучённость по любой нормали равна её цвету
+    let environment = Environment::equirectangular(&Texture::solid([255, 255, 255, 255]).linear())
+        .with_intensity(0.5);
+    for normal in [Vec3::Y, -Vec3::Y, Vec3::X, Vec3::new(0.3, -0.5, 0.8)] {
+        let ambient = environment.ambient(normal);
+        assert!((ambient - Vec3::splat(0.5)).abs().max_element() < 1e-3);
+    }
+}
+
+#[test]
+fn gradient_lights_from_above() {
+    let environment = Environment::gradient([0.0, 0.0, 1.0], [0.5, 0.5, 0.5], [1.0, 0.0, 0.0]);
+    let up = environment.ambient(Vec3::Y);
+    let down = environment.ambient(-Vec3::Y);
+    assert!(up.z > up.x);
+    assert!(down.x > down.z);
+}
+
+#[test]
+fn cube_map_rejects_mismatched_faces() {
+    let face = || Texture::from_rgba8(2, 2, vec![0; 16]).unwrap();
+    let wide = Texture::from_rgba8(4, 2, vec![0; 32]).unwrap();
+    let error = Environment::cube_map([face(), face(), face(), wide, face(), face()]).unwrap_err();
+    assert_eq!(
+        error,
+        EnvironmentError::FaceSize {
+            face: 3,
+            width: 4,
+            height: 2,
+            expected: 2,
+        }
+    );
+    assert!(Environment::cube_map(std::array::from_fn(|_| face())).is_ok());
+}
+
+#[test]
+fn scene_environment_replaces_ambient() {
+    let mut scene = Scene::new();
+    assert!(scene.environment().is_none());
+
+    scene.set_environment(Environment::gradient([0.2; 3], [0.4; 3], [0.1; 3]));
+    scene.environment_mut().unwrap().intensity = 2.0;
+    assert_eq!(scene.environment().unwrap().intensity, 2.0);
+
+    scene.clear_environment();
+    assert!(scene.environment().is_none());
+}
@@ -0,0 +1,80 @@
+use std::{mem, sync::Arc};
+
+use gpu::{Instance, InstancedObject, Mat4, Mesh, Vec3, Vertex, wgpu};
+
+mod common;
+use common::camera;
+
+#[test]
+fn instance_layout_follows_vertex_layout() {
+    let layout = Instance::desc();
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Instance);
+    assert_eq!(layout.array_stride, mem::size_of::<Instance>() as u64);
+
+    // атрибуты экземпляра идут сразу за вершинными и не пересекаются с ними
+    let vertex_locations = Vertex::desc().attributes.iter().map(|a| a.shader_location);
+    let first = vertex_locations.max().unwrap() + 1;
+    let locations: Vec<u32> = layout
+        .attributes
+        .iter()
+        .map(|a| a.shader_location)
+        .collect();
+    assert_eq!(locations, (first..first + 5).collect::<Vec<_>>());
+
+    // четыре столбца матрицы, затем цвет — как поля структуры
+    let offsets: Vec<u64> = layout.attributes.iter().map(|a| a.offset).collect();
+    let color = mem::offset_of!(Instance, color) as u64;
+    assert_eq!(offsets, [0, 16, 32, 48, color]);
+    let last = layout.attributes.last().unwrap();
+    assert!(last.offset + last.format.size() <= layout.array_stride);
+
+    let instances = [
+        Instance::new(Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)), [0.5; 3]),
+        Instance::default(),
+    ];
+    let bytes = Instance::as_byte_slice(&instances);
+    assert_eq!(bytes.len() as u64, 2 * layout.array_stride);
+    // перенос — четвёртый столбец первого экземпляра
+    let column: Vec<f32> = bytes[48..64]
+        .chunks_exact(4)
+        .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(column, [1.0, 2.0, 3.0, 1.0]);
+}
+
+#[test]
+fn instances_are_culled_individually() {
+    let v = |x: f32, y: f32| Vertex {
+        position: [x, y, 0.0],
+        ..Default::default()
+    };
+    let mesh = Mesh::new(
+        vec![v(-0.5, -0.5), v(0.5, -0.5), v(0.0, 0.5)],
+        vec![0, 1, 2],
+    );
+    let object = InstancedObject::new(Arc::new(mesh), Mat4::from_translation(Vec3::X))
+        .with_instances(vec![
+            Instance::default(),
+            // за камерой
+            Instance::new(Mat4::from_translation(Vec3::new(0.0, 0.0, 20.0)), [1.0; 3]),
+            Instance::new(Mat4::from_scale(Vec3::splat(3.0)), [1.0; 3]),
+        ]);
+    let frustum = camera(Vec3::new(0.0, 0.0, 10.0)).frustum(1.0);
+
+    let spheres: Vec<_> = object
+        .instances()
+        .iter()
+        .map(|instance| object.instance_bounding_sphere(instance))
+        .collect();
+    // model_matrix группы применяется поверх трансформации экземпляра
+    assert_eq!(
+        spheres[0].center,
+        Vec3::X + object.local_bounding_sphere().center
+    );
+    assert!((spheres[2].radius - 3.0 * spheres[0].radius).abs() < 1e-5);
+    let visible: Vec<bool> = spheres
+        .iter()
+        .map(|sphere| frustum.intersects_sphere(sphere))
+        .collect();
+    assert_eq!(visible, [true, false, true]);
+}
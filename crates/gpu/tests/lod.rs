@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use gpu::{
+    BoundingSphere, Camera, LodLevel, Mesh, Vec3,
+    lod::{screen_size, select_lod},
+};
+
+fn lods() -> Vec<LodLevel> {
+    [0.5, 0.25]
+        .map(|size| LodLevel::new(Arc::new(Mesh::default()), size))
+        .to_vec()
+}
+
+#[test]
+fn screen_size_shrinks_with_distance() {
+    let camera = Camera::new(
+        Vec3::ZERO,
+        -Vec3::Z,
+        Vec3::Y,
+        90f32.to_radians(),
+        0.1,
+        100.0,
+    );
+    let sphere = |z| BoundingSphere::new(Vec3::new(0.0, 0.0, z), 1.0);
+    assert!((screen_size(&camera, &sphere(-1.0 / 0.5)) - 0.5).abs() < 1e-5);
+    assert!(screen_size(&camera, &sphere(-10.0)) < screen_size(&camera, &sphere(-5.0)));
+    assert_eq!(screen_size(&camera, &sphere(-0.5)), f32::INFINITY);
+}
+
+#[test]
+fn lod_switches_with_hysteresis() {
+    let lods = lods();
+    assert_eq!(select_lod(&lods, 1.0, 0, 0.1), 0);
+    assert_eq!(select_lod(&lods, 0.3, 0, 0.1), 1);
+    assert_eq!(select_lod(&lods, 0.1, 0, 0.1), 2);
+    // рядом с порогом уровень не меняется ни в одну сторону
+    assert_eq!(select_lod(&lods, 0.48, 0, 0.1), 0);
+    assert_eq!(select_lod(&lods, 0.52, 1, 0.1), 1);
+    assert_eq!(select_lod(&lods, 0.6, 1, 0.1), 0);
+    assert_eq!(select_lod(&lods, 0.6, 5, 0.1), 0);
+    assert_eq!(select_lod(&[], 0.01, 3, 0.1), 0);
+}
@@ -0,0 +1,108 @@
+use gpu::{Mat4, Object, Object3D, Ray, Scene, SceneHit, Vec3, Vertex};
+
+mod common;
+use common::camera;
+
+fn quad(z: f32) -> Object3D {
+    let vertex = |x, y| Vertex {
+        position: [x, y, z],
+        ..Default::default()
+    };
+    Object3D::new(
+        vec![
+            vertex(-1.0, -1.0),
+            vertex(1.0, -1.0),
+            vertex(1.0, 1.0),
+            vertex(-1.0, 1.0),
+        ],
+        vec![0, 1, 2, 0, 2, 3],
+        Mat4::IDENTITY,
+    )
+}
+
+#[test]
+fn pixel_rays_go_through_the_frame() {
+    let camera = camera(Vec3::new(0.0, 0.0, 10.0));
+    let center = camera.ray_from_pixel(400.0, 300.0, 800.0, 600.0);
+    assert!((center.direction - -Vec3::Z).length() < 1e-5);
+    assert!((center.origin.z - (10.0 - camera.near)).abs() < 1e-3);
+    // верх кадра — вверх, левый край — влево
+    assert!(camera.ray_from_pixel(400.0, 0.0, 800.0, 600.0).direction.y > 0.0);
+    assert!(camera.ray_from_pixel(0.0, 300.0, 800.0, 600.0).direction.x < 0.0);
+}
+
+#[test]
+fn scene_raycast_returns_nearest_object() {
+    let mut scene = Scene::new();
+    let far = scene.add_object(quad(-2.0));
+    let near = scene.add_object(quad(1.0));
+    let camera = camera(Vec3::new(0.0, 0.0, 10.0));
+
+    let hit = scene
+        .raycast(&camera.ray_from_pixel(400.0, 300.0, 800.0, 600.0))
+        .unwrap();
+    assert_eq!(hit.object, near);
+    assert!((hit.hit.distance - (9.0 - camera.near)).abs() < 1e-3);
+    assert!((hit.hit.normal - Vec3::Z).length() < 1e-5);
+
+    scene.objects_mut()[near].set_model_matrix(Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)));
+    let hit = scene
+        .raycast(&camera.ray_from_pixel(400.0, 300.0, 800.0, 600.0))
+        .unwrap();
+    assert_eq!(hit.object, far);
+    assert!(
+        scene
+            .raycast(&camera.ray_from_pixel(0.0, 0.0, 800.0, 600.0))
+            .is_none()
+    );
+}
+
+fn linear_raycast(scene: &Scene, ray: &Ray) -> Option<usize> {
+    scene
+        .objects()
+        .iter()
+        .enumerate()
+        .filter_map(|(object, obj)| {
+            obj.raycast(ray, f32::INFINITY)
+                .map(|hit| SceneHit { object, hit })
+        })
+        .min_by(|a, b| a.hit.distance.total_cmp(&b.hit.distance))
+        .map(|hit| hit.object)
+}
+
+#[test]
+fn scene_raycast_matches_brute_force() {
+    let mut scene = Scene::new();
+    let mesh = quad(0.0).mesh().clone();
+    for i in 0..25 {
+        let (x, y) = ((i % 5) as f32 * 1.5 - 3.0, (i / 5) as f32 * 1.5 - 3.0);
+        let model = Mat4::from_translation(Vec3::new(x, y, -(i % 3) as f32))
+            * Mat4::from_scale(Vec3::splat(0.5));
+        scene.add_object(Object3D::from_mesh(mesh.clone(), model));
+    }
+    let camera = camera(Vec3::new(1.0, 2.0, 10.0));
+    let check = |scene: &Scene| {
+        let mut hits = 0;
+        for (x, y) in (0..800)
+            .step_by(37)
+            .flat_map(|x| (0..600).step_by(41).map(move |y| (x, y)))
+        {
+            let ray = camera.ray_from_pixel(x as f32, y as f32, 800.0, 600.0);
+            let hit = scene.raycast(&ray).map(|hit| hit.object);
+            assert_eq!(hit, linear_raycast(scene, &ray));
+            hits += hit.is_some() as usize;
+        }
+        assert!(hits > 20);
+    };
+    check(&scene);
+
+    // сдвиг через `objects_mut` и новый объект: дерево подгоняется и перестраивается
+    for (i, object) in scene.objects_mut().iter_mut().enumerate() {
+        let model = Mat4::from_translation(Vec3::new(0.3 * (i % 4) as f32, 0.0, (i % 2) as f32))
+            * object.model_matrix();
+        object.set_model_matrix(model);
+    }
+    check(&scene);
+    scene.add_object(quad(2.0));
+    check(&scene);
+}
@@ -0,0 +1,60 @@
+use gpu::{POST_EFFECT_HEADER, PostEffect, PostEffectList, VIGNETTE_EFFECT_SHADER};
+
+#[test]
+fn source_starts_with_common_header() {
+    let effect = PostEffect::new("Vignette", VIGNETTE_EFFECT_SHADER);
+    let source = effect.source();
+
+    assert!(source.starts_with(POST_EFFECT_HEADER));
+    assert!(source.ends_with(VIGNETTE_EFFECT_SHADER));
+}
+
+#[test]
+fn params_are_padded_to_vec4() {
+    let floats = |effect: &PostEffect| -> Vec<f32> {
+        effect
+            .params_bytes()
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes(b.try_into().unwrap()))
+            .collect()
+    };
+    // без параметров — один нулевой vec4: uniform-буфер не бывает пустым
+    let effect = PostEffect::new("Grain", "");
+    assert_eq!(effect.params_bytes().len(), 16);
+    assert_eq!(floats(&effect), [0.0; 4]);
+
+    assert_eq!(floats(&effect.clone().with_params(&[1.0; 4])), [1.0; 4]);
+    let five = floats(&effect.with_params(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+    assert_eq!(five, [1.0, 2.0, 3.0, 4.0, 5.0, 0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn effects_keep_ids_when_reordered() {
+    let mut list = PostEffectList::new();
+    let grain = list.insert(usize::MAX, "grain");
+    let vignette = list.insert(usize::MAX, "vignette");
+    // вставка в начало и за конец
+    let blur = list.insert(0, "blur");
+    let sharpen = list.insert(10, "sharpen");
+    assert_eq!(list.ids(), [blur, grain, vignette, sharpen]);
+    assert_eq!(
+        list.iter().copied().collect::<Vec<_>>(),
+        ["blur", "grain", "vignette", "sharpen"]
+    );
+
+    assert!(list.move_to(sharpen, 1));
+    assert!(list.move_to(blur, usize::MAX));
+    assert_eq!(list.ids(), [sharpen, grain, vignette, blur]);
+
+    assert_eq!(list.remove(grain), Some("grain"));
+    assert_eq!(list.remove(grain), None);
+    assert!(!list.move_to(grain, 0));
+    assert_eq!(list.ids(), [sharpen, vignette, blur]);
+    assert_eq!(list.get(vignette), Some(&"vignette"));
+
+    // новые id не повторяют удалённые, в том числе после пересборки
+    let mut rebuilt = list.filter_map(|name| (*name != "blur").then_some(name.len()));
+    assert_eq!(rebuilt.ids(), [sharpen, vignette]);
+    let fresh = rebuilt.insert(0, 0);
+    assert!(![grain, blur, sharpen, vignette].contains(&fresh));
+}
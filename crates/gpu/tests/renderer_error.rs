@@ -0,0 +1,54 @@
+use std::io;
+
+use gpu::{RendererError, wgpu};
+
+fn validation(description: &str) -> wgpu::Error {
+    wgpu::Error::Validation {
+        source: Box::new(io::Error::other(description.to_string())),
+        description: description.to_string(),
+    }
+}
+
+#[test]
+fn surface_errors_skip_the_frame() {
+    for error in [
+        wgpu::SurfaceError::Lost,
+        wgpu::SurfaceError::Outdated,
+        wgpu::SurfaceError::Timeout,
+    ] {
+        assert!(
+            RendererError::from_surface(error.clone()).is_none(),
+            "{error:?}"
+        );
+    }
+    assert!(matches!(
+        RendererError::from_surface(wgpu::SurfaceError::OutOfMemory),
+        Some(RendererError::OutOfMemory)
+    ));
+}
+
+#[test]
+fn device_errors_are_classified() {
+    let out_of_memory = wgpu::Error::OutOfMemory {
+        source: Box::new(io::Error::other("out of memory")),
+    };
+    assert!(matches!(
+        RendererError::from_device(out_of_memory),
+        RendererError::OutOfMemory
+    ));
+
+    // так wgpu описывает команду на потерянном устройстве
+    let lost = validation(
+        "Validation Error\n\nCaused by:\n    In Queue::write_buffer\n    parent device is lost\n",
+    );
+    assert!(matches!(
+        RendererError::from_device(lost),
+        RendererError::DeviceLost
+    ));
+
+    // остальное сохраняется с описанием, а не роняет процесс
+    match RendererError::from_device(validation("Buffer is too small")) {
+        RendererError::Validation(description) => assert_eq!(description, "Buffer is too small"),
+        other => panic!("expected Validation, got {other:?}"),
+    }
+}
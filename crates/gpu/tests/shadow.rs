@@ -0,0 +1,47 @@
+use gpu::{ShadowConfig, Vec3, Vec4Swizzles, shadow};
+
+mod common;
+use common::camera;
+
+#[test]
+fn splits_cover_shadow_distance() {
+    let config = ShadowConfig::new().with_cascades(3).with_max_distance(40.0);
+    let cascades = shadow::cascades(
+        &camera(Vec3::new(2.0, 3.0, 5.0)),
+        1.5,
+        Vec3::new(0.3, 1.0, 0.4),
+        &config,
+    );
+
+    assert_eq!(cascades.len(), 3);
+    assert!(
+        cascades
+            .windows(2)
+            .all(|pair| pair[0].split < pair[1].split)
+    );
+    assert!((cascades[2].split - 40.0).abs() < 1e-3);
+    // ближние каскады подробнее дальних
+    assert!(cascades[0].texel_size < cascades[2].texel_size);
+    // число каскадов ограничено шейдером
+    let many = ShadowConfig::new().with_cascades(10);
+    assert_eq!(many.cascade_count(), shadow::MAX_CASCADES);
+}
+
+#[test]
+fn cascade_contains_its_frustum_slice() {
+    let camera = camera(Vec3::new(2.0, 3.0, 5.0));
+    let aspect = 1.5;
+    let config = ShadowConfig::new().with_cascades(4).with_max_distance(30.0);
+    let cascades = shadow::cascades(&camera, aspect, Vec3::new(-0.5, 1.0, 0.2), &config);
+
+    let mut near = camera.near;
+    for cascade in &cascades {
+        for corner in camera.frustum_corners(aspect, near, cascade.split) {
+            let clip = cascade.view_proj * corner.extend(1.0);
+            let ndc = clip.xyz() / clip.w;
+            assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0, "{ndc}");
+            assert!((0.0..=1.0).contains(&ndc.z), "{ndc}");
+        }
+        near = cascade.split;
+    }
+}
@@ -0,0 +1,42 @@
+use gpu::{Mat4, Object, Object3D, Scene, Trail, Vec3};
+
+#[test]
+fn trail_keeps_at_most_max_points() {
+    let mut trail = Trail::new(3);
+    for i in 0..5 {
+        trail.push(Vec3::new(i as f32, 0.0, 0.0), [1.0; 3]);
+    }
+    let xs: Vec<f32> = trail.points().map(|p| p.position.x).collect();
+    assert_eq!(xs, [2.0, 3.0, 4.0]);
+
+    // стоящий объект не плодит точки
+    trail.push(Vec3::new(4.0, 0.0, 0.0), [1.0; 3]);
+    assert_eq!(trail.points().len(), 3);
+}
+
+#[test]
+fn trail_drops_points_older_than_max_age() {
+    let mut trail = Trail::new(100).with_max_age(1.0);
+    for i in 0..10 {
+        trail.push(Vec3::new(i as f32, 0.0, 0.0), [1.0; 3]);
+        trail.advance(0.25);
+    }
+    // время 2.5 с: остались точки, записанные не раньше 1.5 с
+    assert_eq!(trail.points().len(), 4);
+    assert!(trail.points().all(|p| p.time >= 1.5));
+}
+
+#[test]
+fn scene_trail_follows_object() {
+    let mut scene = Scene::new();
+    let object = scene.add_object(Object3D::new(Vec::new(), Vec::new(), Mat4::IDENTITY));
+    let trail = scene.add_trail(Trail::new(10).following(object));
+
+    for step in 1..=3 {
+        scene.objects_mut()[object].translate(Vec3::X);
+        scene.update_trails(0.1);
+        assert_eq!(scene.trails()[trail].points().len(), step);
+    }
+    let last = scene.trails()[trail].points().last().unwrap().position;
+    assert_eq!(last, Vec3::new(3.0, 0.0, 0.0));
+}
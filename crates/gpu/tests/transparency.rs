@@ -0,0 +1,34 @@
+use gpu::{Mat4, Object, Object3D, RendererConfig, Transparency, Vec3, transparency::view_depth};
+
+mod common;
+use common::camera;
+
+#[test]
+fn view_depth_orders_along_view_direction() {
+    let camera = camera(Vec3::new(0.0, 0.0, 5.0));
+    let near = view_depth(&camera, Vec3::new(0.0, 0.0, 2.0));
+    let far = view_depth(&camera, Vec3::new(0.0, 0.0, -2.0));
+    assert!((near - 3.0).abs() < 1e-5);
+    assert!((far - 7.0).abs() < 1e-5);
+    // смещение вбок не меняет глубину
+    let side = view_depth(&camera, Vec3::new(4.0, 1.0, 2.0));
+    assert!((side - near).abs() < 1e-5);
+}
+
+#[test]
+fn transparency_modes_cycle() {
+    assert_eq!(Transparency::default(), Transparency::Sorted);
+    assert_eq!(Transparency::Sorted.next(), Transparency::WeightedBlended);
+    assert_eq!(Transparency::WeightedBlended.next(), Transparency::Sorted);
+    let config = RendererConfig::new().with_transparency(Transparency::WeightedBlended);
+    assert_eq!(config.transparency, Transparency::WeightedBlended);
+}
+
+#[test]
+fn opacity_comes_from_material() {
+    let object = Object3D::new(Vec::new(), Vec::new(), Mat4::IDENTITY);
+    assert_eq!(object.opacity(), 1.0);
+    let glass = object.with_opacity(0.25);
+    assert_eq!(glass.opacity(), 0.25);
+    assert_eq!(glass.material().unwrap().opacity, 0.25);
+}
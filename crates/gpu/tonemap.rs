@@ -0,0 +1,411 @@
+use wgpu::util::DeviceExt;
+
+use crate::shaders::{AUTO_EXPOSURE_SHADER, FULLSCREEN_VERTEX_SHADER, TONE_MAP_FRAGMENT_SHADER};
+
+/// Формат промежуточного HDR-кадра, в который рисуется сцена
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Кривая, сжимающая HDR-яркость в диапазон дисплея
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ToneMapper {
+    #[default]
+    Aces,
+    Reinhard,
+    AgX,
+}
+
+impl ToneMapper {
+    /// следующий оператор по кругу
+    pub fn next(self) -> Self {
+        match self {
+            Self::Aces => Self::Reinhard,
+            Self::Reinhard => Self::AgX,
+            Self::AgX => Self::Aces,
+        }
+    }
+}
+
+/// Экспозиция кадра в стопах (EV): ручная или по гистограмме яркости
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Exposure {
+    Manual {
+        ev: f32,
+    },
+    /// средняя яркость кадра приводится к среднему серому
+    Auto {
+        /// поправка к подобранной экспозиции
+        compensation: f32,
+        /// диапазон гистограммы: log2 яркости
+        min_ev: f32,
+        max_ev: f32,
+        /// скорость привыкания глаза, 1/с
+        speed: f32,
+    },
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Self::Manual { ev: 0.0 }
+    }
+}
+
+impl Exposure {
+    pub fn manual(ev: f32) -> Self {
+        Self::Manual { ev }
+    }
+
+    /// автоэкспозиция с диапазоном -8..8 EV
+    pub fn auto() -> Self {
+        Self::Auto {
+            compensation: 0.0,
+            min_ev: -8.0,
+            max_ev: 8.0,
+            speed: 1.5,
+        }
+    }
+
+    pub fn is_auto(&self) -> bool {
+        matches!(self, Self::Auto { .. })
+    }
+}
+
+/// Настройки тональной компрессии
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ToneMapping {
+    pub operator: ToneMapper,
+    pub exposure: Exposure,
+}
+
+impl ToneMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_operator(mut self, operator: ToneMapper) -> Self {
+        self.operator = operator;
+        self
+    }
+
+    pub fn with_exposure(mut self, exposure: Exposure) -> Self {
+        self.exposure = exposure;
+        self
+    }
+}
+
+/// Параметры шейдеров (`ToneMapParams` в WGSL)
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct ToneMapParams {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    adaptation: f32,
+    exposure: f32,
+    auto_exposure: u32,
+    tone_mapper: u32,
+    pixel_count: u32,
+    _padding: u32,
+}
+
+impl ToneMapParams {
+    fn new(settings: &ToneMapping, delta_time: f32, pixel_count: u32) -> Self {
+        let (exposure, min_ev, max_ev, speed) = match settings.exposure {
+            Exposure::Manual { ev } => (ev, 0.0, 1.0, 0.0),
+            Exposure::Auto {
+                compensation,
+                min_ev,
+                max_ev,
+                speed,
+            } => (compensation, min_ev, max_ev, speed),
+        };
+        Self {
+            min_log_luminance: min_ev,
+            log_luminance_range: (max_ev - min_ev).max(1e-3),
+            adaptation: 1.0 - (-delta_time * speed).exp(),
+            exposure: exposure.exp2(),
+            auto_exposure: settings.exposure.is_auto() as u32,
+            tone_mapper: match settings.operator {
+                ToneMapper::Aces => 0,
+                ToneMapper::Reinhard => 1,
+                ToneMapper::AgX => 2,
+            },
+            pixel_count,
+            _padding: 0,
+        }
+    }
+
+    fn as_byte_slice(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
+
+/// Проход тональной компрессии HDR-кадра в surface и вычисление автоэкспозиции
+pub(crate) struct ToneMapPass {
+    pipeline: wgpu::RenderPipeline,
+    histogram_pipeline: wgpu::ComputePipeline,
+    average_pipeline: wgpu::ComputePipeline,
+    layout: wgpu::BindGroupLayout,
+    compute_layout: wgpu::BindGroupLayout,
+    params: wgpu::Buffer,
+    // 256 корзин гистограммы и сглаженная средняя яркость
+    histogram: wgpu::Buffer,
+    exposure: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    compute_bind_group: wgpu::BindGroup,
+}
+
+impl ToneMapPass {
+    pub fn new(
+        device: &wgpu::Device,
+        output_format: wgpu::TextureFormat,
+        hdr: &wgpu::TextureView,
+    ) -> Self {
+        let entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty,
+            count: None,
+        };
+        let hdr_entry = wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        };
+        let uniform_entry = wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let storage_entry = |read_only| wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tone Map BGL"),
+            entries: &[
+                entry(0, wgpu::ShaderStages::FRAGMENT, hdr_entry),
+                entry(1, wgpu::ShaderStages::FRAGMENT, uniform_entry),
+                entry(2, wgpu::ShaderStages::FRAGMENT, storage_entry(true)),
+            ],
+        });
+        let compute_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Auto Exposure BGL"),
+            entries: &[
+                entry(0, wgpu::ShaderStages::COMPUTE, hdr_entry),
+                entry(1, wgpu::ShaderStages::COMPUTE, uniform_entry),
+                entry(2, wgpu::ShaderStages::COMPUTE, storage_entry(false)),
+                entry(3, wgpu::ShaderStages::COMPUTE, storage_entry(false)),
+            ],
+        });
+
+        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fullscreen Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(FULLSCREEN_VERTEX_SHADER.into()),
+        });
+        let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tone Map Fragment Shader"),
+            source: wgpu::ShaderSource::Wgsl(TONE_MAP_FRAGMENT_SHADER.into()),
+        });
+        let compute_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Auto Exposure Shader"),
+            source: wgpu::ShaderSource::Wgsl(AUTO_EXPOSURE_SHADER.into()),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tone Map Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tone Map Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &vs_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fs_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Auto Exposure Pipeline Layout"),
+                bind_group_layouts: &[&compute_layout],
+                push_constant_ranges: &[],
+            });
+        let compute_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&compute_pipeline_layout),
+                module: &compute_module,
+                entry_point,
+            })
+        };
+        let histogram_pipeline =
+            compute_pipeline("Luminance Histogram Pipeline", "build_histogram");
+        let average_pipeline = compute_pipeline("Average Luminance Pipeline", "average_luminance");
+
+        let params = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tone Map Params"),
+            size: std::mem::size_of::<ToneMapParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let histogram = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Luminance Histogram"),
+            size: 256 * 4,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        // начальная яркость — средний серый, т.е. экспозиция 1
+        let exposure = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Exposure"),
+            contents: &0.18f32.to_ne_bytes(),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        let (bind_group, compute_bind_group) = Self::bind_groups(
+            device,
+            &layout,
+            &compute_layout,
+            hdr,
+            &params,
+            &histogram,
+            &exposure,
+        );
+        Self {
+            pipeline,
+            histogram_pipeline,
+            average_pipeline,
+            layout,
+            compute_layout,
+            params,
+            histogram,
+            exposure,
+            bind_group,
+            compute_bind_group,
+        }
+    }
+
+    /// новый HDR-кадр после ресайза или смены MSAA
+    pub fn set_input(&mut self, device: &wgpu::Device, hdr: &wgpu::TextureView) {
+        (self.bind_group, self.compute_bind_group) = Self::bind_groups(
+            device,
+            &self.layout,
+            &self.compute_layout,
+            hdr,
+            &self.params,
+            &self.histogram,
+            &self.exposure,
+        );
+    }
+
+    /// Записать параметры кадра и добавить в энкодер автоэкспозицию и компрессию в `output`
+    pub fn encode(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        settings: &ToneMapping,
+        delta_time: f32,
+        (width, height): (u32, u32),
+    ) {
+        let params = ToneMapParams::new(settings, delta_time, width * height);
+        queue.write_buffer(&self.params, 0, params.as_byte_slice());
+
+        if settings.exposure.is_auto() {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Auto Exposure Pass"),
+            });
+            cpass.set_bind_group(0, &self.compute_bind_group, &[]);
+            cpass.set_pipeline(&self.histogram_pipeline);
+            cpass.dispatch_workgroups(width.div_ceil(16), height.div_ceil(16), 1);
+            cpass.set_pipeline(&self.average_pipeline);
+            cpass.dispatch_workgroups(1, 1, 1);
+        }
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tone Map Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+
+    fn bind_groups(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        compute_layout: &wgpu::BindGroupLayout,
+        hdr: &wgpu::TextureView,
+        params: &wgpu::Buffer,
+        histogram: &wgpu::Buffer,
+        exposure: &wgpu::Buffer,
+    ) -> (wgpu::BindGroup, wgpu::BindGroup) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Tone Map Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(hdr),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: exposure.as_entire_binding(),
+                },
+            ],
+        });
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Auto Exposure Bind Group"),
+            layout: compute_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(hdr),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: histogram.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: exposure.as_entire_binding(),
+                },
+            ],
+        });
+        (bind_group, compute_bind_group)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use glam::Vec3;
+
+use crate::Vertex;
+
+/// Как рисовать след
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TrailStyle {
+    /// ломаная постоянной толщины в пикселях
+    Line { width: f32 },
+    /// лента шириной `width` в мировых единицах, развёрнутая к камере
+    Ribbon { width: f32 },
+}
+
+impl Default for TrailStyle {
+    fn default() -> Self {
+        Self::Line { width: 2.0 }
+    }
+}
+
+/// Точка следа
+#[derive(Copy, Clone, Debug)]
+pub struct TrailPoint {
+    pub position: Vec3,
+    pub color: [f32; 3],
+    /// время записи в секундах от создания следа
+    pub time: f32,
+}
+
+/// След объекта: история мировых позиций с ограничением по числу точек и/или возрасту
+#[derive(Clone, Debug)]
+pub struct Trail {
+    points: VecDeque<TrailPoint>,
+    time: f32,
+    /// индекс объекта сцены, за которым след следует сам (см. `Scene::update_trails`)
+    pub target: Option<usize>,
+    pub max_points: usize,
+    /// точки старше этого числа секунд удаляются
+    pub max_age: Option<f32>,
+    /// цвет точек, записанных через `Scene::update_trails`
+    pub color: [f32; 3],
+    pub style: TrailStyle,
+    /// старые точки темнеют к чёрному
+    pub fade: bool,
+    /// новая точка не добавляется ближе этого расстояния к предыдущей
+    pub min_distance: f32,
+}
+
+impl Trail {
+    pub fn new(max_points: usize) -> Self {
+        Self {
+            points: VecDeque::with_capacity(max_points),
+            time: 0.0,
+            target: None,
+            max_points,
+            max_age: None,
+            color: [1.0, 1.0, 1.0],
+            style: TrailStyle::default(),
+            fade: true,
+            min_distance: 1e-4,
+        }
+    }
+
+    /// следовать за объектом сцены с индексом `object`
+    pub fn following(mut self, object: usize) -> Self {
+        self.target = Some(object);
+        self
+    }
+
+    pub fn with_max_age(mut self, seconds: f32) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 3]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_style(mut self, style: TrailStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn points(&self) -> impl ExactSizeIterator<Item = &TrailPoint> {
+        self.points.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// добавить точку с текущим временем следа
+    pub fn push(&mut self, position: Vec3, color: [f32; 3]) {
+        if let Some(last) = self.points.back()
+            && last.position.distance(position) < self.min_distance
+        {
+            return;
+        }
+        self.points.push_back(TrailPoint {
+            position,
+            color,
+            time: self.time,
+        });
+        while self.points.len() > self.max_points {
+            self.points.pop_front();
+        }
+    }
+
+    /// продвинуть время и удалить устаревшие точки
+    pub fn advance(&mut self, delta_time: f32) {
+        self.time += delta_time;
+        if let Some(max_age) = self.max_age {
+            while self
+                .points
+                .front()
+                .is_some_and(|p| self.time - p.time > max_age)
+            {
+                self.points.pop_front();
+            }
+        }
+    }
+
+    /// цвет точки с учётом затухания: по возрасту, если он ограничен, иначе по порядку
+    fn faded_color(&self, index: usize, point: &TrailPoint) -> [f32; 3] {
+        if !self.fade {
+            return point.color;
+        }
+        let factor = match self.max_age {
+            Some(max_age) if max_age > 0.0 => 1.0 - (self.time - point.time) / max_age,
+            _ => (index + 1) as f32 / self.points.len() as f32,
+        }
+        .clamp(0.0, 1.0);
+        point.color.map(|c| c * factor)
+    }
+
+    /// концы отрезков подряд [начало, конец, ...] для линейного пайплайна
+    pub(crate) fn line_vertices(&self) -> Vec<Vertex> {
+        let vertices: Vec<Vertex> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| Vertex {
+                position: point.position.to_array(),
+                color: self.faded_color(i, point),
+                ..Default::default()
+            })
+            .collect();
+        vertices
+            .windows(2)
+            .flat_map(|pair| [pair[0], pair[1]])
+            .collect()
+    }
+
+    /// треугольники ленты (по 6 вершин на отрезок), повёрнутой к `eye`
+    pub(crate) fn ribbon_vertices(&self, eye: Vec3, width: f32) -> Vec<Vertex> {
+        let points: Vec<&TrailPoint> = self.points.iter().collect();
+        let edge = |i: usize| -> [Vertex; 2] {
+            let point = points[i];
+            let prev = points[i.saturating_sub(1)].position;
+            let next = points[(i + 1).min(points.len() - 1)].position;
+            let tangent = (next - prev).normalize_or_zero();
+            let side = tangent.cross(eye - point.position).normalize_or_zero() * (width / 2.0);
+            let color = self.faded_color(i, point);
+            [point.position - side, point.position + side].map(|position| Vertex {
+                position: position.to_array(),
+                color,
+                ..Default::default()
+            })
+        };
+
+        let mut vertices = Vec::with_capacity(points.len().saturating_sub(1) * 6);
+        for i in 1..points.len() {
+            let [a0, a1] = edge(i - 1);
+            let [b0, b1] = edge(i);
+            vertices.extend_from_slice(&[a0, b0, b1, a0, b1, a1]);
+        }
+        vertices
+    }
+}
@@ -0,0 +1,218 @@
+use glam::Vec3;
+
+use crate::{
+    Camera,
+    shaders::{FULLSCREEN_VERTEX_SHADER, OIT_COMPOSITE_SHADER},
+    tonemap::HDR_FORMAT,
+};
+
+/// Как смешивать полупрозрачные объекты (`Object::opacity() < 1`)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Transparency {
+    /// от дальних к ближним по глубине центра объекта, обычное альфа-смешивание
+    #[default]
+    Sorted,
+    /// weighted blended OIT (McGuire, Bavoil): без сортировки и артефактов пересечений,
+    /// но цвет перекрывающихся слоёв усредняется. Линии и точки всё равно сортируются
+    WeightedBlended,
+}
+
+impl Transparency {
+    /// следующий режим (переключение клавишей)
+    pub fn next(self) -> Self {
+        match self {
+            Transparency::Sorted => Transparency::WeightedBlended,
+            Transparency::WeightedBlended => Transparency::Sorted,
+        }
+    }
+}
+
+/// Глубина точки вдоль направления взгляда камеры — ключ сортировки прозрачных объектов
+pub fn view_depth(camera: &Camera, point: Vec3) -> f32 {
+    let forward = (camera.target - camera.position).normalize_or_zero();
+    (point - camera.position).dot(forward)
+}
+
+/// Цели накопления WBOIT и их сведение в HDR-кадр
+pub(crate) struct OitPass {
+    // взвешенная сумма цвета (rgb) и альфы (a)
+    accum: OitTarget,
+    // произведение (1 - alpha): какая доля непрозрачного кадра видна
+    reveal: OitTarget,
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Мультисемплированная цель (если MSAA) и её разрешённая копия для чтения
+struct OitTarget {
+    msaa: Option<wgpu::TextureView>,
+    resolved: wgpu::TextureView,
+}
+
+impl OitTarget {
+    fn new(
+        device: &wgpu::Device,
+        label: &str,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        (width, height): (u32, u32),
+    ) -> Self {
+        let texture = |sample_count, usage| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let attachment = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        Self {
+            msaa: (sample_count > 1).then(|| texture(sample_count, attachment)),
+            resolved: texture(1, attachment | wgpu::TextureUsages::TEXTURE_BINDING),
+        }
+    }
+
+    fn attachment(&self, clear: wgpu::Color) -> wgpu::RenderPassColorAttachment<'_> {
+        let (view, resolve_target) = match &self.msaa {
+            Some(msaa) => (msaa, Some(&self.resolved)),
+            None => (&self.resolved, None),
+        };
+        wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear),
+                store: true,
+            },
+        }
+    }
+}
+
+impl OitPass {
+    pub const ACCUM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    pub const REVEAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R16Float;
+
+    /// Цели размером с кадр; сведение рисуется в проходе прозрачных объектов,
+    /// поэтому пайплайн с тем же MSAA и форматом глубины
+    pub fn new(
+        device: &wgpu::Device,
+        sample_count: u32,
+        depth_format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> Self {
+        let accum = OitTarget::new(device, "OIT Accum", Self::ACCUM_FORMAT, sample_count, size);
+        let reveal = OitTarget::new(
+            device,
+            "OIT Reveal",
+            Self::REVEAL_FORMAT,
+            sample_count,
+            size,
+        );
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("OIT Composite BGL"),
+            entries: &[texture_entry(0), texture_entry(1)],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("OIT Composite Bind Group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&accum.resolved),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&reveal.resolved),
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("OIT Composite Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Fullscreen Vertex Shader"),
+            source: wgpu::ShaderSource::Wgsl(FULLSCREEN_VERTEX_SHADER.into()),
+        });
+        let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("OIT Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(OIT_COMPOSITE_SHADER.into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("OIT Composite Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &vs_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &fs_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            accum,
+            reveal,
+            pipeline,
+            bind_group,
+        }
+    }
+
+    /// цели прохода накопления: сумма с нуля, видимость с единицы
+    pub fn color_attachments(&self) -> [Option<wgpu::RenderPassColorAttachment<'_>>; 2] {
+        [
+            Some(self.accum.attachment(wgpu::Color::TRANSPARENT)),
+            Some(self.reveal.attachment(wgpu::Color::WHITE)),
+        ]
+    }
+
+    /// свести накопленное поверх непрозрачного кадра
+    pub fn composite<'a>(&'a self, rpass: &mut wgpu::RenderPass<'a>) {
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,169 @@
+use std::{hint::black_box, sync::Arc, time::Instant};
+
+use glam::{Mat4, Vec3};
+use utilities::prelude::*;
+
+/// волнистая сетка n x n в плоскости XZ
+fn terrain(n: u32) -> Mesh {
+    let mut vertices = Vec::new();
+    for j in 0..=n {
+        for i in 0..=n {
+            let (x, z) = (i as f32 / n as f32, j as f32 / n as f32);
+            vertices.push(Vertex {
+                position: [x, 0.1 * (x * 7.0).sin() * (z * 5.0).cos(), z],
+                ..Default::default()
+            });
+        }
+    }
+    let mut indices = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            let a = j * (n + 1) + i;
+            let (b, c, d) = (a + 1, a + n + 1, a + n + 2);
+            indices.extend([a, c, d, a, d, b]);
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+fn random(seed: &mut u32) -> f32 {
+    *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+    (*seed >> 8) as f32 / (1 << 24) as f32
+}
+
+fn triangle(mesh: &Mesh, triangle: usize) -> [Vec3; 3] {
+    let tri = &mesh.indices[triangle * 3..triangle * 3 + 3];
+    [0, 1, 2].map(|k| Vec3::from(mesh.vertices[tri[k] as usize].position))
+}
+
+/// среднее время одного вызова за `iterations` повторов
+fn bench(name: &str, iterations: u32, mut f: impl FnMut(u32)) -> f64 {
+    let start = Instant::now();
+    for i in 0..iterations {
+        f(i);
+    }
+    let nanos = start.elapsed().as_nanos() as f64 / iterations as f64;
+    println!("{name:<40} {:>12.1} us", nanos / 1000.0);
+    nanos
+}
+
+fn compare(name: &str, brute: f64, bvh: f64) {
+    println!("{name:<40} {:>12.1}x", brute / bvh);
+    println!();
+}
+
+fn main() {
+    let mesh = terrain(200);
+    let triangles = mesh.indices.len() / 3;
+    println!("mesh: {triangles} triangles");
+    let bvh = bench("MeshBvh::new (SAH)", 5, |_| {
+        black_box(MeshBvh::new(&mesh));
+    });
+    let mut tree = MeshBvh::new(&mesh);
+    let refit = bench("MeshBvh::refit", 5, |_| tree.refit(&mesh));
+    compare("refit speedup over rebuild", bvh, refit);
+
+    let mut seed = 1u32;
+    let rays: Vec<Ray> = (0..256)
+        .map(|_| {
+            let origin = Vec3::new(random(&mut seed), 1.0, random(&mut seed));
+            let target = Vec3::new(random(&mut seed), 0.0, random(&mut seed));
+            Ray::new(origin, target - origin)
+        })
+        .collect();
+    let brute = bench("ray, brute force", 64, |i| {
+        let ray = &rays[i as usize % rays.len()];
+        let nearest = (0..triangles)
+            .filter_map(|t| {
+                let [a, b, c] = triangle(&mesh, t);
+                ray.intersect_triangle(a, b, c).map(|(t, _, _)| t)
+            })
+            .fold(f32::INFINITY, f32::min);
+        black_box(nearest);
+    });
+    let fast = bench("ray, MeshBvh", 100_000, |i| {
+        let ray = &rays[i as usize % rays.len()];
+        black_box(tree.raycast(&mesh, ray, f32::INFINITY));
+    });
+    compare("ray speedup", brute, fast);
+
+    let spheres: Vec<BoundingSphere> = (0..256)
+        .map(|_| {
+            let center = Vec3::new(random(&mut seed), 0.0, random(&mut seed));
+            BoundingSphere::new(center, 0.01 + random(&mut seed) * 0.05)
+        })
+        .collect();
+    let brute = bench("sphere, brute force", 64, |i| {
+        let sphere = &spheres[i as usize % spheres.len()];
+        let count = (0..triangles)
+            .filter(|&t| {
+                let [a, b, c] = triangle(&mesh, t);
+                sphere.intersects_triangle(a, b, c)
+            })
+            .count();
+        black_box(count);
+    });
+    let fast = bench("sphere, MeshBvh", 10_000, |i| {
+        black_box(tree.query_sphere(&mesh, &spheres[i as usize % spheres.len()]));
+    });
+    compare("sphere speedup", brute, fast);
+
+    let brute = bench("aabb, brute force", 64, |i| {
+        let aabb = spheres[i as usize % spheres.len()].aabb();
+        let count = (0..triangles)
+            .filter(|&t| {
+                let [a, b, c] = triangle(&mesh, t);
+                aabb.intersects_triangle(a, b, c)
+            })
+            .count();
+        black_box(count);
+    });
+    let fast = bench("aabb, MeshBvh", 10_000, |i| {
+        black_box(tree.query_aabb(&mesh, &spheres[i as usize % spheres.len()].aabb()));
+    });
+    compare("aabb speedup", brute, fast);
+
+    // сцена: 32 x 32 объекта с общим мешем
+    let shared = Arc::new(terrain(16));
+    let mut objects: Vec<Object3D> = (0..1024)
+        .map(|i| {
+            let (x, z) = ((i % 32) as f32 * 2.0, (i / 32) as f32 * 2.0);
+            Object3D::from_mesh(
+                shared.clone(),
+                Mat4::from_translation(Vec3::new(x, (i % 7) as f32 * 0.3, z)),
+            )
+        })
+        .collect();
+    println!("scene: {} objects", objects.len());
+    let mut scene = SceneBvh::new(&objects);
+    let rays: Vec<Ray> = (0..256)
+        .map(|_| {
+            let origin = Vec3::new(random(&mut seed) * 64.0, 20.0, random(&mut seed) * 64.0);
+            let target = Vec3::new(random(&mut seed) * 64.0, 0.0, random(&mut seed) * 64.0);
+            Ray::new(origin, target - origin)
+        })
+        .collect();
+    let brute = bench("scene ray, every object", 1_000, |i| {
+        let ray = &rays[i as usize % rays.len()];
+        let nearest = objects
+            .iter()
+            .filter_map(|obj| obj.raycast(ray, f32::INFINITY))
+            .map(|hit| hit.distance)
+            .fold(f32::INFINITY, f32::min);
+        black_box(nearest);
+    });
+    let fast = bench("scene ray, SceneBvh", 100_000, |i| {
+        black_box(scene.raycast(&objects, &rays[i as usize % rays.len()], f32::INFINITY));
+    });
+    compare("scene ray speedup", brute, fast);
+
+    let rebuild = bench("SceneBvh::new", 100, |_| {
+        black_box(SceneBvh::new(&objects));
+    });
+    for (i, object) in objects.iter_mut().enumerate() {
+        let model = Mat4::from_translation(Vec3::Y * (i % 3) as f32) * object.model_matrix();
+        object.set_model_matrix(model);
+    }
+    let refit = bench("SceneBvh::refit", 100, |_| scene.refit(&objects));
+    compare("scene refit speedup over rebuild", rebuild, refit);
+}
@@ -0,0 +1,260 @@
+use glam::{Mat4, Vec3, Vec4};
+
+/// Ограничивающий бокс, выровненный по осям
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// пустой бокс: `min > max`, любая точка его расширяет
+    pub const EMPTY: Self = Self {
+        min: Vec3::splat(f32::INFINITY),
+        max: Vec3::splat(f32::NEG_INFINITY),
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// наименьший бокс, содержащий все точки; без точек — `EMPTY`
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        points.into_iter().fold(Self::EMPTY, |aabb, point| Self {
+            min: aabb.min.min(point),
+            max: aabb.max.max(point),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// бокс, содержащий оба
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// площадь половины поверхности (стоимость в SAH); у пустого — 0
+    pub fn half_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let size = self.max - self.min;
+        size.x * size.y + size.y * size.z + size.z * size.x
+    }
+
+    /// пересекаются ли боксы (касание считается пересечением)
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// пересекает ли треугольник бокс (теорема о разделяющей оси: оси бокса,
+    /// нормаль треугольника и девять произведений рёбер на оси бокса)
+    pub fn intersects_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let center = self.center();
+        let half = self.half_extents();
+        let [a, b, c] = [a - center, b - center, c - center];
+        let edges = [b - a, c - b, a - c];
+        let separated = |axis: Vec3| {
+            if axis.length_squared() <= f32::EPSILON * f32::EPSILON {
+                return false;
+            }
+            let [p0, p1, p2] = [a.dot(axis), b.dot(axis), c.dot(axis)];
+            let radius = half.dot(axis.abs());
+            p0.min(p1).min(p2) > radius || p0.max(p1).max(p2) < -radius
+        };
+        let box_axes = [Vec3::X, Vec3::Y, Vec3::Z];
+        !(box_axes.into_iter().any(separated)
+            || separated(edges[0].cross(edges[1]))
+            || edges
+                .iter()
+                .any(|edge| box_axes.iter().any(|axis| separated(edge.cross(*axis)))))
+    }
+
+    /// бокс вокруг преобразованного бокса (не наименьший для повёрнутой геометрии)
+    pub fn transform(&self, matrix: Mat4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        let center = matrix.transform_point3(self.center());
+        let half = self.half_extents();
+        let extents = matrix.x_axis.truncate().abs() * half.x
+            + matrix.y_axis.truncate().abs() * half.y
+            + matrix.z_axis.truncate().abs() * half.z;
+        Self {
+            min: center - extents,
+            max: center + extents,
+        }
+    }
+}
+
+/// Ограничивающая сфера
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// сфера с центром в центре бокса точек (не наименьшая, но близкая к ней);
+    /// без точек — радиус 0 в начале координат
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let aabb = Aabb::from_points(points.iter().copied());
+        if aabb.is_empty() {
+            return Self::new(Vec3::ZERO, 0.0);
+        }
+        let center = aabb.center();
+        let radius = points
+            .iter()
+            .map(|point| point.distance_squared(center))
+            .fold(0.0, f32::max)
+            .sqrt();
+        Self { center, radius }
+    }
+
+    /// бокс вокруг сферы
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(
+            self.center - Vec3::splat(self.radius),
+            self.center + Vec3::splat(self.radius),
+        )
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        !aabb.is_empty()
+            && self
+                .center
+                .clamp(aabb.min, aabb.max)
+                .distance_squared(self.center)
+                <= self.radius * self.radius
+    }
+
+    pub fn intersects_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> bool {
+        closest_point_on_triangle(self.center, a, b, c).distance_squared(self.center)
+            <= self.radius * self.radius
+    }
+
+    /// сфера вокруг преобразованной; радиус растёт на наибольший масштаб матрицы
+    pub fn transform(&self, matrix: Mat4) -> Self {
+        let scale = matrix
+            .x_axis
+            .truncate()
+            .length()
+            .max(matrix.y_axis.truncate().length())
+            .max(matrix.z_axis.truncate().length());
+        Self {
+            center: matrix.transform_point3(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+/// Пирамида видимости: шесть плоскостей `(n, d)` с нормалями внутрь, `n·p + d >= 0` внутри
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+    /// левая, правая, нижняя, верхняя, ближняя, дальняя
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// плоскости из матрицы projection * view с NDC z в [-1, 1] (как у OpenGL-проекции камеры)
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let rows = view_proj.transpose();
+        let (x, y, z, w) = (rows.x_axis, rows.y_axis, rows.z_axis, rows.w_axis);
+        Self::from_planes([w + x, w - x, w + y, w - y, w + z, w - z])
+    }
+
+    /// то же для NDC z в [0, 1] (проекции wgpu/DirectX, например каскады теней)
+    pub fn from_view_proj_zero_to_one(view_proj: Mat4) -> Self {
+        let rows = view_proj.transpose();
+        let (x, y, z, w) = (rows.x_axis, rows.y_axis, rows.z_axis, rows.w_axis);
+        Self::from_planes([w + x, w - x, w + y, w - y, z, w - z])
+    }
+
+    fn from_planes(planes: [Vec4; 6]) -> Self {
+        Self {
+            planes: planes.map(|plane| plane / plane.truncate().length()),
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(point) + plane.w >= 0.0)
+    }
+
+    /// пересекает ли сфера пирамиду (консервативно: у углов возможны ложные «да»)
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(sphere.center) + plane.w >= -sphere.radius)
+    }
+
+    /// пересекает ли бокс пирамиду (консервативно, как `intersects_sphere`)
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+        let center = aabb.center();
+        let half = aabb.half_extents();
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            normal.dot(center) + plane.w >= -normal.abs().dot(half)
+        })
+    }
+}
+
+/// ближайшая к `point` точка треугольника (Ericson, «Real-Time Collision Detection», 5.1.5)
+pub fn closest_point_on_triangle(point: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    // внутри грани
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
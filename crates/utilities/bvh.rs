@@ -0,0 +1,503 @@
+use std::ops::Range;
+
+use glam::Vec3;
+
+use crate::{
+    bounds::{Aabb, BoundingSphere},
+    common::{Mesh, Object3D, Topology},
+    raycast::{Ray, RayHit, SceneHit},
+    traits::Object,
+};
+
+// примитивов в листе, которые никогда не делятся
+const MIN_LEAF_SIZE: usize = 2;
+// больше этого лист не бывает, даже если SAH считает деление невыгодным
+const MAX_LEAF_SIZE: usize = 8;
+// корзин вдоль оси при поиске деления
+const SAH_BINS: usize = 12;
+// стоимость обхода узла относительно проверки одного примитива
+const TRAVERSAL_COST: f32 = 1.0;
+
+/// Иерархия ограничивающих боксов над произвольными примитивами, заданными своими боксами.
+/// Строится по эвристике площади поверхности (SAH) с корзинами; после движения
+/// примитивов дерево можно не перестраивать, а подогнать боксы (`refit`)
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    // номера примитивов в порядке листьев
+    primitives: Vec<u32>,
+}
+
+/// Узел: у листа `count > 0` примитивов с `first`, у внутреннего — дети `first` и `first + 1`.
+/// Дети всегда лежат в массиве после родителя
+#[derive(Copy, Clone, Debug)]
+struct BvhNode {
+    aabb: Aabb,
+    first: u32,
+    count: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Bin {
+    aabb: Aabb,
+    count: usize,
+}
+
+impl Bvh {
+    /// дерево над примитивами с боксами `bounds`; номер примитива — индекс в `bounds`.
+    /// Пустые боксы не попадают ни в один запрос
+    pub fn new(bounds: &[Aabb]) -> Self {
+        let centers: Vec<Vec3> = bounds
+            .iter()
+            .map(|aabb| {
+                if aabb.is_empty() {
+                    Vec3::ZERO
+                } else {
+                    aabb.center()
+                }
+            })
+            .collect();
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * bounds.len() / MIN_LEAF_SIZE + 1),
+            primitives: (0..bounds.len() as u32).collect(),
+        };
+        bvh.nodes.push(BvhNode {
+            aabb: Aabb::EMPTY,
+            first: 0,
+            count: bounds.len() as u32,
+        });
+        bvh.subdivide(0, bounds, &centers);
+        bvh
+    }
+
+    fn subdivide(&mut self, index: usize, bounds: &[Aabb], centers: &[Vec3]) {
+        let BvhNode { first, count, .. } = self.nodes[index];
+        let range = first as usize..(first + count) as usize;
+        let aabb = self.primitives[range.clone()]
+            .iter()
+            .fold(Aabb::EMPTY, |aabb, &p| aabb.union(&bounds[p as usize]));
+        self.nodes[index].aabb = aabb;
+        if range.len() <= MIN_LEAF_SIZE {
+            return;
+        }
+
+        let split = match self.find_split(range.clone(), &aabb, bounds, centers) {
+            Some((axis, plane, cost)) => {
+                if cost >= range.len() as f32 && range.len() <= MAX_LEAF_SIZE {
+                    return;
+                }
+                let primitives = &mut self.primitives[range.clone()];
+                let mut middle = 0;
+                for i in 0..primitives.len() {
+                    if centers[primitives[i] as usize][axis] < plane {
+                        primitives.swap(i, middle);
+                        middle += 1;
+                    }
+                }
+                middle
+            }
+            None => 0,
+        };
+        // все центры в одной корзине: делим пополам по медиане
+        let middle = if split == 0 || split == range.len() {
+            if range.len() <= MAX_LEAF_SIZE {
+                return;
+            }
+            let centroids = Aabb::from_points(
+                self.primitives[range.clone()]
+                    .iter()
+                    .map(|&p| centers[p as usize]),
+            );
+            let axis = centroids.half_extents().max_position();
+            let middle = range.len() / 2;
+            self.primitives[range.clone()].select_nth_unstable_by(middle, |&a, &b| {
+                centers[a as usize][axis]
+                    .total_cmp(&centers[b as usize][axis])
+                    .then(a.cmp(&b))
+            });
+            middle
+        } else {
+            split
+        };
+
+        let left = self.nodes.len() as u32;
+        self.nodes.push(BvhNode {
+            aabb: Aabb::EMPTY,
+            first,
+            count: middle as u32,
+        });
+        self.nodes.push(BvhNode {
+            aabb: Aabb::EMPTY,
+            first: first + middle as u32,
+            count: count - middle as u32,
+        });
+        self.nodes[index].first = left;
+        self.nodes[index].count = 0;
+        self.subdivide(left as usize, bounds, centers);
+        self.subdivide(left as usize + 1, bounds, centers);
+    }
+
+    /// лучшее деление по корзинам центров: ось, плоскость и стоимость
+    /// в единицах проверки примитива (сравнима с их числом в листе)
+    fn find_split(
+        &self,
+        range: Range<usize>,
+        aabb: &Aabb,
+        bounds: &[Aabb],
+        centers: &[Vec3],
+    ) -> Option<(usize, f32, f32)> {
+        let primitives = &self.primitives[range];
+        let centroids = Aabb::from_points(primitives.iter().map(|&p| centers[p as usize]));
+        let parent_area = aabb.half_area();
+        let mut best: Option<(usize, f32, f32)> = None;
+        let extents = centroids
+            .min
+            .to_array()
+            .into_iter()
+            .zip(centroids.max.to_array());
+        for (axis, (min, max)) in extents.enumerate() {
+            if max <= min || parent_area <= 0.0 {
+                continue;
+            }
+            let scale = SAH_BINS as f32 / (max - min);
+            let bin_of =
+                |p: u32| (((centers[p as usize][axis] - min) * scale) as usize).min(SAH_BINS - 1);
+            let mut bins = [Bin {
+                aabb: Aabb::EMPTY,
+                count: 0,
+            }; SAH_BINS];
+            for &p in primitives {
+                let bin = &mut bins[bin_of(p)];
+                bin.count += 1;
+                bin.aabb = bin.aabb.union(&bounds[p as usize]);
+            }
+
+            // площади и количества слева от каждой плоскости, затем справа
+            let mut left = [(0.0f32, 0usize); SAH_BINS - 1];
+            let (mut area, mut count) = (Aabb::EMPTY, 0);
+            for (plane, bin) in bins[..SAH_BINS - 1].iter().enumerate() {
+                area = area.union(&bin.aabb);
+                count += bin.count;
+                left[plane] = (area.half_area(), count);
+            }
+            let (mut area, mut count) = (Aabb::EMPTY, 0);
+            for plane in (0..SAH_BINS - 1).rev() {
+                area = area.union(&bins[plane + 1].aabb);
+                count += bins[plane + 1].count;
+                let (left_area, left_count) = left[plane];
+                if left_count == 0 || count == 0 {
+                    continue;
+                }
+                let cost = TRAVERSAL_COST
+                    + (left_area * left_count as f32 + area.half_area() * count as f32)
+                        / parent_area;
+                if best.is_none_or(|(_, _, best)| cost < best) {
+                    best = Some((axis, min + (plane + 1) as f32 / scale, cost));
+                }
+            }
+        }
+        best
+    }
+
+    /// подогнать боксы узлов под новые боксы тех же примитивов, не меняя структуру.
+    /// Быстрее перестройки, но дерево хуже, если примитивы сильно перемешались
+    pub fn refit(&mut self, bounds: &[Aabb]) {
+        assert_eq!(
+            bounds.len(),
+            self.primitives.len(),
+            "refit with a different number of primitives"
+        );
+        // дети лежат после родителей: обратный порядок обходит их раньше
+        for index in (0..self.nodes.len()).rev() {
+            let BvhNode { first, count, .. } = self.nodes[index];
+            self.nodes[index].aabb = if count > 0 {
+                self.primitives[first as usize..(first + count) as usize]
+                    .iter()
+                    .fold(Aabb::EMPTY, |aabb, &p| aabb.union(&bounds[p as usize]))
+            } else {
+                self.nodes[first as usize]
+                    .aabb
+                    .union(&self.nodes[first as usize + 1].aabb)
+            };
+        }
+    }
+
+    /// бокс всех примитивов
+    pub fn aabb(&self) -> Aabb {
+        self.nodes[0].aabb
+    }
+
+    /// число примитивов
+    pub fn len(&self) -> usize {
+        self.primitives.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.primitives.is_empty()
+    }
+
+    /// обход примитивов, чьи листья пересекает луч не дальше `max_distance`, от ближних к дальним.
+    /// `test(примитив, предел)` возвращает расстояние попадания — оно становится новым пределом;
+    /// итог — последний (ближайший) предел, если было хоть одно попадание
+    pub fn traverse_ray(
+        &self,
+        ray: &Ray,
+        max_distance: f32,
+        mut test: impl FnMut(usize, f32) -> Option<f32>,
+    ) -> Option<f32> {
+        let mut limit = max_distance;
+        let mut hit = None;
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            match ray.intersect_aabb(&node.aabb) {
+                Some(distance) if distance <= limit && !node.aabb.is_empty() => {}
+                _ => continue,
+            }
+            if node.count == 0 {
+                // сначала ближний ребёнок: дальний чаще отсекается по `limit`
+                let (left, right) = (node.first as usize, node.first as usize + 1);
+                let distance = |i: usize| {
+                    ray.intersect_aabb(&self.nodes[i].aabb)
+                        .unwrap_or(f32::INFINITY)
+                };
+                if distance(left) <= distance(right) {
+                    stack.extend([right, left]);
+                } else {
+                    stack.extend([left, right]);
+                }
+                continue;
+            }
+            for &primitive in
+                &self.primitives[node.first as usize..(node.first + node.count) as usize]
+            {
+                if let Some(distance) = test(primitive as usize, limit)
+                    && distance <= limit
+                {
+                    limit = distance;
+                    hit = Some(distance);
+                }
+            }
+        }
+        hit
+    }
+
+    /// все примитивы в листьях, чьи боксы принимает `overlaps`; порядок не определён
+    pub fn traverse(&self, mut overlaps: impl FnMut(&Aabb) -> bool, mut visit: impl FnMut(usize)) {
+        if self.is_empty() {
+            return;
+        }
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.aabb.is_empty() || !overlaps(&node.aabb) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.extend([node.first as usize + 1, node.first as usize]);
+                continue;
+            }
+            for &primitive in
+                &self.primitives[node.first as usize..(node.first + node.count) as usize]
+            {
+                visit(primitive as usize);
+            }
+        }
+    }
+
+    /// примитивы, чьи листья пересекает бокс (кандидаты: боксы самих примитивов не проверяются)
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.traverse(|node| node.intersects(aabb), |p| found.push(p));
+        found
+    }
+
+    /// примитивы, чьи листья пересекает сфера (кандидаты, как у `query_aabb`)
+    pub fn query_sphere(&self, sphere: &BoundingSphere) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.traverse(|node| sphere.intersects_aabb(node), |p| found.push(p));
+        found
+    }
+}
+
+/// BVH над треугольниками меша (в его локальных координатах) — нижний уровень (BLAS)
+#[derive(Clone, Debug)]
+pub struct MeshBvh {
+    bvh: Bvh,
+}
+
+impl MeshBvh {
+    pub fn new(mesh: &Mesh) -> Self {
+        Self {
+            bvh: Bvh::new(&triangle_bounds(mesh)),
+        }
+    }
+
+    /// подогнать дерево под сдвинутые вершины (индексы и число треугольников те же)
+    pub fn refit(&mut self, mesh: &Mesh) {
+        self.bvh.refit(&triangle_bounds(mesh));
+    }
+
+    /// бокс всего меша
+    pub fn aabb(&self) -> Aabb {
+        self.bvh.aabb()
+    }
+
+    /// ближайшее попадание не дальше `max_distance`; `mesh` — тот же, по которому строилось дерево.
+    /// Для луча с ненормализованным направлением `distance` — параметр на луче
+    pub fn raycast(&self, mesh: &Mesh, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        let mut nearest = None;
+        self.bvh.traverse_ray(ray, max_distance, |triangle, limit| {
+            let [a, b, c] = triangle_vertices(mesh, triangle);
+            let (t, u, v) = ray.intersect_triangle(a, b, c)?;
+            if t > limit {
+                return None;
+            }
+            nearest = Some(RayHit {
+                distance: t,
+                point: ray.at(t),
+                normal: (b - a).cross(c - a).normalize_or_zero(),
+                barycentric: [1.0 - u - v, u, v],
+                triangle,
+            });
+            Some(t)
+        });
+        nearest
+    }
+
+    /// первое попадание на отрезке от `start` до `end`
+    pub fn segment(&self, mesh: &Mesh, start: Vec3, end: Vec3) -> Option<RayHit> {
+        self.raycast(mesh, &Ray::new(start, end - start), start.distance(end))
+    }
+
+    /// треугольники, которые пересекает сфера, по возрастанию номера
+    pub fn query_sphere(&self, mesh: &Mesh, sphere: &BoundingSphere) -> Vec<usize> {
+        let mut found = self.bvh.query_sphere(sphere);
+        found.retain(|&triangle| {
+            let [a, b, c] = triangle_vertices(mesh, triangle);
+            sphere.intersects_triangle(a, b, c)
+        });
+        found.sort_unstable();
+        found
+    }
+
+    /// треугольники, которые пересекает бокс, по возрастанию номера
+    pub fn query_aabb(&self, mesh: &Mesh, aabb: &Aabb) -> Vec<usize> {
+        let mut found = self.bvh.query_aabb(aabb);
+        found.retain(|&triangle| {
+            let [a, b, c] = triangle_vertices(mesh, triangle);
+            aabb.intersects_triangle(a, b, c)
+        });
+        found.sort_unstable();
+        found
+    }
+}
+
+fn triangle_vertices(mesh: &Mesh, triangle: usize) -> [Vec3; 3] {
+    let tri = &mesh.indices[triangle * 3..triangle * 3 + 3];
+    [0, 1, 2].map(|k| Vec3::from(mesh.vertices[tri[k] as usize].position))
+}
+
+fn triangle_bounds(mesh: &Mesh) -> Vec<Aabb> {
+    (0..mesh.indices.len() / 3)
+        .map(|triangle| Aabb::from_points(triangle_vertices(mesh, triangle)))
+        .collect()
+}
+
+/// Двухуровневая BVH над объектами: верхний уровень (TLAS) — по их мировым боксам,
+/// нижний (BLAS) — `Object3D::bvh` каждого меша в его локальных координатах.
+/// После смены матриц объектов достаточно `refit`; при добавлении или удалении — перестроить
+#[derive(Clone, Debug)]
+pub struct SceneBvh {
+    bvh: Bvh,
+}
+
+impl SceneBvh {
+    /// дерево над треугольными объектами; линии и точки в запросы не попадают
+    pub fn new(objects: &[Object3D]) -> Self {
+        Self {
+            bvh: Bvh::new(&object_bounds(objects)),
+        }
+    }
+
+    /// подогнать верхний уровень под новые матрицы тех же объектов
+    pub fn refit(&mut self, objects: &[Object3D]) {
+        self.bvh.refit(&object_bounds(objects));
+    }
+
+    /// бокс всей сцены
+    pub fn aabb(&self) -> Aabb {
+        self.bvh.aabb()
+    }
+
+    /// ближайшее попадание луча (в мировых координатах) не дальше `max_distance`
+    pub fn raycast(&self, objects: &[Object3D], ray: &Ray, max_distance: f32) -> Option<SceneHit> {
+        let mut nearest = None;
+        self.bvh.traverse_ray(ray, max_distance, |object, limit| {
+            let hit = objects[object].raycast(ray, limit)?;
+            nearest = Some(SceneHit { object, hit });
+            Some(hit.distance)
+        });
+        nearest
+    }
+
+    /// первое попадание на отрезке от `start` до `end`
+    pub fn segment(&self, objects: &[Object3D], start: Vec3, end: Vec3) -> Option<SceneHit> {
+        self.raycast(objects, &Ray::new(start, end - start), start.distance(end))
+    }
+
+    /// пары (объект, треугольник), которые пересекает сфера в мировых координатах
+    pub fn query_sphere(
+        &self,
+        objects: &[Object3D],
+        sphere: &BoundingSphere,
+    ) -> Vec<(usize, usize)> {
+        self.query(objects, &sphere.aabb(), |a, b, c| {
+            sphere.intersects_triangle(a, b, c)
+        })
+    }
+
+    /// пары (объект, треугольник), которые пересекает бокс в мировых координатах
+    pub fn query_aabb(&self, objects: &[Object3D], aabb: &Aabb) -> Vec<(usize, usize)> {
+        self.query(objects, aabb, |a, b, c| aabb.intersects_triangle(a, b, c))
+    }
+
+    // кандидаты ищутся по боксу в локальных координатах объекта, точная проверка — в мировых,
+    // поэтому масштаб и поворот матрицы на результат не влияют
+    fn query(
+        &self,
+        objects: &[Object3D],
+        bounds: &Aabb,
+        mut intersects: impl FnMut(Vec3, Vec3, Vec3) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        for object in self.bvh.query_aabb(bounds) {
+            let obj = &objects[object];
+            let model = obj.model_matrix();
+            let local = bounds.transform(model.inverse());
+            let mesh = obj.mesh();
+            for triangle in obj.bvh().bvh.query_aabb(&local) {
+                let [a, b, c] =
+                    triangle_vertices(mesh, triangle).map(|v| model.transform_point3(v));
+                if intersects(a, b, c) {
+                    found.push((object, triangle));
+                }
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+fn object_bounds(objects: &[Object3D]) -> Vec<Aabb> {
+    objects
+        .iter()
+        .map(|obj| {
+            if obj.mesh().topology == Topology::TriangleList {
+                obj.world_aabb()
+            } else {
+                Aabb::EMPTY
+            }
+        })
+        .collect()
+}
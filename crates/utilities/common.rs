@@ -1,16 +1,31 @@
-use std::mem::size_of_val;
+use std::{
+    fmt,
+    mem::size_of_val,
+    sync::{Arc, OnceLock},
+};
 
-use glam::Mat4;
+use glam::{Mat3, Mat4, Vec2, Vec3};
 
-use crate::traits::Object;
+use crate::{
+    bounds::{Aabb, BoundingSphere},
+    bvh::MeshBvh,
+    material::Material,
+    raycast::{Ray, RayHit},
+    simplify::lod_chain,
+    texture::Texture,
+    traits::Object,
+};
 
-/// Описание вершины для 3D: позиция (vec3) + цвет (vec3)
+/// Описание вершины для 3D: позиция, нормаль, цвет, UV и тангент
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub normal: [f32; 3],
     pub color: [f32; 3],
+    pub uv: [f32; 2],
+    /// xyz — тангент, w — знак битангента (±1)
+    pub tangent: [f32; 4],
 }
 
 impl Vertex {
@@ -38,6 +53,19 @@ impl Vertex {
                     shader_location: 2,
                     format: wgpu::VertexFormat::Float32x3,
                 },
+                // uv @location(3)
+                wgpu::VertexAttribute {
+                    offset: (mem::size_of::<[f32; 3]>() * 3) as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                // tangent @location(4)
+                wgpu::VertexAttribute {
+                    offset: (mem::size_of::<[f32; 3]>() * 3 + mem::size_of::<[f32; 2]>())
+                        as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
@@ -47,46 +75,548 @@ impl Vertex {
         let byte_len = size_of_val(vertices);
         unsafe { std::slice::from_raw_parts(vertices.as_ptr() as *const u8, byte_len) }
     }
+
+    /// Посчитать тангенты по UV для индексированного треугольного меша
+    /// (метод Lengyel, с ортогонализацией Грама-Шмидта)
+    pub fn compute_tangents(vertices: &mut [Vertex], indices: &[u32]) {
+        let mut tan = vec![Vec3::ZERO; vertices.len()];
+        let mut bitan = vec![Vec3::ZERO; vertices.len()];
+
+        for tri in indices.chunks_exact(3) {
+            let [i0, i1, i2] = [tri[0] as usize, tri[1] as usize, tri[2] as usize];
+            if i0 >= vertices.len() || i1 >= vertices.len() || i2 >= vertices.len() {
+                continue;
+            }
+            let p0 = Vec3::from(vertices[i0].position);
+            let p1 = Vec3::from(vertices[i1].position);
+            let p2 = Vec3::from(vertices[i2].position);
+            let w0 = Vec2::from(vertices[i0].uv);
+            let w1 = Vec2::from(vertices[i1].uv);
+            let w2 = Vec2::from(vertices[i2].uv);
+
+            let e1 = p1 - p0;
+            let e2 = p2 - p0;
+            let d1 = w1 - w0;
+            let d2 = w2 - w0;
+            let det = d1.x * d2.y - d2.x * d1.y;
+            if det.abs() < f32::EPSILON {
+                continue;
+            }
+            let r = 1.0 / det;
+            let t = (e1 * d2.y - e2 * d1.y) * r;
+            let b = (e2 * d1.x - e1 * d2.x) * r;
+            for i in [i0, i1, i2] {
+                tan[i] += t;
+                bitan[i] += b;
+            }
+        }
+
+        for (i, v) in vertices.iter_mut().enumerate() {
+            let n = Vec3::from(v.normal);
+            let t = (tan[i] - n * n.dot(tan[i])).normalize_or_zero();
+            let w = if n.cross(t).dot(bitan[i]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            v.tangent = [t.x, t.y, t.z, w];
+        }
+    }
+}
+
+/// Как индексы меша собираются в примитивы
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// тройки индексов, обход против часовой стрелки
+    #[default]
+    TriangleList,
+    /// пары индексов — отдельные отрезки
+    LineList,
+    /// ломаная через все индексы подряд
+    LineStrip,
+    /// каждый индекс — точка; без индексов рисуются все вершины
+    PointList,
+}
+
+/// Геометрия: вершины, индексы и топология. Разделяется между объектами через `Arc`
+#[derive(Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub topology: Topology,
+    // строится при первом `bvh` и живёт, пока жив меш: объекты с одним `Arc<Mesh>`
+    // получают одно дерево
+    bvh: OnceLock<MeshBvh>,
+}
+
+// копия может разойтись с исходником по вершинам, поэтому дерево строит заново
+impl Clone for Mesh {
+    fn clone(&self) -> Self {
+        Self {
+            vertices: self.vertices.clone(),
+            indices: self.indices.clone(),
+            topology: self.topology,
+            bvh: OnceLock::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Mesh {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mesh")
+            .field("vertices", &self.vertices)
+            .field("indices", &self.indices)
+            .field("topology", &self.topology)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        Self {
+            vertices,
+            indices,
+            topology: Topology::TriangleList,
+            bvh: OnceLock::new(),
+        }
+    }
+
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// BVH треугольников; строится при первом обращении. Вершины меша, дерево
+    /// которого уже построено, менять нельзя — оно не перестроится
+    pub fn bvh(&self) -> &MeshBvh {
+        self.bvh.get_or_init(|| MeshBvh::new(self))
+    }
+
+    /// индексы, разложенные в отрезки (пары) для LineList и LineStrip
+    pub fn line_segments(&self) -> Vec<[u32; 2]> {
+        match self.topology {
+            Topology::LineList => self
+                .indices
+                .chunks_exact(2)
+                .map(|pair| [pair[0], pair[1]])
+                .collect(),
+            Topology::LineStrip => self
+                .indices
+                .windows(2)
+                .map(|pair| [pair[0], pair[1]])
+                .collect(),
+            Topology::TriangleList | Topology::PointList => Vec::new(),
+        }
+    }
+
+    /// бокс вершин в локальных координатах
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.vertices.iter().map(|v| Vec3::from(v.position)))
+    }
+
+    /// сфера вокруг вершин в локальных координатах
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        let points: Vec<Vec3> = self
+            .vertices
+            .iter()
+            .map(|v| Vec3::from(v.position))
+            .collect();
+        BoundingSphere::from_points(&points)
+    }
+
+    /// ближайшее попадание луча (в координатах меша) перебором всех треугольников.
+    /// Для луча с ненормализованным направлением `distance` — параметр на луче
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        let mut nearest: Option<RayHit> = None;
+        let mut limit = max_distance;
+        for (triangle, tri) in self.indices.chunks_exact(3).enumerate() {
+            let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(self.vertices[tri[k] as usize].position));
+            if let Some((t, u, v)) = ray.intersect_triangle(a, b, c)
+                && t <= limit
+            {
+                limit = t;
+                nearest = Some(RayHit {
+                    distance: t,
+                    point: ray.at(t),
+                    normal: (b - a).cross(c - a).normalize_or_zero(),
+                    barycentric: [1.0 - u - v, u, v],
+                    triangle,
+                });
+            }
+        }
+        nearest
+    }
+
+    /// перекрасить все вершины
+    pub fn set_color(&mut self, color: [f32; 3]) {
+        for v in &mut self.vertices {
+            v.color = color;
+        }
+    }
+}
+
+/// Уровень детализации: упрощённый меш и размер на экране, ниже которого он рисуется
+#[derive(Clone, Debug)]
+pub struct LodLevel {
+    pub mesh: Arc<Mesh>,
+    /// диаметр ограничивающей сферы в долях высоты экрана
+    pub screen_size: f32,
+}
+
+impl LodLevel {
+    pub fn new(mesh: Arc<Mesh>, screen_size: f32) -> Self {
+        Self { mesh, screen_size }
+    }
 }
 
 pub struct Object3D {
-    vertices: Vec<Vertex>,
-    indices: Vec<u32>,
+    mesh: Arc<Mesh>,
+    // границы меша считаются один раз: меш за `Arc` не меняется
+    aabb: Aabb,
+    bounding_sphere: BoundingSphere,
+    // упрощённые меши от подробного к грубому
+    lods: Vec<LodLevel>,
     model_matrix: Mat4,
+    texture: Option<Arc<Texture>>,
+    material: Option<Material>,
+    line_width: f32,
+    cast_shadows: bool,
+    receive_shadows: bool,
 }
 
 impl Object3D {
     pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>, model_matrix: Mat4) -> Self {
+        Self::from_mesh(Arc::new(Mesh::new(vertices, indices)), model_matrix)
+    }
+
+    /// объект поверх уже существующей (возможно, общей) геометрии
+    pub fn from_mesh(mesh: Arc<Mesh>, model_matrix: Mat4) -> Self {
         Self {
-            vertices,
-            indices,
+            aabb: mesh.aabb(),
+            bounding_sphere: mesh.bounding_sphere(),
+            lods: Vec::new(),
+            mesh,
             model_matrix,
+            texture: None,
+            material: None,
+            line_width: 1.0,
+            cast_shadows: true,
+            receive_shadows: true,
         }
     }
-}
 
-impl Object for Object3D {
-    fn vertices(&self) -> &[Vertex] {
-        &self.vertices
+    /// толщина линий / размер точек в пикселях
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+
+    pub fn with_line_width(mut self, line_width: f32) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    pub fn set_cast_shadows(&mut self, cast_shadows: bool) {
+        self.cast_shadows = cast_shadows;
+    }
+
+    pub fn set_receive_shadows(&mut self, receive_shadows: bool) {
+        self.receive_shadows = receive_shadows;
+    }
+
+    pub fn with_shadows(mut self, cast_shadows: bool, receive_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self.receive_shadows = receive_shadows;
+        self
+    }
+
+    pub fn set_material(&mut self, material: Option<Material>) {
+        self.material = material;
+    }
+
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = Some(material);
+        self
+    }
+
+    /// непрозрачность материала (без материала создаётся материал по умолчанию)
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.material.get_or_insert_with(Material::default).opacity = opacity;
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// уровни детализации от подробного к грубому; основной меш — уровень 0
+    pub fn lods(&self) -> &[LodLevel] {
+        &self.lods
+    }
+
+    pub fn set_lods(&mut self, lods: Vec<LodLevel>) {
+        self.lods = lods;
+    }
+
+    pub fn with_lods(mut self, lods: Vec<LodLevel>) -> Self {
+        self.lods = lods;
+        self
+    }
+
+    /// сгенерировать `levels` уровней упрощением (`lod_chain`), каждый в `1 / ratio` раз
+    /// меньше по треугольникам; уровень k включается, когда объект меньше `0.5^k` высоты экрана
+    pub fn with_generated_lods(self, levels: usize, ratio: f32) -> Self {
+        let lods = lod_chain(&self.mesh, levels, ratio)
+            .into_iter()
+            .zip(1..)
+            .map(|(mesh, k)| LodLevel::new(Arc::new(mesh), 0.5f32.powi(k)))
+            .collect();
+        self.with_lods(lods)
+    }
+
+    /// BVH треугольников основного меша; хранится в меше, поэтому общее
+    /// для всех объектов поверх того же `Arc<Mesh>`
+    pub fn bvh(&self) -> &MeshBvh {
+        self.mesh.bvh()
+    }
+
+    /// ближайшее попадание луча (в мировых координатах) в треугольники объекта.
+    /// Линии и точки не пересекаются
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Option<RayHit> {
+        if self.mesh.topology != Topology::TriangleList
+            || ray.intersect_sphere(&self.world_bounding_sphere())? > max_distance
+        {
+            return None;
+        }
+        // параметр на локальном луче равен расстоянию на мировом
+        let local = ray.transform(self.model_matrix.inverse());
+        let hit = self.bvh().raycast(&self.mesh, &local, max_distance)?;
+        let normal_matrix = Mat3::from_mat4(self.model_matrix).inverse().transpose();
+        Some(RayHit {
+            point: ray.at(hit.distance),
+            normal: (normal_matrix * hit.normal).normalize_or_zero(),
+            ..hit
+        })
+    }
+
+    /// текстура (albedo), если объект рисуется текстурным пайплайном
+    pub fn texture(&self) -> Option<&Arc<Texture>> {
+        self.texture.as_ref()
     }
 
-    fn indices(&self) -> &[u32] {
-        &self.indices
+    pub fn set_texture(&mut self, texture: Option<Arc<Texture>>) {
+        self.texture = texture;
+    }
+
+    pub fn with_texture(mut self, texture: Arc<Texture>) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+}
+
+impl Object for Object3D {
+    fn mesh(&self) -> &Arc<Mesh> {
+        &self.mesh
     }
 
     fn model_matrix(&self) -> Mat4 {
         self.model_matrix
     }
 
+    fn set_model_matrix(&mut self, model_matrix: Mat4) {
+        self.model_matrix = model_matrix;
+    }
+
     fn to_object3d(self) -> Object3D {
         self
     }
 
-    fn translate(&mut self, offset: glam::Vec3) {
-        self.model_matrix = Mat4::from_translation(offset) * self.model_matrix;
+    fn local_aabb(&self) -> Aabb {
+        self.aabb
+    }
+
+    fn local_bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_sphere
+    }
+
+    fn material(&self) -> Option<&Material> {
+        self.material.as_ref()
+    }
+
+    fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    fn cast_shadows(&self) -> bool {
+        self.cast_shadows
+    }
+
+    fn receive_shadows(&self) -> bool {
+        self.receive_shadows
+    }
+}
+
+/// Данные одного экземпляра при инстансинге: трансформация и множитель цвета вершин
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct Instance {
+    /// локальная трансформация экземпляра (применяется до model_matrix объекта)
+    pub transform: [[f32; 4]; 4],
+    pub color: [f32; 3],
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::new(Mat4::IDENTITY, [1.0, 1.0, 1.0])
+    }
+}
+
+impl Instance {
+    pub fn new(transform: Mat4, color: [f32; 3]) -> Self {
+        Self {
+            transform: transform.to_cols_array_2d(),
+            color,
+        }
+    }
+
+    pub fn transform(&self) -> Mat4 {
+        Mat4::from_cols_array_2d(&self.transform)
+    }
+
+    pub fn set_transform(&mut self, transform: Mat4) {
+        self.transform = transform.to_cols_array_2d();
+    }
+
+    /// второй вершинный буфер (step mode Instance): столбцы матрицы @location(5..8), цвет @location(9)
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use std::mem;
+        const COLUMN: wgpu::BufferAddress = mem::size_of::<[f32; 4]>() as wgpu::BufferAddress;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: COLUMN,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: COLUMN * 2,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: COLUMN * 3,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // color @location(9)
+                wgpu::VertexAttribute {
+                    offset: COLUMN * 4,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+            ],
+        }
+    }
+
+    pub fn as_byte_slice(instances: &[Instance]) -> &[u8] {
+        let byte_len = size_of_val(instances);
+        unsafe { std::slice::from_raw_parts(instances.as_ptr() as *const u8, byte_len) }
+    }
+}
+
+/// Один меш, нарисованный много раз одним draw call (частицы, астероиды).
+/// Не `Object`: свести группу к одному `Object3D` нельзя без потери экземпляров, поэтому
+/// она добавляется в сцену отдельно. model_matrix умножается на трансформацию каждого экземпляра.
+pub struct InstancedObject {
+    mesh: Arc<Mesh>,
+    // сфера меша для отсечения отдельных экземпляров
+    bounding_sphere: BoundingSphere,
+    model_matrix: Mat4,
+    instances: Vec<Instance>,
+    cast_shadows: bool,
+    receive_shadows: bool,
+}
+
+impl InstancedObject {
+    pub fn new(mesh: Arc<Mesh>, model_matrix: Mat4) -> Self {
+        Self {
+            bounding_sphere: mesh.bounding_sphere(),
+            mesh,
+            model_matrix,
+            instances: Vec::new(),
+            cast_shadows: true,
+            receive_shadows: true,
+        }
+    }
+
+    pub fn with_instances(mut self, instances: Vec<Instance>) -> Self {
+        self.instances = instances;
+        self
+    }
+
+    /// геометрия, общая для всех экземпляров
+    pub fn mesh(&self) -> &Arc<Mesh> {
+        &self.mesh
     }
 
-    fn scale(&mut self, factor: glam::Vec3) {
-        self.model_matrix = Mat4::from_scale(factor) * self.model_matrix;
+    /// матрица всей группы, применяется поверх трансформации экземпляра
+    pub fn model_matrix(&self) -> Mat4 {
+        self.model_matrix
+    }
+
+    pub fn set_model_matrix(&mut self, model_matrix: Mat4) {
+        self.model_matrix = model_matrix;
+    }
+
+    /// сфера меша в локальных координатах
+    pub fn local_bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_sphere
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// изменить экземпляры (например, каждый кадр из симуляции); буфер обновится при следующем рендере
+    pub fn instances_mut(&mut self) -> &mut Vec<Instance> {
+        &mut self.instances
+    }
+
+    pub fn push(&mut self, instance: Instance) {
+        self.instances.push(instance);
+    }
+
+    /// ограничивающая сфера экземпляра в мировых координатах — для отсечения по одному
+    pub fn instance_bounding_sphere(&self, instance: &Instance) -> BoundingSphere {
+        self.bounding_sphere
+            .transform(self.model_matrix * instance.transform())
+    }
+
+    pub fn cast_shadows(&self) -> bool {
+        self.cast_shadows
+    }
+
+    pub fn set_cast_shadows(&mut self, cast_shadows: bool) {
+        self.cast_shadows = cast_shadows;
+    }
+
+    pub fn receive_shadows(&self) -> bool {
+        self.receive_shadows
+    }
+
+    pub fn set_receive_shadows(&mut self, receive_shadows: bool) {
+        self.receive_shadows = receive_shadows;
+    }
+
+    pub fn with_shadows(mut self, cast_shadows: bool, receive_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self.receive_shadows = receive_shadows;
+        self
     }
 }
@@ -0,0 +1,114 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::texture::TextureError;
+
+/// Ошибка импорта моделей
+#[derive(Debug)]
+pub enum ImportError {
+    /// ошибка ввода-вывода; `path` — файл, если ошибка при его открытии
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// ошибка парсинга OBJ/MTL в tobj
+    Obj(tobj::LoadError),
+    /// ошибка чтения glTF/GLB (JSON, буферы, изображения)
+    Gltf(gltf::Error),
+    /// OBJ ссылается на MTL (`mtllib`), которого нет на диске
+    MissingMaterialLibrary { path: PathBuf },
+    /// не удалось загрузить текстуру из материала
+    Texture { path: PathBuf, source: TextureError },
+    /// индекс указывает за пределы массива вершин
+    IndexOutOfRange {
+        mesh: String,
+        index: u32,
+        vertex_count: usize,
+    },
+    /// NaN или бесконечность в позициях, нормалях или UV
+    NonFinite { mesh: String },
+    /// меш (или весь файл) без вершин или без треугольников
+    EmptyMesh { mesh: String },
+    /// файл не соответствует формату (STL, PLY)
+    Malformed {
+        format: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "failed to open {}: {source}", path.display()),
+            ImportError::Io { path: None, source } => write!(f, "io error: {source}"),
+            ImportError::Obj(e) => write!(f, "obj load error: {e}"),
+            ImportError::Gltf(e) => write!(f, "gltf load error: {e}"),
+            ImportError::MissingMaterialLibrary { path } => {
+                write!(f, "material library not found: {}", path.display())
+            }
+            ImportError::Texture { path, source } => {
+                write!(f, "failed to load texture {}: {source}", path.display())
+            }
+            ImportError::IndexOutOfRange {
+                mesh,
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "mesh '{mesh}': index {index} out of range for {vertex_count} vertices"
+            ),
+            ImportError::NonFinite { mesh } => {
+                write!(f, "mesh '{mesh}': non-finite vertex attribute")
+            }
+            ImportError::EmptyMesh { mesh } => write!(f, "mesh '{mesh}' is empty"),
+            ImportError::Malformed { format, reason } => {
+                write!(f, "malformed {format} file: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io { source, .. } => Some(source),
+            ImportError::Obj(e) => Some(e),
+            ImportError::Gltf(e) => Some(e),
+            ImportError::Texture { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl ImportError {
+    /// ошибка открытия файла `path`, для `map_err`
+    pub(crate) fn open(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| ImportError::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+}
+
+impl From<io::Error> for ImportError {
+    fn from(source: io::Error) -> Self {
+        ImportError::Io { path: None, source }
+    }
+}
+
+impl From<tobj::LoadError> for ImportError {
+    fn from(e: tobj::LoadError) -> Self {
+        ImportError::Obj(e)
+    }
+}
+
+impl From<gltf::Error> for ImportError {
+    fn from(e: gltf::Error) -> Self {
+        ImportError::Gltf(e)
+    }
+}
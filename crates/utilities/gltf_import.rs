@@ -0,0 +1,406 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use glam::Mat4;
+use gltf::{image::Format, mesh::Mode, texture};
+
+use crate::{
+    common::{Mesh, Object3D, Topology, Vertex},
+    error::ImportError,
+    material::Material,
+    normals::{NormalMode, generate_normals},
+    texture::{SamplerDesc, Texture},
+    traits::Object,
+};
+
+/// Узел иерархии glTF
+pub struct SceneNode {
+    pub name: Option<String>,
+    /// локальная трансформация относительно родителя
+    pub transform: Mat4,
+    /// примитивы меша узла, model_matrix у них единичная (система координат узла)
+    pub objects: Vec<Object3D>,
+    pub children: Vec<SceneNode>,
+}
+
+impl SceneNode {
+    /// Развернуть поддерево в плоский список объектов с мировыми матрицами
+    pub fn flatten(self, parent: Mat4) -> Vec<Object3D> {
+        let world = parent * self.transform;
+        let mut objects = Vec::new();
+        for mut object in self.objects {
+            object.set_model_matrix(world * object.model_matrix());
+            objects.push(object);
+        }
+        for child in self.children {
+            objects.extend(child.flatten(world));
+        }
+        objects
+    }
+}
+
+/// Загрузить `.gltf`/`.glb` и вернуть плоский список объектов.
+/// `model_matrix` применяется поверх трансформаций узлов, как в `load_obj`.
+pub fn load_gltf(path: impl AsRef<Path>, model_matrix: Mat4) -> Result<Vec<Object3D>, ImportError> {
+    Ok(load_gltf_scene(path)?
+        .into_iter()
+        .flat_map(|node| node.flatten(model_matrix))
+        .collect())
+}
+
+/// Загрузить сцену glTF (сцену по умолчанию или первую) как дерево узлов
+pub fn load_gltf_scene(path: impl AsRef<Path>) -> Result<Vec<SceneNode>, ImportError> {
+    let path = path.as_ref();
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let (document, buffers, images) = gltf::import(path)?;
+
+    let mut loader = Loader {
+        base_dir,
+        buffers: &buffers,
+        images: &images,
+        textures: HashMap::new(),
+    };
+
+    let Some(scene) = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    else {
+        return Ok(Vec::new());
+    };
+
+    scene.nodes().map(|node| loader.node(&node)).collect()
+}
+
+struct Loader<'a> {
+    base_dir: &'a Path,
+    buffers: &'a [gltf::buffer::Data],
+    images: &'a [gltf::image::Data],
+    // одна текстура на все примитивы, которые на неё ссылаются
+    textures: HashMap<usize, Arc<Texture>>,
+}
+
+impl Loader<'_> {
+    fn node(&mut self, node: &gltf::Node) -> Result<SceneNode, ImportError> {
+        let transform = Mat4::from_cols_array_2d(&node.transform().matrix());
+
+        let mut objects = Vec::new();
+        if let Some(mesh) = node.mesh() {
+            let mesh_name = mesh.name().unwrap_or("").to_owned();
+            for primitive in mesh.primitives() {
+                objects.push(self.primitive(&mesh_name, &primitive)?);
+            }
+        }
+
+        let children = node
+            .children()
+            .map(|child| self.node(&child))
+            .collect::<Result<_, _>>()?;
+
+        Ok(SceneNode {
+            name: node.name().map(str::to_owned),
+            transform,
+            objects,
+            children,
+        })
+    }
+
+    fn primitive(
+        &mut self,
+        mesh_name: &str,
+        primitive: &gltf::Primitive,
+    ) -> Result<Object3D, ImportError> {
+        let reader = primitive.reader(|buffer| Some(&self.buffers[buffer.index()]));
+        let gltf_material = primitive.material();
+        let pbr = gltf_material.pbr_metallic_roughness();
+        let base_color = pbr.base_color_factor();
+        let base_texture = pbr.base_color_texture();
+        let uv_set = base_texture.as_ref().map_or(0, |info| info.tex_coord());
+
+        let positions: Vec<[f32; 3]> = reader
+            .read_positions()
+            .map(|iter| iter.collect())
+            .unwrap_or_default();
+        let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|iter| iter.collect());
+        let uvs: Option<Vec<[f32; 2]>> = reader
+            .read_tex_coords(uv_set)
+            .map(|iter| iter.into_f32().collect());
+        let colors: Option<Vec<[f32; 3]>> = reader
+            .read_colors(0)
+            .map(|iter| iter.into_rgb_f32().collect());
+        let tangents: Option<Vec<[f32; 4]>> = reader.read_tangents().map(|iter| iter.collect());
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(iter) => iter.into_u32().collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        let (topology, indices) = to_list(primitive.mode(), indices);
+
+        validate(mesh_name, topology, &positions, &indices)?;
+        // атрибуты читаются по номеру вершины, поэтому короче позиций быть не могут
+        let counts = [
+            ("NORMAL", normals.as_ref().map(Vec::len)),
+            ("TEXCOORD", uvs.as_ref().map(Vec::len)),
+            ("COLOR_0", colors.as_ref().map(Vec::len)),
+            ("TANGENT", tangents.as_ref().map(Vec::len)),
+        ];
+        for (attribute, count) in counts {
+            if let Some(count) = count.filter(|&count| count != positions.len()) {
+                return Err(ImportError::Malformed {
+                    format: "glTF",
+                    reason: format!(
+                        "mesh '{mesh_name}': {attribute} has {count} values for {} vertices",
+                        positions.len()
+                    ),
+                });
+            }
+        }
+        let all_finite = normals
+            .iter()
+            .flatten()
+            .flatten()
+            .chain(uvs.iter().flatten().flatten())
+            .chain(tangents.iter().flatten().flatten())
+            .all(|v| v.is_finite());
+        if !all_finite {
+            return Err(ImportError::NonFinite {
+                mesh: mesh_name.to_owned(),
+            });
+        }
+
+        let mut vertices: Vec<Vertex> = positions
+            .iter()
+            .enumerate()
+            .map(|(i, &position)| {
+                let color = colors.as_ref().map_or([1.0; 3], |c| c[i]);
+                Vertex {
+                    position,
+                    normal: normals.as_ref().map_or([0.0; 3], |n| n[i]),
+                    color: [
+                        color[0] * base_color[0],
+                        color[1] * base_color[1],
+                        color[2] * base_color[2],
+                    ],
+                    uv: uvs.as_ref().map_or([0.0; 2], |uv| uv[i]),
+                    tangent: tangents.as_ref().map_or([0.0; 4], |t| t[i]),
+                }
+            })
+            .collect();
+        let mut indices = indices;
+
+        // по спецификации при отсутствии нормалей используется плоское затенение
+        let triangles = topology == Topology::TriangleList;
+        if normals.is_none() && triangles {
+            (vertices, indices) = generate_normals(&vertices, &indices, NormalMode::Flat);
+        }
+        if tangents.is_none() && uvs.is_some() && triangles {
+            Vertex::compute_tangents(&mut vertices, &indices);
+        }
+
+        let material = Material {
+            name: gltf_material.name().unwrap_or("").to_owned(),
+            diffuse: [base_color[0], base_color[1], base_color[2]],
+            emissive: gltf_material.emissive_factor(),
+            opacity: base_color[3],
+            metallic: pbr.metallic_factor(),
+            roughness: pbr.roughness_factor(),
+            diffuse_texture: base_texture.as_ref().and_then(|t| self.uri(&t.texture())),
+            normal_texture: gltf_material
+                .normal_texture()
+                .and_then(|t| self.uri(&t.texture())),
+            metallic_roughness_texture: pbr
+                .metallic_roughness_texture()
+                .and_then(|t| self.uri(&t.texture())),
+            occlusion_texture: gltf_material
+                .occlusion_texture()
+                .and_then(|t| self.uri(&t.texture())),
+            emissive_texture: gltf_material
+                .emissive_texture()
+                .and_then(|t| self.uri(&t.texture())),
+            ..Default::default()
+        };
+
+        let mesh = Mesh::new(vertices, indices).with_topology(topology);
+        let mut object = Object3D::from_mesh(Arc::new(mesh), Mat4::IDENTITY);
+        if let Some(info) = base_texture.filter(|_| uvs.is_some()) {
+            object.set_texture(Some(self.texture(&info.texture())?));
+        }
+        object.set_material(Some(material));
+        Ok(object)
+    }
+
+    /// путь к внешнему файлу текстуры (None для встроенных и data: URI)
+    fn uri(&self, texture: &texture::Texture) -> Option<std::path::PathBuf> {
+        match texture.source().source() {
+            gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => {
+                Some(self.base_dir.join(uri))
+            }
+            _ => None,
+        }
+    }
+
+    fn texture(&mut self, texture: &texture::Texture) -> Result<Arc<Texture>, ImportError> {
+        if let Some(cached) = self.textures.get(&texture.index()) {
+            return Ok(cached.clone());
+        }
+        let image = &self.images[texture.source().index()];
+        let loaded = Texture::from_rgba8(image.width, image.height, to_rgba8(image))
+            .map_err(|source| ImportError::Texture {
+                path: self
+                    .uri(texture)
+                    .unwrap_or_else(|| format!("<embedded #{}>", texture.index()).into()),
+                source,
+            })?
+            .with_sampler(sampler_desc(&texture.sampler()));
+        let loaded = Arc::new(loaded);
+        self.textures.insert(texture.index(), loaded.clone());
+        Ok(loaded)
+    }
+}
+
+/// Привести примитив glTF к топологии движка: полосы и вееры треугольников
+/// разворачиваются в списки, замкнутая ломаная — в ломаную с повтором первой вершины
+fn to_list(mode: Mode, indices: Vec<u32>) -> (Topology, Vec<u32>) {
+    match mode {
+        Mode::Triangles => (Topology::TriangleList, indices),
+        // обход чередуется, чтобы все треугольники смотрели в одну сторону
+        Mode::TriangleStrip => (
+            Topology::TriangleList,
+            (0..indices.len().saturating_sub(2))
+                .flat_map(|i| {
+                    let odd = i % 2;
+                    [indices[i], indices[i + 1 + odd], indices[i + 2 - odd]]
+                })
+                .collect(),
+        ),
+        Mode::TriangleFan => (
+            Topology::TriangleList,
+            (1..indices.len().saturating_sub(1))
+                .flat_map(|i| [indices[i], indices[i + 1], indices[0]])
+                .collect(),
+        ),
+        Mode::Lines => (Topology::LineList, indices),
+        Mode::LineStrip => (Topology::LineStrip, indices),
+        Mode::LineLoop => {
+            let mut indices = indices;
+            if let Some(&first) = indices.first() {
+                indices.push(first);
+            }
+            (Topology::LineStrip, indices)
+        }
+        Mode::Points => (Topology::PointList, indices),
+    }
+}
+
+fn validate(
+    mesh: &str,
+    topology: Topology,
+    positions: &[[f32; 3]],
+    indices: &[u32],
+) -> Result<(), ImportError> {
+    let min_indices = match topology {
+        Topology::TriangleList => 3,
+        Topology::LineList | Topology::LineStrip => 2,
+        Topology::PointList => 1,
+    };
+    if positions.is_empty() || indices.len() < min_indices {
+        return Err(ImportError::EmptyMesh {
+            mesh: mesh.to_owned(),
+        });
+    }
+    if let Some(&index) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+        return Err(ImportError::IndexOutOfRange {
+            mesh: mesh.to_owned(),
+            index,
+            vertex_count: positions.len(),
+        });
+    }
+    if !positions.iter().flatten().all(|v| v.is_finite()) {
+        return Err(ImportError::NonFinite {
+            mesh: mesh.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+/// Привести декодированное изображение glTF к RGBA8
+fn to_rgba8(image: &gltf::image::Data) -> Vec<u8> {
+    let pixels = &image.pixels;
+    let (channels, bytes_per_channel) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+    let channel = |offset: usize| -> u8 {
+        match bytes_per_channel {
+            1 => pixels[offset],
+            2 => (u16::from_le_bytes([pixels[offset], pixels[offset + 1]]) >> 8) as u8,
+            _ => {
+                let bytes = [
+                    pixels[offset],
+                    pixels[offset + 1],
+                    pixels[offset + 2],
+                    pixels[offset + 3],
+                ];
+                (f32::from_le_bytes(bytes).clamp(0.0, 1.0) * 255.0).round() as u8
+            }
+        }
+    };
+
+    let pixel_size = channels * bytes_per_channel;
+    let count = image.width as usize * image.height as usize;
+    let mut rgba = Vec::with_capacity(count * 4);
+    for p in 0..count {
+        let base = p * pixel_size;
+        let c: Vec<u8> = (0..channels)
+            .map(|c| channel(base + c * bytes_per_channel))
+            .collect();
+        let rgba_pixel = match channels {
+            1 => [c[0], c[0], c[0], 255],
+            2 => [c[0], c[0], c[0], c[1]],
+            3 => [c[0], c[1], c[2], 255],
+            _ => [c[0], c[1], c[2], c[3]],
+        };
+        rgba.extend_from_slice(&rgba_pixel);
+    }
+    rgba
+}
+
+fn sampler_desc(sampler: &texture::Sampler) -> SamplerDesc {
+    use texture::{MagFilter, MinFilter, WrappingMode};
+
+    let address = |mode: WrappingMode| match mode {
+        WrappingMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        WrappingMode::MirroredRepeat => wgpu::AddressMode::MirrorRepeat,
+        WrappingMode::Repeat => wgpu::AddressMode::Repeat,
+    };
+    let mag_filter = match sampler.mag_filter() {
+        Some(MagFilter::Nearest) => wgpu::FilterMode::Nearest,
+        _ => wgpu::FilterMode::Linear,
+    };
+    let (min_filter, mipmap_filter) = match sampler.min_filter() {
+        Some(MinFilter::Nearest) | Some(MinFilter::NearestMipmapNearest) => {
+            (wgpu::FilterMode::Nearest, wgpu::FilterMode::Nearest)
+        }
+        Some(MinFilter::NearestMipmapLinear) => {
+            (wgpu::FilterMode::Nearest, wgpu::FilterMode::Linear)
+        }
+        Some(MinFilter::LinearMipmapNearest) => {
+            (wgpu::FilterMode::Linear, wgpu::FilterMode::Nearest)
+        }
+        _ => (wgpu::FilterMode::Linear, wgpu::FilterMode::Linear),
+    };
+
+    SamplerDesc {
+        mag_filter,
+        min_filter,
+        mipmap_filter,
+        address_mode_u: address(sampler.wrap_s()),
+        address_mode_v: address(sampler.wrap_t()),
+        address_mode_w: wgpu::AddressMode::Repeat,
+    }
+}
@@ -1,4 +1,16 @@
+pub mod bounds;
+pub mod bvh;
 pub mod common;
+pub mod error;
+pub mod gltf_import;
+pub mod material;
+pub mod normals;
+pub mod obj_export;
 pub mod obj_import;
+pub mod ply;
 pub mod prelude;
+pub mod raycast;
+pub mod simplify;
+pub mod stl;
+pub mod texture;
 pub mod traits;
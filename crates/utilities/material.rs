@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+
+/// Материал поверхности (подмножество MTL)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Kd
+    pub diffuse: [f32; 3],
+    /// Ks
+    pub specular: [f32; 3],
+    /// Ke
+    pub emissive: [f32; 3],
+    /// Ns
+    pub shininess: f32,
+    /// d (1.0 — непрозрачный)
+    pub opacity: f32,
+    /// PBR metallic-roughness (glTF); для MTL — значения по умолчанию
+    pub metallic: f32,
+    pub roughness: f32,
+    /// map_Kd / baseColorTexture, путь уже разрешён относительно файла модели
+    pub diffuse_texture: Option<PathBuf>,
+    /// map_Bump / norm
+    pub normal_texture: Option<PathBuf>,
+    /// map_Ks
+    pub specular_texture: Option<PathBuf>,
+    /// map_d
+    pub opacity_texture: Option<PathBuf>,
+    /// glTF metallicRoughnessTexture (B — metallic, G — roughness)
+    pub metallic_roughness_texture: Option<PathBuf>,
+    pub occlusion_texture: Option<PathBuf>,
+    pub emissive_texture: Option<PathBuf>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            diffuse: [0.8, 0.8, 0.8],
+            specular: [0.0, 0.0, 0.0],
+            emissive: [0.0, 0.0, 0.0],
+            shininess: 0.0,
+            opacity: 1.0,
+            metallic: 0.0,
+            roughness: 1.0,
+            diffuse_texture: None,
+            normal_texture: None,
+            specular_texture: None,
+            opacity_texture: None,
+            metallic_roughness_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+impl Material {
+    /// Сконвертировать материал tobj, разрешив пути текстур относительно `base_dir`
+    pub fn from_mtl(mtl: &tobj::Material, base_dir: &Path) -> Self {
+        let default = Self::default();
+        let resolve = |name: &Option<String>| name.as_ref().map(|n| base_dir.join(n));
+        Self {
+            name: mtl.name.clone(),
+            diffuse: mtl.diffuse.unwrap_or(default.diffuse),
+            specular: mtl.specular.unwrap_or(default.specular),
+            emissive: mtl.emissive.unwrap_or(default.emissive),
+            shininess: mtl.shininess.unwrap_or(default.shininess),
+            opacity: mtl.dissolve.unwrap_or(default.opacity),
+            diffuse_texture: resolve(&mtl.diffuse_texture),
+            normal_texture: resolve(&mtl.normal_texture),
+            specular_texture: resolve(&mtl.specular_texture),
+            opacity_texture: resolve(&mtl.dissolve_texture),
+            ..default
+        }
+    }
+}
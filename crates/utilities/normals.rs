@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+use crate::common::Vertex;
+
+/// Способ генерации нормалей
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum NormalMode {
+    /// нормаль грани на каждой вершине треугольника
+    Flat,
+    /// усреднение по соседним граням, если угол между ними не больше порога (радианы)
+    Smooth { angle_threshold: f32 },
+}
+
+impl Default for NormalMode {
+    fn default() -> Self {
+        NormalMode::Smooth {
+            angle_threshold: 60f32.to_radians(),
+        }
+    }
+}
+
+/// Пересчитать нормали треугольного меша.
+///
+/// Вершины на жёстких рёбрах дублируются, совпадающие вершины после пересчёта
+/// снова склеиваются, поэтому меняются и вершины, и индексы.
+/// Индексы должны указывать внутрь `vertices`.
+pub fn generate_normals(
+    vertices: &[Vertex],
+    indices: &[u32],
+    mode: NormalMode,
+) -> (Vec<Vertex>, Vec<u32>) {
+    let cos_threshold = match mode {
+        NormalMode::Flat => 1.0 - 1e-4,
+        NormalMode::Smooth { angle_threshold } => angle_threshold.cos(),
+    };
+
+    // нормали граней, взвешенные по площади (длина = 2 * площадь)
+    let face_normals: Vec<Vec3> = indices
+        .chunks_exact(3)
+        .map(|tri| {
+            let a = Vec3::from(vertices[tri[0] as usize].position);
+            let b = Vec3::from(vertices[tri[1] as usize].position);
+            let c = Vec3::from(vertices[tri[2] as usize].position);
+            (b - a).cross(c - a)
+        })
+        .collect();
+
+    // грани, сходящиеся в каждой позиции (вершины с разными UV — одна позиция)
+    let mut faces_at: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+    for (face, tri) in indices.chunks_exact(3).enumerate() {
+        for &i in tri {
+            faces_at
+                .entry(position_key(&vertices[i as usize]))
+                .or_default()
+                .push(face);
+        }
+    }
+
+    let mut out_vertices: Vec<Vertex> = Vec::with_capacity(vertices.len());
+    let mut out_indices = Vec::with_capacity(indices.len());
+    let mut welded: HashMap<[u32; 17], u32> = HashMap::new();
+
+    for (face, tri) in indices.chunks_exact(3).enumerate() {
+        let own = face_normals[face].normalize_or_zero();
+        for &i in tri {
+            let vertex = vertices[i as usize];
+            let mut normal = Vec3::ZERO;
+            for &other in &faces_at[&position_key(&vertex)] {
+                let n = face_normals[other];
+                if other == face || own.dot(n.normalize_or_zero()) >= cos_threshold {
+                    normal += n;
+                }
+            }
+            let normal = normal.try_normalize().unwrap_or(own);
+
+            let vertex = Vertex {
+                normal: normal.to_array(),
+                ..vertex
+            };
+            let index = *welded.entry(vertex_key(&vertex)).or_insert_with(|| {
+                out_vertices.push(vertex);
+                out_vertices.len() as u32 - 1
+            });
+            out_indices.push(index);
+        }
+    }
+
+    (out_vertices, out_indices)
+}
+
+fn position_key(v: &Vertex) -> [u32; 3] {
+    v.position.map(f32::to_bits)
+}
+
+fn vertex_key(v: &Vertex) -> [u32; 17] {
+    let mut key = [0u32; 17];
+    let fields = v
+        .position
+        .iter()
+        .chain(&v.normal)
+        .chain(&v.color)
+        .chain(&v.uv)
+        .chain(&v.tangent);
+    for (k, f) in key.iter_mut().zip(fields) {
+        *k = f.to_bits();
+    }
+    key
+}
@@ -0,0 +1,222 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{common::Topology, material::Material, traits::Object};
+
+/// Настройки экспорта OBJ
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ObjExportOptions {
+    /// применить model_matrix к позициям и нормалям; иначе пишутся локальные координаты
+    pub bake_transform: bool,
+    /// писать цвет вершин расширением `v x y z r g b` (понимают Blender, MeshLab)
+    pub vertex_colors: bool,
+}
+
+impl Default for ObjExportOptions {
+    fn default() -> Self {
+        Self {
+            bake_transform: true,
+            vertex_colors: true,
+        }
+    }
+}
+
+/// Записать объекты в `path` (.obj) и материалы рядом в `.mtl` с тем же именем
+pub fn save_obj(
+    path: impl AsRef<Path>,
+    objects: &[&dyn Object],
+    options: &ObjExportOptions,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let mtl_path = path.with_extension("mtl");
+    let mtl_name = mtl_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("materials.mtl");
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut obj = BufWriter::new(File::create(path)?);
+    write_obj(&mut obj, objects, options, Some(mtl_name))?;
+    obj.flush()?;
+
+    let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+    write_mtl(&mut mtl, objects, base_dir)?;
+    mtl.flush()
+}
+
+/// Записать геометрию в OBJ. `mtl_name` — имя библиотеки для `mtllib`
+pub fn write_obj(
+    mut writer: impl Write,
+    objects: &[&dyn Object],
+    options: &ObjExportOptions,
+    mtl_name: Option<&str>,
+) -> io::Result<()> {
+    writeln!(writer, "# schwarzschild-engine")?;
+    if let Some(mtl_name) = mtl_name {
+        writeln!(writer, "mtllib {mtl_name}")?;
+    }
+
+    let names = material_names(objects);
+    // OBJ индексирует вершины глобально, начиная с 1
+    let mut offset = 1;
+    for (i, object) in objects.iter().enumerate() {
+        let (vertices, indices) = if options.bake_transform {
+            (object.world_vertices(), object.world_indices())
+        } else {
+            (object.vertices().to_vec(), object.indices().to_vec())
+        };
+
+        // импорт умножает цвет вершины на Kd, поэтому пишем цвет без Kd
+        let diffuse = object.material().map_or([1.0; 3], |m| m.diffuse);
+        writeln!(writer, "o object_{i}")?;
+        for v in &vertices {
+            let [x, y, z] = v.position;
+            if options.vertex_colors {
+                let [r, g, b] = [0, 1, 2].map(|c| {
+                    if diffuse[c] != 0.0 {
+                        v.color[c] / diffuse[c]
+                    } else {
+                        0.0
+                    }
+                });
+                writeln!(writer, "v {x} {y} {z} {r} {g} {b}")?;
+            } else {
+                writeln!(writer, "v {x} {y} {z}")?;
+            }
+        }
+        for v in &vertices {
+            // в OBJ начало UV снизу
+            writeln!(writer, "vt {} {}", v.uv[0], 1.0 - v.uv[1])?;
+        }
+        for v in &vertices {
+            let [x, y, z] = v.normal;
+            writeln!(writer, "vn {x} {y} {z}")?;
+        }
+
+        if mtl_name.is_some() {
+            writeln!(writer, "usemtl {}", names[i])?;
+        }
+        let global = |index: &u32| *index as usize + offset;
+        match object.topology() {
+            Topology::TriangleList => {
+                for tri in indices.chunks_exact(3) {
+                    let [a, b, c] = [&tri[0], &tri[1], &tri[2]].map(global);
+                    writeln!(writer, "f {a}/{a}/{a} {b}/{b}/{b} {c}/{c}/{c}")?;
+                }
+            }
+            Topology::LineList => {
+                for pair in indices.chunks_exact(2) {
+                    let [a, b] = [&pair[0], &pair[1]].map(global);
+                    writeln!(writer, "l {a}/{a} {b}/{b}")?;
+                }
+            }
+            Topology::LineStrip if indices.len() >= 2 => {
+                let strip: Vec<String> = indices
+                    .iter()
+                    .map(global)
+                    .map(|i| format!("{i}/{i}"))
+                    .collect();
+                writeln!(writer, "l {}", strip.join(" "))?;
+            }
+            Topology::LineStrip => {}
+            Topology::PointList => {
+                let points: Vec<String> = if indices.is_empty() {
+                    (offset..offset + vertices.len())
+                        .map(|i| i.to_string())
+                        .collect()
+                } else {
+                    indices.iter().map(global).map(|i| i.to_string()).collect()
+                };
+                if !points.is_empty() {
+                    writeln!(writer, "p {}", points.join(" "))?;
+                }
+            }
+        }
+
+        offset += vertices.len();
+    }
+    Ok(())
+}
+
+/// Записать материалы объектов в MTL; пути текстур — относительно `base_dir`, если возможно
+pub fn write_mtl(
+    mut writer: impl Write,
+    objects: &[&dyn Object],
+    base_dir: &Path,
+) -> io::Result<()> {
+    writeln!(writer, "# schwarzschild-engine")?;
+
+    let names = material_names(objects);
+    let mut written = HashSet::new();
+    for (object, name) in objects.iter().zip(&names) {
+        if !written.insert(name) {
+            continue;
+        }
+        // без материала цвет задают вершины, Kd — белый множитель
+        let material = object.material().cloned().unwrap_or(Material {
+            diffuse: [1.0, 1.0, 1.0],
+            ..Default::default()
+        });
+
+        writeln!(writer, "newmtl {name}")?;
+        let [r, g, b] = material.diffuse;
+        writeln!(writer, "Kd {r} {g} {b}")?;
+        let [r, g, b] = material.specular;
+        writeln!(writer, "Ks {r} {g} {b}")?;
+        let [r, g, b] = material.emissive;
+        writeln!(writer, "Ke {r} {g} {b}")?;
+        writeln!(writer, "Ns {}", material.shininess)?;
+        writeln!(writer, "d {}", material.opacity)?;
+        writeln!(writer, "illum 2")?;
+
+        let maps = [
+            ("map_Kd", &material.diffuse_texture),
+            ("map_Bump", &material.normal_texture),
+            ("map_Ks", &material.specular_texture),
+            ("map_d", &material.opacity_texture),
+        ];
+        for (key, path) in maps {
+            if let Some(path) = path {
+                let path = path.strip_prefix(base_dir).unwrap_or(path);
+                writeln!(writer, "{key} {}", path.display())?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// имя материала для каждого объекта; безымянные и отсутствующие получают свои.
+/// Одинаковые материалы делят имя, разные с одним именем получают суффикс `_2`, `_3`, ...
+fn material_names(objects: &[&dyn Object]) -> Vec<String> {
+    let mut taken: HashMap<String, Option<&Material>> = HashMap::new();
+    let mut names = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        let material = object.material();
+        let base = match material {
+            Some(material) if !material.name.is_empty() => material.name.replace(' ', "_"),
+            _ => format!("material_{i}"),
+        };
+        let mut name = base.clone();
+        let mut suffix = 1;
+        loop {
+            match taken.get(&name) {
+                None => {
+                    taken.insert(name.clone(), material);
+                    break;
+                }
+                Some(&existing) if material.is_some() && existing == material => break,
+                Some(_) => {
+                    suffix += 1;
+                    name = format!("{base}_{suffix}");
+                }
+            }
+        }
+        names.push(name);
+    }
+    names
+}
@@ -1,52 +1,250 @@
-use tobj::{self, LoadError};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::common::{Object3D, Vertex};
+use crate::{
+    common::{Object3D, Vertex},
+    error::ImportError,
+    material::Material,
+    normals::{NormalMode, generate_normals},
+    texture::Texture,
+};
 
-use glam::Mat4;
+use glam::{Mat4, Vec3};
 
-pub fn load_obj(path: &str, model_matrix: Mat4) -> Result<Vec<Object3D>, LoadError> {
-    let (models, _materials) = tobj::load_obj(
-        path,
+/// Ось "вверх" в исходном файле
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UpAxis {
+    #[default]
+    Y,
+    /// Blender/3ds Max: конвертируется в Y-up как (x, z, -y)
+    Z,
+}
+
+impl UpAxis {
+    /// перевести вектор из осей файла в Y-up
+    pub fn to_y_up(self, v: [f32; 3]) -> [f32; 3] {
+        match self {
+            UpAxis::Y => v,
+            UpAxis::Z => [v[0], v[2], -v[1]],
+        }
+    }
+}
+
+/// Настройки импорта OBJ
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ObjImportOptions {
+    /// масштаб, применяемый к позициям
+    pub scale: f32,
+    pub up_axis: UpAxis,
+    /// поменять порядок обхода треугольников (CW <-> CCW)
+    pub flip_winding: bool,
+    /// как генерировать нормали, если в файле нет `vn`
+    pub normals: NormalMode,
+    /// пересчитать нормали, даже если они есть в файле
+    pub recompute_normals: bool,
+}
+
+impl Default for ObjImportOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            up_axis: UpAxis::Y,
+            flip_winding: false,
+            normals: NormalMode::default(),
+            recompute_normals: false,
+        }
+    }
+}
+
+pub fn load_obj(path: impl AsRef<Path>, model_matrix: Mat4) -> Result<Vec<Object3D>, ImportError> {
+    load_obj_with(path, model_matrix, &ObjImportOptions::default())
+}
+
+pub fn load_obj_with(
+    path: impl AsRef<Path>,
+    model_matrix: Mat4,
+    options: &ObjImportOptions,
+) -> Result<Vec<Object3D>, ImportError> {
+    let path = path.as_ref();
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let file = File::open(path).map_err(ImportError::open(path))?;
+    let mut reader = BufReader::new(file);
+
+    // tobj сообщит об отсутствующем MTL только как OpenFileFailed — запоминаем путь сами
+    let missing_mtl: RefCell<Option<PathBuf>> = RefCell::new(None);
+    let (models, materials) = tobj::load_obj_buf(
+        &mut reader,
         &tobj::LoadOptions {
             triangulate: true,
             single_index: true,
             ..Default::default()
         },
+        |mtl_path| {
+            let full_path = base_dir.join(mtl_path);
+            if !full_path.exists() {
+                missing_mtl.borrow_mut().get_or_insert(full_path.clone());
+            }
+            tobj::load_mtl(full_path)
+        },
     )?;
 
-    let mut objects = Vec::new();
+    if let Some(path) = missing_mtl.into_inner() {
+        return Err(ImportError::MissingMaterialLibrary { path });
+    }
+    let materials: Vec<Material> = materials?
+        .iter()
+        .map(|mtl| Material::from_mtl(mtl, base_dir))
+        .collect();
 
-    // Пока берём только первый меш
+    // одна текстура на все меши, которые на неё ссылаются
+    let mut textures: HashMap<PathBuf, Arc<Texture>> = HashMap::new();
+
+    let mut objects = Vec::new();
 
     for model in models {
+        // группа без граней (например, пустой `g` в конце файла) — пропускаем
+        if model.mesh.indices.len() < 3 {
+            continue;
+        }
         // Собираем вершины
         let mesh = model.mesh;
-        let mut vertices = Vec::with_capacity(mesh.positions.len() / 3);
-        for i in 0..mesh.positions.len() / 3 {
-            let px = mesh.positions[i * 3];
-            let py = mesh.positions[i * 3 + 1];
-            let pz = mesh.positions[i * 3 + 2];
+        let material = mesh.material_id.and_then(|id| materials.get(id)).cloned();
+        let color = material
+            .as_ref()
+            .map_or(Material::default().diffuse, |m| m.diffuse);
+
+        let vertex_count = mesh.positions.len() / 3;
+        let has_uv = mesh.texcoords.len() / 2 == vertex_count;
+        let has_normals = mesh.normals.len() / 3 == vertex_count;
+        let has_colors = mesh.vertex_color.len() / 3 == vertex_count;
+
+        validate_mesh(&model.name, &mesh)?;
+
+        let mut vertices = Vec::with_capacity(vertex_count);
+        for i in 0..vertex_count {
+            let position = Vec3::from(options.up_axis.to_y_up([
+                mesh.positions[i * 3],
+                mesh.positions[i * 3 + 1],
+                mesh.positions[i * 3 + 2],
+            ])) * options.scale;
 
-            let nx = mesh.normals[i * 3];
-            let ny = mesh.normals[i * 3 + 1];
-            let nz = mesh.normals[i * 3 + 2];
+            let normal = if has_normals {
+                let n = options.up_axis.to_y_up([
+                    mesh.normals[i * 3],
+                    mesh.normals[i * 3 + 1],
+                    mesh.normals[i * 3 + 2],
+                ]);
+                // отрицательный масштаб зеркалит геометрию
+                (Vec3::from(n) * options.scale.signum()).to_array()
+            } else {
+                [0.0, 0.0, 0.0]
+            };
 
-            // пока просто зададим цвет = позиция (для теста)
-            let color = [px.abs(), py.abs(), pz.abs()];
+            // UV: в OBJ начало координат снизу, в wgpu — сверху
+            let uv = if has_uv {
+                [mesh.texcoords[i * 2], 1.0 - mesh.texcoords[i * 2 + 1]]
+            } else {
+                [0.0, 0.0]
+            };
 
             vertices.push(Vertex {
-                position: [px, py, pz],
-                color,
-                normal: [nx, ny, nz],
+                position: position.to_array(),
+                // цвет вершины (`v x y z r g b`) умножается на Kd
+                color: if has_colors {
+                    [0, 1, 2].map(|c| mesh.vertex_color[i * 3 + c] * color[c])
+                } else {
+                    color
+                },
+                normal,
+                uv,
+                ..Default::default()
             });
         }
 
-        // Индексы (tobj даёт u32)
-        let indices: Vec<u32> = mesh.indices;
+        // Индексы (tobj даёт u32). Отрицательный масштаб зеркалит геометрию и сам
+        // меняет обход, поэтому его компенсируем (смена осей определитель не меняет)
+        let mut indices: Vec<u32> = mesh.indices;
+        if options.flip_winding != (options.scale < 0.0) {
+            for tri in indices.chunks_exact_mut(3) {
+                tri.swap(1, 2);
+            }
+        }
+
+        if !has_normals || options.recompute_normals {
+            (vertices, indices) = generate_normals(&vertices, &indices, options.normals);
+        }
+
+        if has_uv {
+            Vertex::compute_tangents(&mut vertices, &indices);
+        }
+
+        let mut object = Object3D::new(vertices, indices, model_matrix);
+
+        if let Some(material) = material {
+            if let Some(texture_path) = material.diffuse_texture.as_ref().filter(|_| has_uv) {
+                let texture = match textures.get(texture_path) {
+                    Some(texture) => texture.clone(),
+                    None => {
+                        let texture =
+                            Texture::load(texture_path).map_err(|source| ImportError::Texture {
+                                path: texture_path.clone(),
+                                source,
+                            })?;
+                        let texture = Arc::new(texture);
+                        textures.insert(texture_path.clone(), texture.clone());
+                        texture
+                    }
+                };
+                object.set_texture(Some(texture));
+            }
+            object.set_material(Some(material));
+        }
 
-        let object = Object3D::new(vertices, indices, model_matrix);
         objects.push(object);
     }
 
+    if objects.is_empty() {
+        return Err(ImportError::EmptyMesh {
+            mesh: path
+                .file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()),
+        });
+    }
     Ok(objects)
 }
+
+/// Проверить меш tobj до сборки вершин: пустота, индексы, NaN
+pub fn validate_mesh(name: &str, mesh: &tobj::Mesh) -> Result<(), ImportError> {
+    let vertex_count = mesh.positions.len() / 3;
+    if vertex_count == 0 || mesh.indices.len() < 3 {
+        return Err(ImportError::EmptyMesh {
+            mesh: name.to_owned(),
+        });
+    }
+    if let Some(&index) = mesh.indices.iter().find(|&&i| i as usize >= vertex_count) {
+        return Err(ImportError::IndexOutOfRange {
+            mesh: name.to_owned(),
+            index,
+            vertex_count,
+        });
+    }
+    let all_finite = mesh
+        .positions
+        .iter()
+        .chain(&mesh.normals)
+        .chain(&mesh.texcoords)
+        .all(|v| v.is_finite());
+    if !all_finite {
+        return Err(ImportError::NonFinite {
+            mesh: name.to_owned(),
+        });
+    }
+    Ok(())
+}
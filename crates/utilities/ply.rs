@@ -0,0 +1,531 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use glam::Mat4;
+
+use crate::{
+    common::{Mesh, Object3D, Topology, Vertex},
+    error::ImportError,
+    normals::{NormalMode, generate_normals},
+    traits::Object,
+};
+
+/// Вариант PLY при записи
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    #[default]
+    BinaryLittleEndian,
+}
+
+/// цвет для PLY без цвета
+const DEFAULT_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+
+/// Загрузить PLY (ASCII или бинарный). Файл без граней, но с рёбрами (`edge`) даёт
+/// LineList, совсем без граней и рёбер — облако точек, объект без индексов.
+pub fn load_ply(path: impl AsRef<Path>, model_matrix: Mat4) -> Result<Object3D, ImportError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(ImportError::open(path))?;
+    read_ply(BufReader::new(file), model_matrix)
+}
+
+pub fn read_ply(mut reader: impl Read, model_matrix: Mat4) -> Result<Object3D, ImportError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    let header = Header::parse(&data)?;
+    let mut body = Body::new(&data[header.body_offset..], header.encoding);
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut edges = Vec::new();
+    let mut has_normals = false;
+
+    for element in &header.elements {
+        match element.name.as_str() {
+            "vertex" => {
+                has_normals = element.index_of("nx").is_some();
+                // счётчик из заголовка не проверен: на вершину уходит хотя бы байт данных
+                vertices.reserve(element.count.min(body.remaining()));
+                for _ in 0..element.count {
+                    let values = body.read_element(element)?;
+                    vertices.push(element.vertex(&values));
+                }
+            }
+            "face" => {
+                let list = element
+                    .index_of("vertex_indices")
+                    .or_else(|| element.index_of("vertex_index"))
+                    .ok_or_else(|| malformed("face element without vertex_indices"))?;
+                for _ in 0..element.count {
+                    let values = body.read_element(element)?;
+                    let Value::List(polygon) = &values[list] else {
+                        return Err(malformed("vertex_indices is not a list"));
+                    };
+                    // индекс шире u32 не обрезаем: иначе он мог бы попасть в диапазон вершин
+                    let polygon = polygon
+                        .iter()
+                        .map(|&v| u32::try_from(v))
+                        .collect::<Result<Vec<u32>, _>>()
+                        .map_err(|_| out_of_range(u32::MAX, vertices.len()))?;
+                    // многоугольник разбиваем веером
+                    for i in 1..polygon.len().saturating_sub(1) {
+                        indices.extend([polygon[0], polygon[i], polygon[i + 1]]);
+                    }
+                }
+            }
+            "edge" => {
+                let (Some(first), Some(second)) =
+                    (element.index_of("vertex1"), element.index_of("vertex2"))
+                else {
+                    return Err(malformed("edge element without vertex1 and vertex2"));
+                };
+                for _ in 0..element.count {
+                    let values = body.read_element(element)?;
+                    for i in [first, second] {
+                        let Value::Scalar(v) = values[i] else {
+                            return Err(malformed("edge vertex is a list"));
+                        };
+                        if v < 0.0 || v.fract() != 0.0 {
+                            return Err(malformed("edge vertex is not an index"));
+                        }
+                        edges.push(
+                            u32::try_from(v as u64)
+                                .map_err(|_| out_of_range(u32::MAX, vertices.len()))?,
+                        );
+                    }
+                }
+            }
+            // прочие элементы (material, ...) пропускаем
+            _ => {
+                for _ in 0..element.count {
+                    body.read_element(element)?;
+                }
+            }
+        }
+    }
+
+    if vertices.is_empty() {
+        return Err(ImportError::EmptyMesh {
+            mesh: "ply".to_owned(),
+        });
+    }
+    if let Some(&index) = indices
+        .iter()
+        .chain(&edges)
+        .find(|&&i| i as usize >= vertices.len())
+    {
+        return Err(out_of_range(index, vertices.len()));
+    }
+    if vertices
+        .iter()
+        .any(|v| v.position.iter().any(|c| !c.is_finite()))
+    {
+        return Err(ImportError::NonFinite {
+            mesh: "ply".to_owned(),
+        });
+    }
+
+    // рёбра без граней — линии; при гранях объект остаётся треугольным
+    if indices.is_empty() && !edges.is_empty() {
+        let mesh = Mesh::new(vertices, edges).with_topology(Topology::LineList);
+        return Ok(Object3D::from_mesh(Arc::new(mesh), model_matrix));
+    }
+    // без граней это облако точек
+    if indices.is_empty() {
+        let mesh = Mesh::new(vertices, indices).with_topology(Topology::PointList);
+        return Ok(Object3D::from_mesh(Arc::new(mesh), model_matrix));
+    }
+    if !has_normals {
+        (vertices, indices) = generate_normals(&vertices, &indices, NormalMode::default());
+    }
+
+    Ok(Object3D::new(vertices, indices, model_matrix))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, ImportError> {
+        Ok(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            other => return Err(malformed(&format!("unknown property type '{other}'"))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// целочисленные цвета лежат в 0..=255, вещественные — в 0..=1
+    fn is_integer(self) -> bool {
+        !matches!(self, Scalar::F32 | Scalar::F64)
+    }
+}
+
+enum PropertyKind {
+    Scalar(Scalar),
+    List { count: Scalar, item: Scalar },
+}
+
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.properties.iter().position(|p| p.name == name)
+    }
+
+    fn scalar(&self, values: &[Value], names: &[&str]) -> Option<(f64, Scalar)> {
+        names.iter().find_map(|name| {
+            let i = self.index_of(name)?;
+            match (&values[i], &self.properties[i].kind) {
+                (Value::Scalar(v), PropertyKind::Scalar(ty)) => Some((*v, *ty)),
+                _ => None,
+            }
+        })
+    }
+
+    fn vertex(&self, values: &[Value]) -> Vertex {
+        let get = |names: &[&str], default: f64| {
+            self.scalar(values, names).map_or(default, |(v, _)| v) as f32
+        };
+        let color = |names: &[&str], default: f32| {
+            self.scalar(values, names).map_or(
+                default,
+                |(v, ty)| if ty.is_integer() { v / 255.0 } else { v } as f32,
+            )
+        };
+        Vertex {
+            position: [get(&["x"], 0.0), get(&["y"], 0.0), get(&["z"], 0.0)],
+            normal: [get(&["nx"], 0.0), get(&["ny"], 0.0), get(&["nz"], 0.0)],
+            color: [
+                color(&["red", "r", "diffuse_red"], DEFAULT_COLOR[0]),
+                color(&["green", "g", "diffuse_green"], DEFAULT_COLOR[1]),
+                color(&["blue", "b", "diffuse_blue"], DEFAULT_COLOR[2]),
+            ],
+            uv: [
+                get(&["u", "s", "texture_u", "texture_s"], 0.0),
+                get(&["v", "t", "texture_v", "texture_t"], 0.0),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+struct Header {
+    encoding: Encoding,
+    elements: Vec<Element>,
+    body_offset: usize,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Self, ImportError> {
+        const END: &[u8] = b"end_header";
+        let end = data
+            .windows(END.len())
+            .position(|w| w == END)
+            .ok_or_else(|| malformed("missing end_header"))?;
+        let mut body_offset = end + END.len();
+        // за end_header следует \n или \r\n
+        if data.get(body_offset) == Some(&b'\r') {
+            body_offset += 1;
+        }
+        if data.get(body_offset) == Some(&b'\n') {
+            body_offset += 1;
+        }
+
+        let text =
+            std::str::from_utf8(&data[..end]).map_err(|_| malformed("header is not UTF-8"))?;
+        let mut lines = text.lines().map(str::trim);
+        if lines.next() != Some("ply") {
+            return Err(malformed("missing 'ply' magic"));
+        }
+
+        let mut encoding = None;
+        let mut elements: Vec<Element> = Vec::new();
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["format", format, _version] => {
+                    encoding = Some(match *format {
+                        "ascii" => Encoding::Ascii,
+                        "binary_little_endian" => Encoding::BinaryLittleEndian,
+                        "binary_big_endian" => Encoding::BinaryBigEndian,
+                        other => return Err(malformed(&format!("unknown format '{other}'"))),
+                    });
+                }
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| malformed("bad element count"))?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count, item, name] => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| malformed("property before element"))?;
+                    element.properties.push(Property {
+                        name: name.to_string(),
+                        kind: PropertyKind::List {
+                            count: Scalar::parse(count)?,
+                            item: Scalar::parse(item)?,
+                        },
+                    });
+                }
+                ["property", ty, name] => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| malformed("property before element"))?;
+                    element.properties.push(Property {
+                        name: name.to_string(),
+                        kind: PropertyKind::Scalar(Scalar::parse(ty)?),
+                    });
+                }
+                ["comment", ..] | ["obj_info", ..] | [] => {}
+                _ => return Err(malformed(&format!("unexpected header line '{line}'"))),
+            }
+        }
+
+        Ok(Self {
+            encoding: encoding.ok_or_else(|| malformed("missing format line"))?,
+            elements,
+            body_offset,
+        })
+    }
+}
+
+enum Value {
+    Scalar(f64),
+    List(Vec<u64>),
+}
+
+struct Body<'a> {
+    data: &'a [u8],
+    offset: usize,
+    encoding: Encoding,
+}
+
+impl<'a> Body<'a> {
+    fn new(data: &'a [u8], encoding: Encoding) -> Self {
+        Self {
+            data,
+            offset: 0,
+            encoding,
+        }
+    }
+
+    fn read_element(&mut self, element: &Element) -> Result<Vec<Value>, ImportError> {
+        element
+            .properties
+            .iter()
+            .map(|property| match property.kind {
+                PropertyKind::Scalar(ty) => self.read(ty).map(Value::Scalar),
+                PropertyKind::List { count, item } => {
+                    let len = self.read(count)? as usize;
+                    (0..len)
+                        .map(|_| match self.read(item)? {
+                            v if v < 0.0 => Err(malformed("negative value in list property")),
+                            v => Ok(v as u64),
+                        })
+                        .collect::<Result<_, _>>()
+                        .map(Value::List)
+                }
+            })
+            .collect()
+    }
+
+    /// сколько байт осталось непрочитанными
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.offset)
+    }
+
+    fn read(&mut self, ty: Scalar) -> Result<f64, ImportError> {
+        if self.encoding == Encoding::Ascii {
+            return self.read_ascii();
+        }
+        let size = ty.size();
+        let bytes = self
+            .data
+            .get(self.offset..self.offset + size)
+            .ok_or_else(|| malformed("unexpected end of data"))?;
+        self.offset += size;
+
+        let mut buf = [0u8; 8];
+        buf[..size].copy_from_slice(bytes);
+        if self.encoding == Encoding::BinaryBigEndian {
+            buf[..size].reverse();
+        }
+        Ok(match ty {
+            Scalar::I8 => buf[0] as i8 as f64,
+            Scalar::U8 => buf[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            Scalar::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            Scalar::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            Scalar::F64 => f64::from_le_bytes(buf),
+        })
+    }
+
+    fn read_ascii(&mut self) -> Result<f64, ImportError> {
+        let rest = &self.data[self.offset..];
+        let start = rest
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .ok_or_else(|| malformed("unexpected end of data"))?;
+        let len = rest[start..]
+            .iter()
+            .position(|b| b.is_ascii_whitespace())
+            .unwrap_or(rest.len() - start);
+        self.offset += start + len;
+        std::str::from_utf8(&rest[start..start + len])
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| malformed("bad number"))
+    }
+}
+
+fn malformed(reason: &str) -> ImportError {
+    ImportError::Malformed {
+        format: "PLY",
+        reason: reason.to_owned(),
+    }
+}
+
+fn out_of_range(index: u32, vertex_count: usize) -> ImportError {
+    ImportError::IndexOutOfRange {
+        mesh: "ply".to_owned(),
+        index,
+        vertex_count,
+    }
+}
+
+/// Сохранить объект в PLY с нормалями, цветом и UV; позиции в мировых координатах.
+/// Треугольники пишутся гранями (`face`), линии — рёбрами (`edge`), точки — только вершинами
+pub fn save_ply(
+    path: impl AsRef<Path>,
+    object: &impl Object,
+    format: PlyFormat,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_ply(&mut writer, object, format)?;
+    writer.flush()
+}
+
+pub fn write_ply(
+    mut writer: impl Write,
+    object: &impl Object,
+    format: PlyFormat,
+) -> std::io::Result<()> {
+    let vertices = object.world_vertices();
+    let (indices, edges) = match object.topology() {
+        Topology::TriangleList => (object.world_indices(), Vec::new()),
+        Topology::LineList | Topology::LineStrip => (Vec::new(), object.mesh().line_segments()),
+        Topology::PointList => (Vec::new(), Vec::new()),
+    };
+    let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    writeln!(writer, "ply")?;
+    match format {
+        PlyFormat::Ascii => writeln!(writer, "format ascii 1.0")?,
+        PlyFormat::BinaryLittleEndian => writeln!(writer, "format binary_little_endian 1.0")?,
+    }
+    writeln!(writer, "comment schwarzschild-engine")?;
+    writeln!(writer, "element vertex {}", vertices.len())?;
+    for name in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(writer, "property float {name}")?;
+    }
+    for name in ["red", "green", "blue"] {
+        writeln!(writer, "property uchar {name}")?;
+    }
+    writeln!(writer, "property float u")?;
+    writeln!(writer, "property float v")?;
+    writeln!(writer, "element face {}", indices.len() / 3)?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    if !edges.is_empty() {
+        writeln!(writer, "element edge {}", edges.len())?;
+        writeln!(writer, "property uint vertex1")?;
+        writeln!(writer, "property uint vertex2")?;
+    }
+    writeln!(writer, "end_header")?;
+
+    match format {
+        PlyFormat::Ascii => {
+            for v in &vertices {
+                let [x, y, z] = v.position;
+                let [nx, ny, nz] = v.normal;
+                let [r, g, b] = v.color.map(to_u8);
+                let [u, t] = v.uv;
+                writeln!(writer, "{x} {y} {z} {nx} {ny} {nz} {r} {g} {b} {u} {t}")?;
+            }
+            for tri in indices.chunks_exact(3) {
+                writeln!(writer, "3 {} {} {}", tri[0], tri[1], tri[2])?;
+            }
+            for [a, b] in &edges {
+                writeln!(writer, "{a} {b}")?;
+            }
+        }
+        PlyFormat::BinaryLittleEndian => {
+            for v in &vertices {
+                for c in v.position.iter().chain(&v.normal) {
+                    writer.write_all(&c.to_le_bytes())?;
+                }
+                writer.write_all(&v.color.map(to_u8))?;
+                for c in v.uv {
+                    writer.write_all(&c.to_le_bytes())?;
+                }
+            }
+            for tri in indices.chunks_exact(3) {
+                writer.write_all(&[3])?;
+                for i in tri {
+                    writer.write_all(&i.to_le_bytes())?;
+                }
+            }
+            for i in edges.iter().flatten() {
+                writer.write_all(&i.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,2 +1,10 @@
+pub use crate::bounds::*;
+pub use crate::bvh::*;
 pub use crate::common::*;
+pub use crate::error::*;
+pub use crate::material::*;
+pub use crate::normals::*;
+pub use crate::raycast::*;
+pub use crate::simplify::*;
+pub use crate::texture::*;
 pub use crate::traits::*;
@@ -0,0 +1,115 @@
+use glam::{Mat4, Vec3};
+
+use crate::bounds::{Aabb, BoundingSphere};
+
+/// Луч: начало и единичное направление
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// направление нормализуется
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// луч в другой системе координат. Направление не нормализуется, поэтому
+    /// параметр точки на луче в обеих системах один и тот же
+    pub fn transform(&self, matrix: Mat4) -> Self {
+        Self {
+            origin: matrix.transform_point3(self.origin),
+            direction: matrix.transform_vector3(self.direction),
+        }
+    }
+
+    /// расстояние входа в бокс (0, если начало внутри); нет — промах
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let (mut near, mut far) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            let (origin, direction) = (self.origin[axis], self.direction[axis]);
+            let (min, max) = (aabb.min[axis], aabb.max[axis]);
+            if direction == 0.0 {
+                // параллельно слою: либо внутри него целиком, либо мимо
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (min - origin) / direction;
+            let t1 = (max - origin) / direction;
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        (near <= far).then_some(near)
+    }
+
+    /// расстояние до ближайшего пересечения со сферой (0, если начало внутри)
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let offset = self.origin - sphere.center;
+        let a = self.direction.length_squared();
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - sphere.radius * sphere.radius;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 || a == 0.0 {
+            return None;
+        }
+        let far = (-b + discriminant.sqrt()) / a;
+        (far >= 0.0).then(|| ((-b - discriminant.sqrt()) / a).max(0.0))
+    }
+
+    /// пересечение с треугольником с обеих сторон (Möller, Trumbore):
+    /// параметр на луче и барицентрические координаты (u, v) вершин b и c
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<(f32, f32, f32)> {
+        let ab = b - a;
+        let ac = c - a;
+        let p = self.direction.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() <= f32::EPSILON * ab.length_squared().max(ac.length_squared()) {
+            return None;
+        }
+        let inverse = 1.0 / det;
+        let offset = self.origin - a;
+        let u = offset.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = offset.cross(ab);
+        let v = self.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = ac.dot(q) * inverse;
+        (t >= 0.0).then_some((t, u, v))
+    }
+}
+
+/// Попадание луча в треугольник меша
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    /// расстояние вдоль луча
+    pub distance: f32,
+    pub point: Vec3,
+    /// нормаль грани по обходу против часовой стрелки (не интерполированная)
+    pub normal: Vec3,
+    /// веса вершин треугольника: point = w0 * a + w1 * b + w2 * c
+    pub barycentric: [f32; 3],
+    /// номер треугольника в `Mesh::indices` (тройка `3 * triangle ..`)
+    pub triangle: usize,
+}
+
+/// Попадание луча в сцену: индекс объекта и точка на нём
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SceneHit {
+    /// номер объекта в срезе (у `gpu::Scene` — как у `add_object`)
+    pub object: usize,
+    pub hit: RayHit,
+}
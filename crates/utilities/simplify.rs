@@ -0,0 +1,353 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use glam::{DMat3, DVec3, Vec3};
+
+use crate::common::{Mesh, Topology};
+
+// вес плоскостей вдоль открытых краёв: край сдвигается неохотнее, чем поверхность
+const BOUNDARY_WEIGHT: f64 = 10.0;
+
+/// Упростить треугольный меш схлопыванием рёбер по квадрикам ошибки (Garland, Heckbert)
+/// до `target_triangles` треугольников или чуть меньше.
+///
+/// Вершины с разными нормалями и UV в одной точке двигаются вместе, поэтому швы не рвутся;
+/// атрибуты вершин сохраняются, меняются только позиции. Открытые края и схлопывания,
+/// переворачивающие грани, штрафуются. Не-треугольные меши возвращаются без изменений.
+pub fn simplify(mesh: &Mesh, target_triangles: usize) -> Mesh {
+    if mesh.topology != Topology::TriangleList || mesh.indices.len() / 3 <= target_triangles {
+        return mesh.clone();
+    }
+
+    // склейка по позициям
+    let mut ids: HashMap<[u32; 3], usize> = HashMap::new();
+    let mut positions: Vec<DVec3> = Vec::new();
+    let vertex_position: Vec<usize> = mesh
+        .vertices
+        .iter()
+        .map(|v| {
+            *ids.entry(v.position.map(f32::to_bits)).or_insert_with(|| {
+                positions.push(Vec3::from(v.position).as_dvec3());
+                positions.len() - 1
+            })
+        })
+        .collect();
+
+    let corners: Vec<[u32; 3]> = mesh
+        .indices
+        .chunks_exact(3)
+        .map(|tri| [tri[0], tri[1], tri[2]])
+        .collect();
+    let mut triangles: Vec<[usize; 3]> = corners
+        .iter()
+        .map(|tri| tri.map(|i| vertex_position[i as usize]))
+        .collect();
+    let mut removed: Vec<bool> = triangles.iter().map(|&tri| is_degenerate(tri)).collect();
+    let mut live = removed.iter().filter(|&&removed| !removed).count();
+
+    // квадрики плоскостей граней (с весом по площади) и краёв
+    let mut quadrics = vec![Quadric::default(); positions.len()];
+    let mut edge_uses: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    for (t, tri) in triangles.iter().enumerate() {
+        if removed[t] {
+            continue;
+        }
+        let [p0, p1, p2] = tri.map(|p| positions[p]);
+        let normal = (p1 - p0).cross(p2 - p0);
+        let area2 = normal.length();
+        if area2 > 0.0 {
+            let normal = normal / area2;
+            let plane = Quadric::plane(normal, -normal.dot(p0), area2 * 0.5);
+            for &p in tri {
+                quadrics[p] = quadrics[p].add(&plane);
+            }
+        }
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            edge_uses.entry((a.min(b), a.max(b))).or_insert((0, t)).0 += 1;
+        }
+    }
+    // порядок рёбер фиксирован, чтобы результат не зависел от хеширования
+    let mut edges: Vec<(usize, usize)> = edge_uses.keys().copied().collect();
+    edges.sort_unstable();
+    for &(a, b) in &edges {
+        let (uses, t) = edge_uses[&(a, b)];
+        if uses != 1 {
+            continue;
+        }
+        let [p0, p1, p2] = triangles[t].map(|p| positions[p]);
+        let face_normal = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+        let edge = positions[b] - positions[a];
+        let side = edge.cross(face_normal).normalize_or_zero();
+        if side != DVec3::ZERO {
+            let plane = Quadric::plane(
+                side,
+                -side.dot(positions[a]),
+                BOUNDARY_WEIGHT * edge.length_squared(),
+            );
+            quadrics[a] = quadrics[a].add(&plane);
+            quadrics[b] = quadrics[b].add(&plane);
+        }
+    }
+
+    let mut faces: Vec<Vec<usize>> = vec![Vec::new(); positions.len()];
+    for (t, tri) in triangles.iter().enumerate() {
+        if !removed[t] {
+            for &p in tri {
+                faces[p].push(t);
+            }
+        }
+    }
+
+    // схлопывания по возрастанию ошибки; устаревшие узнаются по счётчикам версий
+    let mut merged_into: Vec<usize> = (0..positions.len()).collect();
+    let mut stamps = vec![0u32; positions.len()];
+    let mut heap = BinaryHeap::new();
+    for (a, b) in edges {
+        heap.push(Collapse::new(&positions, &quadrics, &stamps, a, b));
+    }
+
+    while live > target_triangles {
+        let Some(collapse) = heap.pop() else {
+            break;
+        };
+        let (a, b) = (collapse.a, collapse.b);
+        if merged_into[a] != a || merged_into[b] != b || (stamps[a], stamps[b]) != collapse.stamps {
+            continue;
+        }
+        let target = collapse.position;
+        if flips(&triangles, &removed, &positions, &faces[a], a, b, target)
+            || flips(&triangles, &removed, &positions, &faces[b], b, a, target)
+        {
+            continue;
+        }
+
+        // a сливается в b, b переезжает в target
+        positions[b] = target;
+        quadrics[b] = quadrics[b].add(&quadrics[a]);
+        merged_into[a] = b;
+        stamps[a] += 1;
+        stamps[b] += 1;
+        for t in std::mem::take(&mut faces[a]) {
+            if removed[t] {
+                continue;
+            }
+            let tri = &mut triangles[t];
+            if tri.contains(&b) {
+                removed[t] = true;
+                live -= 1;
+            } else {
+                for p in tri.iter_mut().filter(|p| **p == a) {
+                    *p = b;
+                }
+                faces[b].push(t);
+            }
+        }
+        faces[b].retain(|&t| !removed[t]);
+
+        let mut neighbours: Vec<usize> = faces[b]
+            .iter()
+            .flat_map(|&t| triangles[t])
+            .filter(|&p| p != b)
+            .collect();
+        neighbours.sort_unstable();
+        neighbours.dedup();
+        for n in neighbours {
+            heap.push(Collapse::new(&positions, &quadrics, &stamps, b, n));
+        }
+    }
+
+    // оставшиеся треугольники с исходными вершинами на новых позициях
+    let mut remap: Vec<Option<u32>> = vec![None; mesh.vertices.len()];
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(live * 3);
+    for (t, tri) in corners.iter().enumerate() {
+        if removed[t] {
+            continue;
+        }
+        for &i in tri {
+            let index = *remap[i as usize].get_or_insert_with(|| {
+                let mut vertex = mesh.vertices[i as usize];
+                let position = positions[find(&mut merged_into, vertex_position[i as usize])];
+                vertex.position = position.as_vec3().to_array();
+                vertices.push(vertex);
+                vertices.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+/// Цепочка уровней детализации: каждый меш упрощается из предыдущего примерно
+/// в `1 / ratio` раз по числу треугольников. Исходный меш в цепочку не входит;
+/// цепочка короче `levels`, если упрощать дальше нечего.
+pub fn lod_chain(mesh: &Mesh, levels: usize, ratio: f32) -> Vec<Mesh> {
+    let mut chain: Vec<Mesh> = Vec::with_capacity(levels);
+    for _ in 0..levels {
+        let source = chain.last().unwrap_or(mesh);
+        let target = (source.indices.len() / 3) as f32 * ratio.clamp(0.0, 1.0);
+        let simplified = simplify(source, target as usize);
+        if simplified.indices.is_empty() || simplified.indices.len() >= source.indices.len() {
+            break;
+        }
+        chain.push(simplified);
+    }
+    chain
+}
+
+fn is_degenerate([a, b, c]: [usize; 3]) -> bool {
+    a == b || b == c || a == c
+}
+
+fn find(merged_into: &mut [usize], mut p: usize) -> usize {
+    while merged_into[p] != p {
+        merged_into[p] = merged_into[merged_into[p]];
+        p = merged_into[p];
+    }
+    p
+}
+
+/// перевернётся ли (или выродится) какая-то из граней `moved`, когда `moved` переедет
+/// в `target`; грани с обоими концами ребра исчезают и не проверяются
+fn flips(
+    triangles: &[[usize; 3]],
+    removed: &[bool],
+    positions: &[DVec3],
+    faces: &[usize],
+    moved: usize,
+    other: usize,
+    target: DVec3,
+) -> bool {
+    faces.iter().any(|&t| {
+        let tri = triangles[t];
+        if removed[t] || tri.contains(&other) {
+            return false;
+        }
+        let normal = |position: &dyn Fn(usize) -> DVec3| {
+            let [p0, p1, p2] = tri.map(position);
+            (p1 - p0).cross(p2 - p0)
+        };
+        let before = normal(&|p| positions[p]);
+        let after = normal(&|p| if p == moved { target } else { positions[p] });
+        after.length_squared() <= f64::EPSILON * before.length_squared() || after.dot(before) <= 0.0
+    })
+}
+
+/// Симметричная матрица 4x4 квадрики: a², ab, ac, ad, b², bc, bd, c², cd, d²
+#[derive(Copy, Clone, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// квадрат расстояния до плоскости n·p + d = 0, умноженный на вес
+    fn plane(n: DVec3, d: f64, weight: f64) -> Self {
+        let [a, b, c] = n.to_array();
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|x| x * weight),
+        )
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] + other.0[i]))
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        let q = &self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+
+    /// точка наименьшей ошибки; нет, если система вырождена (плоская область, прямой край)
+    fn minimizer(&self) -> Option<DVec3> {
+        let q = &self.0;
+        let matrix = DMat3::from_cols(
+            DVec3::new(q[0], q[1], q[2]),
+            DVec3::new(q[1], q[4], q[5]),
+            DVec3::new(q[2], q[5], q[7]),
+        );
+        let scale = (q[0] + q[4] + q[7]).powi(3);
+        (matrix.determinant().abs() > 1e-9 * scale)
+            .then(|| matrix.inverse() * -DVec3::new(q[3], q[6], q[8]))
+    }
+}
+
+/// Кандидат на схлопывание ребра a -> b; в куче наверху наименьшая ошибка
+struct Collapse {
+    cost: f64,
+    a: usize,
+    b: usize,
+    position: DVec3,
+    stamps: (u32, u32),
+}
+
+impl Collapse {
+    fn new(positions: &[DVec3], quadrics: &[Quadric], stamps: &[u32], a: usize, b: usize) -> Self {
+        let quadric = quadrics[a].add(&quadrics[b]);
+        let (pa, pb) = (positions[a], positions[b]);
+        let midpoint = (pa + pb) * 0.5;
+        // оптимум квадрики — только недалеко от ребра, иначе почти вырожденная система
+        // уводит вершину далеко от поверхности
+        let optimum = quadric
+            .minimizer()
+            .filter(|p| p.distance(midpoint) <= pa.distance(pb));
+        let (cost, position) = [Some(pa), Some(pb), Some(midpoint), optimum]
+            .into_iter()
+            .flatten()
+            .map(|p| (quadric.error(p).max(0.0), p))
+            .min_by(|x, y| x.0.total_cmp(&y.0))
+            .expect("candidates are not empty");
+        Self {
+            cost,
+            a,
+            b,
+            position,
+            stamps: (stamps[a], stamps[b]),
+        }
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| (other.a, other.b).cmp(&(self.a, self.b)))
+    }
+}
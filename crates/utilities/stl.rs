@@ -0,0 +1,220 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use glam::{Mat4, Vec3};
+
+use crate::{
+    common::{Object3D, Topology, Vertex},
+    error::ImportError,
+    traits::Object,
+};
+
+/// Вариант STL при записи
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    #[default]
+    Binary,
+}
+
+/// цвет для STL без цвета
+const DEFAULT_COLOR: [f32; 3] = [0.8, 0.8, 0.8];
+
+/// Загрузить ASCII или бинарный STL (формат определяется автоматически)
+pub fn load_stl(path: impl AsRef<Path>, model_matrix: Mat4) -> Result<Object3D, ImportError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(ImportError::open(path))?;
+    read_stl(BufReader::new(file), model_matrix)
+}
+
+pub fn read_stl(mut reader: impl Read, model_matrix: Mat4) -> Result<Object3D, ImportError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    // бинарный файл тоже может начинаться с "solid", поэтому сначала сверяем размер;
+    // хвост после треугольников (выравнивание некоторых экспортёров) допускается.
+    // У ASCII-файла байты 80..84 — текст, и счётчик из них заведомо больше файла
+    let binary_count = (data.len() >= 84)
+        .then(|| u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize)
+        .filter(|&count| data.len() >= 84 + count * 50);
+    let facets = match binary_count {
+        Some(count) => parse_binary(&data, count),
+        None if data.trim_ascii_start().starts_with(b"solid") => parse_ascii(&data)?,
+        None => return Err(malformed("neither ASCII nor complete binary STL")),
+    };
+
+    if facets.is_empty() {
+        return Err(ImportError::EmptyMesh {
+            mesh: "stl".to_owned(),
+        });
+    }
+
+    let mut vertices = Vec::with_capacity(facets.len() * 3);
+    let mut indices = Vec::with_capacity(facets.len() * 3);
+    for (normal, corners) in facets {
+        if corners.iter().flatten().any(|v| !v.is_finite()) {
+            return Err(ImportError::NonFinite {
+                mesh: "stl".to_owned(),
+            });
+        }
+        // нормаль в файле часто нулевая — считаем по вершинам
+        let [a, b, c] = corners.map(Vec3::from);
+        let normal = Vec3::from(normal)
+            .try_normalize()
+            .unwrap_or_else(|| (b - a).cross(c - a).normalize_or_zero());
+        for position in corners {
+            indices.push(vertices.len() as u32);
+            vertices.push(Vertex {
+                position,
+                normal: normal.to_array(),
+                color: DEFAULT_COLOR,
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(Object3D::new(vertices, indices, model_matrix))
+}
+
+type Facet = ([f32; 3], [[f32; 3]; 3]);
+
+fn parse_binary(data: &[u8], count: usize) -> Vec<Facet> {
+    let read_vec = |chunk: &[u8], offset: usize| -> [f32; 3] {
+        [0, 1, 2].map(|i| {
+            let o = offset + i * 4;
+            f32::from_le_bytes([chunk[o], chunk[o + 1], chunk[o + 2], chunk[o + 3]])
+        })
+    };
+    data[84..84 + count * 50]
+        .chunks_exact(50)
+        .map(|chunk| {
+            (
+                read_vec(chunk, 0),
+                [
+                    read_vec(chunk, 12),
+                    read_vec(chunk, 24),
+                    read_vec(chunk, 36),
+                ],
+            )
+        })
+        .collect()
+}
+
+fn parse_ascii(data: &[u8]) -> Result<Vec<Facet>, ImportError> {
+    let text = std::str::from_utf8(data).map_err(|_| malformed("ASCII STL is not UTF-8"))?;
+    let mut tokens = text.split_whitespace();
+    let mut facets = Vec::new();
+
+    let read_vec = |tokens: &mut std::str::SplitWhitespace| -> Result<[f32; 3], ImportError> {
+        let mut v = [0.0; 3];
+        for c in &mut v {
+            *c = tokens
+                .next()
+                .and_then(|t| t.parse().ok())
+                .ok_or_else(|| malformed("bad number"))?;
+        }
+        Ok(v)
+    };
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "facet" => {
+                if tokens.next() != Some("normal") {
+                    return Err(malformed("expected 'normal' after 'facet'"));
+                }
+                let normal = read_vec(&mut tokens)?;
+                let mut corners = Vec::with_capacity(3);
+                while let Some(token) = tokens.next() {
+                    match token {
+                        "vertex" => corners.push(read_vec(&mut tokens)?),
+                        "endfacet" => break,
+                        _ => {}
+                    }
+                }
+                // многоугольник разбиваем веером
+                for i in 1..corners.len().saturating_sub(1) {
+                    facets.push((normal, [corners[0], corners[i], corners[i + 1]]));
+                }
+            }
+            "endsolid" => break,
+            _ => {}
+        }
+    }
+    Ok(facets)
+}
+
+fn malformed(reason: &str) -> ImportError {
+    ImportError::Malformed {
+        format: "STL",
+        reason: reason.to_owned(),
+    }
+}
+
+/// Сохранить объект в STL; позиции записываются в мировых координатах.
+/// STL хранит только треугольники: для линий и точек — ошибка `ErrorKind::Unsupported`
+/// (их сохраняет `ply::save_ply`)
+pub fn save_stl(
+    path: impl AsRef<Path>,
+    object: &impl Object,
+    format: StlFormat,
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_stl(&mut writer, object, format)?;
+    writer.flush()
+}
+
+/// Записать объект в STL; для линий и точек — ошибка `ErrorKind::Unsupported`, как у `save_stl`
+pub fn write_stl(
+    mut writer: impl Write,
+    object: &impl Object,
+    format: StlFormat,
+) -> std::io::Result<()> {
+    if object.topology() != Topology::TriangleList {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "STL can only store triangles",
+        ));
+    }
+    let vertices = object.world_vertices();
+    let indices = object.world_indices();
+    let facets = indices.chunks_exact(3).map(|tri| {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(vertices[tri[i] as usize].position));
+        ((b - a).cross(c - a).normalize_or_zero(), [a, b, c])
+    });
+
+    match format {
+        StlFormat::Ascii => {
+            writeln!(writer, "solid schwarzschild")?;
+            for (n, corners) in facets {
+                writeln!(writer, "facet normal {:e} {:e} {:e}", n.x, n.y, n.z)?;
+                writeln!(writer, "  outer loop")?;
+                for p in corners {
+                    writeln!(writer, "    vertex {:e} {:e} {:e}", p.x, p.y, p.z)?;
+                }
+                writeln!(writer, "  endloop")?;
+                writeln!(writer, "endfacet")?;
+            }
+            writeln!(writer, "endsolid schwarzschild")?;
+        }
+        StlFormat::Binary => {
+            let mut header = [0u8; 80];
+            let label = b"schwarzschild-engine binary STL";
+            header[..label.len()].copy_from_slice(label);
+            writer.write_all(&header)?;
+            writer.write_all(&((indices.len() / 3) as u32).to_le_bytes())?;
+            for (n, corners) in facets {
+                for v in std::iter::once(n).chain(corners) {
+                    for c in v.to_array() {
+                        writer.write_all(&c.to_le_bytes())?;
+                    }
+                }
+                // attribute byte count
+                writer.write_all(&[0, 0])?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use glam::{
+    Mat4, Vec3,
+    camera::rh::proj::{directx, opengl},
+};
+use utilities::prelude::*;
+
+fn cube() -> Object3D {
+    let vertices = [-1.0f32, 1.0]
+        .iter()
+        .flat_map(|&x| [-1.0f32, 1.0].map(|y| (x, y)))
+        .flat_map(|(x, y)| {
+            [-1.0f32, 1.0].map(|z| Vertex {
+                position: [x, y, z],
+                ..Default::default()
+            })
+        })
+        .collect();
+    Object3D::new(vertices, vec![0, 1, 2], Mat4::IDENTITY)
+}
+
+fn frustum() -> Frustum {
+    // камера в начале координат смотрит вдоль -Z
+    let projection = opengl::perspective(90f32.to_radians(), 1.0, 0.1, 100.0);
+    Frustum::from_view_proj(projection)
+}
+
+#[test]
+fn object_bounds_follow_model_matrix() {
+    let mut object = cube();
+    assert_eq!(object.local_aabb(), Aabb::new(Vec3::splat(-1.0), Vec3::ONE));
+    assert!((object.local_bounding_sphere().radius - 3f32.sqrt()).abs() < 1e-5);
+
+    object.set_model_matrix(
+        Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)) * Mat4::from_scale(Vec3::splat(2.0)),
+    );
+    let aabb = object.world_aabb();
+    assert!((aabb.min - Vec3::new(3.0, -2.0, -2.0)).length() < 1e-5);
+    assert!((aabb.max - Vec3::new(7.0, 2.0, 2.0)).length() < 1e-5);
+    let sphere = object.world_bounding_sphere();
+    assert!((sphere.center - Vec3::new(5.0, 0.0, 0.0)).length() < 1e-5);
+    assert!((sphere.radius - 2.0 * 3f32.sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn rotated_aabb_contains_corners() {
+    let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::ONE);
+    let rotation = Mat4::from_rotation_y(45f32.to_radians());
+    let rotated = aabb.transform(rotation);
+    let half = 2f32.sqrt();
+    assert!((rotated.max - Vec3::new(half, 1.0, half)).length() < 1e-5);
+    assert!(Aabb::EMPTY.transform(rotation).is_empty());
+}
+
+#[test]
+fn frustum_separates_visible_and_hidden() {
+    let frustum = frustum();
+    assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -5.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 5.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -200.0)));
+
+    // за камерой и далеко сбоку — отсекаются, касающиеся края — нет
+    let sphere = |x, z, radius| BoundingSphere::new(Vec3::new(x, 0.0, z), radius);
+    assert!(frustum.intersects_sphere(&sphere(0.0, -10.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 10.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(30.0, -10.0, 1.0)));
+    assert!(frustum.intersects_sphere(&sphere(10.5, -10.0, 1.0)));
+
+    let aabb = |x: f32, z: f32| {
+        Aabb::new(
+            Vec3::new(x - 1.0, -1.0, z - 1.0),
+            Vec3::new(x + 1.0, 1.0, z + 1.0),
+        )
+    };
+    assert!(frustum.intersects_aabb(&aabb(0.0, -10.0)));
+    assert!(!frustum.intersects_aabb(&aabb(0.0, 10.0)));
+    assert!(!frustum.intersects_aabb(&Aabb::EMPTY));
+}
+
+#[test]
+fn zero_to_one_depth_frustum() {
+    let projection = directx::orthographic(-1.0, 1.0, -1.0, 1.0, 0.0, 10.0);
+    let frustum = Frustum::from_view_proj_zero_to_one(projection);
+    assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -0.5)));
+    assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -9.5)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 0.5)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -10.5)));
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+
+use glam::{Mat4, Vec3};
+use utilities::prelude::*;
+
+/// волнистая сетка n x n в плоскости XZ
+fn terrain(n: u32) -> Mesh {
+    let mut vertices = Vec::new();
+    for j in 0..=n {
+        for i in 0..=n {
+            let (x, z) = (i as f32 / n as f32, j as f32 / n as f32);
+            vertices.push(Vertex {
+                position: [x, 0.1 * (x * 7.0).sin() * (z * 5.0).cos(), z],
+                ..Default::default()
+            });
+        }
+    }
+    let mut indices = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            let a = j * (n + 1) + i;
+            let (b, c, d) = (a + 1, a + n + 1, a + n + 2);
+            indices.extend([a, c, d, a, d, b]);
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+fn random(seed: &mut u32) -> f32 {
+    *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+    (*seed >> 8) as f32 / (1 << 24) as f32
+}
+
+fn triangles(mesh: &Mesh) -> impl Iterator<Item = (usize, [Vec3; 3])> + '_ {
+    mesh.indices
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(triangle, tri)| {
+            (
+                triangle,
+                [0, 1, 2].map(|k| Vec3::from(mesh.vertices[tri[k] as usize].position)),
+            )
+        })
+}
+
+#[test]
+fn triangle_overlap_tests() {
+    let aabb = Aabb::new(Vec3::ZERO, Vec3::ONE);
+    // треугольник пересекает бокс, хотя ни одной вершины внутри нет
+    assert!(aabb.intersects_triangle(
+        Vec3::new(-1.0, 0.5, -1.0),
+        Vec3::new(2.0, 0.5, -1.0),
+        Vec3::new(0.5, 0.5, 3.0)
+    ));
+    // бокс треугольника пересекает бокс, а сам треугольник — нет
+    assert!(!aabb.intersects_triangle(
+        Vec3::new(1.5, -1.0, 0.5),
+        Vec3::new(3.0, 2.0, 0.5),
+        Vec3::new(1.2, 2.0, 0.5)
+    ));
+
+    let sphere = BoundingSphere::new(Vec3::new(0.25, 0.25, 0.5), 0.6);
+    assert!(sphere.intersects_triangle(Vec3::ZERO, Vec3::X, Vec3::Y));
+    assert!(!sphere.intersects_triangle(Vec3::X * 2.0, Vec3::X * 3.0, Vec3::new(2.0, 1.0, 0.0)));
+    assert_eq!(
+        closest_point_on_triangle(Vec3::new(2.0, 2.0, 0.0), Vec3::ZERO, Vec3::X, Vec3::Y),
+        Vec3::new(0.5, 0.5, 0.0)
+    );
+    assert!(sphere.intersects_aabb(&aabb));
+    assert!(!BoundingSphere::new(Vec3::splat(2.0), 1.0).intersects_aabb(&aabb));
+}
+
+#[test]
+fn raycast_matches_brute_force() {
+    let mesh = terrain(20);
+    let bvh = MeshBvh::new(&mesh);
+    assert_eq!(bvh.aabb(), mesh.aabb());
+    // детерминированные лучи сверху и сбоку
+    let mut seed = 12345u32;
+    for _ in 0..200 {
+        let origin = Vec3::new(
+            random(&mut seed) * 1.4 - 0.2,
+            1.0,
+            random(&mut seed) * 1.4 - 0.2,
+        );
+        let target = Vec3::new(random(&mut seed), 0.0, random(&mut seed));
+        let ray = Ray::new(origin, target - origin);
+        assert_eq!(
+            bvh.raycast(&mesh, &ray, f32::INFINITY),
+            mesh.raycast(&ray, f32::INFINITY)
+        );
+    }
+}
+
+#[test]
+fn mesh_queries_match_brute_force() {
+    let mesh = terrain(24);
+    let bvh = MeshBvh::new(&mesh);
+    let mut seed = 7u32;
+    for _ in 0..100 {
+        let center = Vec3::new(random(&mut seed), 0.0, random(&mut seed));
+        let radius = 0.02 + random(&mut seed) * 0.2;
+
+        let sphere = BoundingSphere::new(center, radius);
+        let expected: Vec<usize> = triangles(&mesh)
+            .filter(|(_, [a, b, c])| sphere.intersects_triangle(*a, *b, *c))
+            .map(|(triangle, _)| triangle)
+            .collect();
+        assert_eq!(bvh.query_sphere(&mesh, &sphere), expected);
+
+        let aabb = Aabb::new(center - Vec3::splat(radius), center + Vec3::splat(radius));
+        let expected: Vec<usize> = triangles(&mesh)
+            .filter(|(_, [a, b, c])| aabb.intersects_triangle(*a, *b, *c))
+            .map(|(triangle, _)| triangle)
+            .collect();
+        assert_eq!(bvh.query_aabb(&mesh, &aabb), expected);
+    }
+}
+
+#[test]
+fn segment_stops_at_its_end() {
+    let mesh = terrain(8);
+    let bvh = MeshBvh::new(&mesh);
+    let hit = bvh
+        .segment(&mesh, Vec3::new(0.5, 1.0, 0.5), Vec3::new(0.5, -1.0, 0.5))
+        .unwrap();
+    assert!((hit.point.x - 0.5).abs() < 1e-5 && (hit.point.z - 0.5).abs() < 1e-5);
+    // отрезок кончается над поверхностью
+    assert!(
+        bvh.segment(&mesh, Vec3::new(0.5, 1.0, 0.5), Vec3::new(0.5, 0.5, 0.5))
+            .is_none()
+    );
+}
+
+#[test]
+fn refit_follows_deformed_vertices() {
+    let mut mesh = terrain(12);
+    let mut bvh = MeshBvh::new(&mesh);
+    for vertex in &mut mesh.vertices {
+        vertex.position[1] += vertex.position[0] * 2.0;
+    }
+    bvh.refit(&mesh);
+    assert_eq!(bvh.aabb(), mesh.aabb());
+    let rebuilt = MeshBvh::new(&mesh);
+    let mut seed = 99u32;
+    for _ in 0..100 {
+        let origin = Vec3::new(random(&mut seed), 5.0, random(&mut seed));
+        let ray = Ray::new(origin, -Vec3::Y);
+        assert_eq!(
+            bvh.raycast(&mesh, &ray, f32::INFINITY)
+                .map(|hit| hit.triangle),
+            rebuilt
+                .raycast(&mesh, &ray, f32::INFINITY)
+                .map(|hit| hit.triangle)
+        );
+    }
+}
+
+#[test]
+fn objects_sharing_a_mesh_share_its_bvh() {
+    let mesh = Arc::new(terrain(4));
+    let a = Object3D::from_mesh(mesh.clone(), Mat4::IDENTITY);
+    let b = Object3D::from_mesh(mesh.clone(), Mat4::from_translation(Vec3::X));
+    assert!(std::ptr::eq(a.bvh(), b.bvh()));
+
+    // такой же, но отдельный меш получает своё дерево
+    let copy = Object3D::from_mesh(Arc::new(terrain(4)), Mat4::IDENTITY);
+    assert!(!std::ptr::eq(a.bvh(), copy.bvh()));
+    assert_eq!(copy.bvh().aabb(), a.bvh().aabb());
+}
+
+/// сетка объектов с общим мешем, разной высоты и масштаба
+fn scene() -> Vec<Object3D> {
+    let mesh = Arc::new(terrain(6));
+    (0..36)
+        .map(|i| {
+            let (x, z) = ((i % 6) as f32 * 3.0, (i / 6) as f32 * 3.0);
+            let model = Mat4::from_translation(Vec3::new(x, (i % 4) as f32, z))
+                * Mat4::from_scale(Vec3::new(2.0, 1.0 + (i % 3) as f32, 2.0));
+            Object3D::from_mesh(mesh.clone(), model)
+        })
+        .collect()
+}
+
+fn linear_raycast(objects: &[Object3D], ray: &Ray) -> Option<SceneHit> {
+    objects
+        .iter()
+        .enumerate()
+        .filter_map(|(object, obj)| {
+            obj.raycast(ray, f32::INFINITY)
+                .map(|hit| SceneHit { object, hit })
+        })
+        .min_by(|a, b| a.hit.distance.total_cmp(&b.hit.distance))
+}
+
+#[test]
+fn scene_bvh_matches_linear_search() {
+    let mut objects = scene();
+    let mut bvh = SceneBvh::new(&objects);
+    let mut seed = 3u32;
+    let mut check = |objects: &[Object3D], bvh: &SceneBvh| {
+        for _ in 0..100 {
+            let origin = Vec3::new(random(&mut seed) * 20.0, 10.0, random(&mut seed) * 20.0);
+            let target = Vec3::new(random(&mut seed) * 18.0, 0.0, random(&mut seed) * 18.0);
+            let ray = Ray::new(origin, target - origin);
+            let expected = linear_raycast(objects, &ray);
+            let hit = bvh.raycast(objects, &ray, f32::INFINITY);
+            assert_eq!(hit.map(|hit| hit.object), expected.map(|hit| hit.object));
+            assert_eq!(
+                hit.map(|hit| hit.hit.triangle),
+                expected.map(|hit| hit.hit.triangle)
+            );
+        }
+    };
+    check(&objects, &bvh);
+
+    // объекты сдвинулись: дерево подгоняется без перестройки
+    for (i, object) in objects.iter_mut().enumerate() {
+        let model = Mat4::from_translation(Vec3::new(0.0, (i % 5) as f32 * 0.7, 0.5))
+            * object.model_matrix();
+        object.set_model_matrix(model);
+    }
+    bvh.refit(&objects);
+    check(&objects, &bvh);
+}
+
+#[test]
+fn scene_queries_in_world_space() {
+    let objects = scene();
+    let bvh = SceneBvh::new(&objects);
+    let sphere = BoundingSphere::new(Vec3::new(4.0, 1.0, 2.5), 1.5);
+    let aabb = sphere.aabb();
+
+    let mut expected_sphere = Vec::new();
+    let mut expected_aabb = Vec::new();
+    for (object, obj) in objects.iter().enumerate() {
+        for (triangle, vertices) in triangles(obj.mesh()) {
+            let [a, b, c] = vertices.map(|v| obj.model_matrix().transform_point3(v));
+            if sphere.intersects_triangle(a, b, c) {
+                expected_sphere.push((object, triangle));
+            }
+            if aabb.intersects_triangle(a, b, c) {
+                expected_aabb.push((object, triangle));
+            }
+        }
+    }
+    assert!(!expected_sphere.is_empty());
+    assert_eq!(bvh.query_sphere(&objects, &sphere), expected_sphere);
+    assert_eq!(bvh.query_aabb(&objects, &aabb), expected_aabb);
+    assert!(
+        bvh.query_sphere(&objects, &BoundingSphere::new(Vec3::splat(-50.0), 1.0))
+            .is_empty()
+    );
+}
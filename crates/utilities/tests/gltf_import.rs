@@ -0,0 +1,106 @@
+use std::path::{Path, PathBuf};
+
+use glam::{Mat4, Vec3};
+use utilities::{
+    gltf_import::{load_gltf, load_gltf_scene},
+    prelude::*,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/gltf")
+        .join(name)
+}
+
+#[test]
+fn node_hierarchy_and_transforms() {
+    let roots = load_gltf_scene(fixture("scene.gltf")).unwrap();
+    assert_eq!(roots.len(), 1);
+    let root = &roots[0];
+    assert_eq!(root.name.as_deref(), Some("root"));
+    assert_eq!(root.transform, Mat4::from_translation(Vec3::X));
+    assert_eq!(root.objects.len(), 1);
+    assert_eq!(root.children.len(), 1);
+    let child = &root.children[0];
+    assert_eq!(child.name.as_deref(), Some("child"));
+    assert_eq!(
+        child.transform,
+        Mat4::from_translation(Vec3::new(0.0, 2.0, 0.0)) * Mat4::from_scale(Vec3::splat(2.0))
+    );
+
+    // плоский список: матрицы узлов перемножаются от корня, `model_matrix` — поверх них
+    let base = Mat4::from_translation(Vec3::new(0.0, 0.0, -5.0));
+    let objects = load_gltf(fixture("scene.gltf"), base).unwrap();
+    assert_eq!(objects.len(), 2);
+    assert_eq!(objects[0].model_matrix(), base * root.transform);
+    let child_world = objects[1].model_matrix();
+    assert!(child_world.abs_diff_eq(base * root.transform * child.transform, 1e-6));
+    assert_eq!(
+        child_world.transform_point3(Vec3::ONE),
+        Vec3::new(3.0, 4.0, -3.0)
+    );
+}
+
+#[test]
+fn pbr_materials_and_textures() {
+    let objects = load_gltf(fixture("scene.gltf"), Mat4::IDENTITY).unwrap();
+
+    let checker = objects[0].material().unwrap();
+    assert_eq!(checker.name, "checker");
+    assert_eq!(checker.diffuse, [1.0, 0.5, 0.5]);
+    assert_eq!((checker.metallic, checker.roughness), (0.0, 0.9));
+    // внешняя текстура: путь относительно файла, пиксели и сэмплер из glTF
+    assert_eq!(checker.diffuse_texture, Some(fixture("checker.png")));
+    let texture = objects[0].texture().unwrap();
+    assert_eq!((texture.width(), texture.height()), (2, 2));
+    assert_eq!(&texture.data()[4..8], &[0, 0, 0, 255]);
+    assert_eq!(texture.sampler().mag_filter, wgpu::FilterMode::Nearest);
+    assert_eq!(
+        texture.sampler().address_mode_u,
+        wgpu::AddressMode::ClampToEdge
+    );
+    // базовый цвет умножается на цвет вершин
+    for v in objects[0].vertices() {
+        assert_eq!(v.color, [1.0, 0.5, 0.5]);
+    }
+
+    let glow = objects[1].material().unwrap();
+    assert_eq!(glow.opacity, 0.5);
+    assert_eq!(glow.emissive, [1.0, 0.5, 0.0]);
+    // встроенная (data: URI) текстура загружается, но пути у неё нет
+    assert_eq!(glow.diffuse_texture, None);
+    assert_eq!(objects[1].texture().unwrap().width(), 2);
+}
+
+#[test]
+fn triangle_strip_becomes_list() {
+    let objects = load_gltf(fixture("scene.gltf"), Mat4::IDENTITY).unwrap();
+    let strip = &objects[1];
+    assert_eq!(strip.topology(), Topology::TriangleList);
+    assert_eq!(strip.indices().len(), 6);
+    // чередование обхода: оба треугольника смотрят по +Z, как и нормали
+    for tri in strip.indices().chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(strip.vertices()[tri[k] as usize].position));
+        assert!((b - a).cross(c - a).z > 0.0);
+        assert_eq!(strip.vertices()[tri[0] as usize].normal, [0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn non_finite_normals_are_rejected() {
+    assert!(matches!(
+        load_gltf(fixture("non_finite_normals.gltf"), Mat4::IDENTITY),
+        Err(ImportError::NonFinite { mesh }) if mesh == "broken"
+    ));
+}
+
+#[test]
+fn short_attributes_are_rejected() {
+    match load_gltf(fixture("short_normals.gltf"), Mat4::IDENTITY) {
+        Err(ImportError::Malformed { format, reason }) => {
+            assert_eq!(format, "glTF");
+            assert!(reason.contains("'short'") && reason.contains("NORMAL"));
+        }
+        other => panic!("expected Malformed, got {:?}", other.err()),
+    }
+}
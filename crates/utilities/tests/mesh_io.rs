@@ -0,0 +1,315 @@
+use glam::{Mat4, Vec3};
+use utilities::{
+    obj_export::{ObjExportOptions, save_obj},
+    obj_import::load_obj,
+    ply::{PlyFormat, read_ply, write_ply},
+    prelude::*,
+    stl::{StlFormat, read_stl, write_stl},
+};
+
+/// квадрат из двух треугольников с разными цветами и UV
+fn quad() -> Object3D {
+    let vertex = |position: [f32; 3], color: [f32; 3], uv: [f32; 2]| Vertex {
+        position,
+        normal: [0.0, 0.0, 1.0],
+        color,
+        uv,
+        ..Default::default()
+    };
+    Object3D::new(
+        vec![
+            vertex([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0]),
+            vertex([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0]),
+            vertex([1.0, 1.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0]),
+            vertex([0.0, 1.0, 0.0], [1.0, 1.0, 1.0], [0.0, 0.0]),
+        ],
+        vec![0, 1, 2, 0, 2, 3],
+        Mat4::IDENTITY,
+    )
+}
+
+fn triangles(object: &Object3D) -> Vec<[[f32; 3]; 3]> {
+    object
+        .indices()
+        .chunks_exact(3)
+        .map(|tri| [0, 1, 2].map(|i| object.vertices()[tri[i] as usize].position))
+        .collect()
+}
+
+#[test]
+fn stl_round_trip() {
+    let source = quad();
+    for format in [StlFormat::Ascii, StlFormat::Binary] {
+        let mut bytes = Vec::new();
+        write_stl(&mut bytes, &source, format).unwrap();
+        let loaded = read_stl(bytes.as_slice(), Mat4::IDENTITY).unwrap();
+
+        assert_eq!(triangles(&loaded), triangles(&source), "{format:?}");
+        for v in loaded.vertices() {
+            assert_eq!(v.normal, [0.0, 0.0, 1.0]);
+        }
+    }
+}
+
+#[test]
+fn ply_round_trip() {
+    let source = quad();
+    for format in [PlyFormat::Ascii, PlyFormat::BinaryLittleEndian] {
+        let mut bytes = Vec::new();
+        write_ply(&mut bytes, &source, format).unwrap();
+        let loaded = read_ply(bytes.as_slice(), Mat4::IDENTITY).unwrap();
+
+        assert_eq!(loaded.indices(), source.indices(), "{format:?}");
+        for (a, b) in loaded.vertices().iter().zip(source.vertices()) {
+            assert_eq!(a.position, b.position);
+            assert_eq!(a.normal, b.normal);
+            assert_eq!(a.uv, b.uv);
+            assert_eq!(a.color, b.color);
+        }
+    }
+}
+
+#[test]
+fn export_bakes_model_matrix() {
+    let mut source = quad();
+    source.translate(Vec3::new(0.0, 0.0, 5.0));
+    // зеркальный масштаб меняет порядок обхода
+    source.scale(Vec3::new(-1.0, 1.0, 1.0));
+
+    let mut bytes = Vec::new();
+    write_stl(&mut bytes, &source, StlFormat::Binary).unwrap();
+    let loaded = read_stl(bytes.as_slice(), Mat4::IDENTITY).unwrap();
+
+    let first = triangles(&loaded)[0];
+    assert_eq!(first, [[0.0, 0.0, 5.0], [-1.0, 1.0, 5.0], [-1.0, 0.0, 5.0]]);
+    assert_eq!(loaded.vertices()[0].normal, [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn ply_point_cloud_with_colour() {
+    let ply = b"ply
+format ascii 1.0
+element vertex 2
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+end_header
+0 0 0 255 0 0
+1 2 3 0 0 255
+";
+    let loaded = read_ply(&ply[..], Mat4::IDENTITY).unwrap();
+    assert!(loaded.indices().is_empty());
+    assert_eq!(loaded.topology(), Topology::PointList);
+    assert_eq!(loaded.vertices()[1].position, [1.0, 2.0, 3.0]);
+    assert_eq!(loaded.vertices()[0].color, [1.0, 0.0, 0.0]);
+    assert_eq!(loaded.vertices()[1].color, [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn ply_big_endian_quad_is_triangulated() {
+    let mut ply = b"ply
+format binary_big_endian 1.0
+element vertex 4
+property double x
+property double y
+property double z
+element face 1
+property list uchar int vertex_indices
+end_header
+"
+    .to_vec();
+    for p in [
+        [0.0f64, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ] {
+        for c in p {
+            ply.extend_from_slice(&c.to_be_bytes());
+        }
+    }
+    ply.push(4);
+    for i in [0i32, 1, 2, 3] {
+        ply.extend_from_slice(&i.to_be_bytes());
+    }
+
+    let loaded = read_ply(ply.as_slice(), Mat4::IDENTITY).unwrap();
+    assert_eq!(loaded.indices().len(), 6);
+    // нормали сгенерированы, так как в файле их нет
+    for v in loaded.vertices() {
+        assert_eq!(v.normal, [0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn truncated_binary_stl_is_rejected() {
+    let mut bytes = Vec::new();
+    write_stl(&mut bytes, &quad(), StlFormat::Binary).unwrap();
+    bytes.truncate(bytes.len() - 10);
+    assert!(read_stl(bytes.as_slice(), Mat4::IDENTITY).is_err());
+}
+
+#[test]
+fn obj_round_trip_with_material() {
+    let dir = std::env::temp_dir().join(format!("schwarzschild-obj-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("quad.obj");
+
+    let mut source = quad();
+    source.set_material(Some(Material {
+        name: "glass".to_owned(),
+        diffuse: [0.25, 0.5, 1.0],
+        opacity: 0.5,
+        ..Default::default()
+    }));
+    source.translate(Vec3::new(0.0, 1.0, 0.0));
+    let plain = quad();
+
+    save_obj(&path, &[&source, &plain], &ObjExportOptions::default()).unwrap();
+    let loaded = load_obj(&path, Mat4::IDENTITY).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded.len(), 2);
+    let material = loaded[0].material().unwrap();
+    assert_eq!(material.name, "glass");
+    assert_eq!(material.diffuse, [0.25, 0.5, 1.0]);
+    assert_eq!(material.opacity, 0.5);
+    assert_eq!(loaded[0].vertices()[0].position, [0.0, 1.0, 0.0]);
+    // Kd при импорте не накладывается второй раз: цвета вершин те же
+    for (a, b) in loaded[0].vertices().iter().zip(source.vertices()) {
+        for c in 0..3 {
+            assert!(
+                (a.color[c] - b.color[c]).abs() < 1e-6,
+                "{:?} vs {:?}",
+                a.color,
+                b.color
+            );
+        }
+    }
+    assert_eq!(loaded[1].indices(), plain.indices());
+    for (a, b) in loaded[1].vertices().iter().zip(plain.vertices()) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.normal, b.normal);
+        assert_eq!(a.uv, b.uv);
+        assert_eq!(a.color, b.color);
+    }
+}
+
+#[test]
+fn binary_stl_with_padding_and_solid_header() {
+    let mut bytes = Vec::new();
+    write_stl(&mut bytes, &quad(), StlFormat::Binary).unwrap();
+    // некоторые экспортёры пишут в заголовок "solid" и выравнивают файл нулями
+    bytes[..5].copy_from_slice(b"solid");
+    bytes.extend([0; 7]);
+    let loaded = read_stl(bytes.as_slice(), Mat4::IDENTITY).unwrap();
+    assert_eq!(triangles(&loaded), triangles(&quad()));
+}
+
+#[test]
+fn ply_negative_index_is_rejected() {
+    let ply = b"ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+0 1 0
+3 0 -1 2
+";
+    assert!(matches!(
+        read_ply(&ply[..], Mat4::IDENTITY),
+        Err(ImportError::Malformed { format: "PLY", .. })
+    ));
+}
+
+#[test]
+fn ply_index_wider_than_u32_is_rejected() {
+    // 2^32 при обрезке до u32 стал бы нулём — корректной вершиной
+    let ply = b"ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar double vertex_indices
+end_header
+0 0 0
+1 0 0
+0 1 0
+3 4294967296 1 2
+";
+    assert!(matches!(
+        read_ply(&ply[..], Mat4::IDENTITY),
+        Err(ImportError::IndexOutOfRange {
+            vertex_count: 3,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn ply_with_inflated_vertex_count_fails_cleanly() {
+    // заголовок обещает миллиарды вершин, данных — на одну
+    let mut ply = b"ply
+format binary_little_endian 1.0
+element vertex 4000000000
+property float x
+property float y
+property float z
+end_header
+"
+    .to_vec();
+    ply.extend([0; 12]);
+    assert!(matches!(
+        read_ply(ply.as_slice(), Mat4::IDENTITY),
+        Err(ImportError::Malformed { format: "PLY", .. })
+    ));
+}
+
+#[test]
+fn obj_export_keeps_same_named_materials_apart() {
+    let dir = std::env::temp_dir().join(format!("schwarzschild-mtl-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("quads.obj");
+
+    let material = |diffuse| Material {
+        name: "paint".to_owned(),
+        diffuse,
+        ..Default::default()
+    };
+    let mut red = quad();
+    red.set_material(Some(material([1.0, 0.0, 0.0])));
+    let mut blue = quad();
+    blue.set_material(Some(material([0.0, 0.0, 1.0])));
+    // такой же материал, как у первого, — имя общее
+    let mut red_again = quad();
+    red_again.set_material(Some(material([1.0, 0.0, 0.0])));
+
+    save_obj(
+        &path,
+        &[&red, &blue, &red_again],
+        &ObjExportOptions::default(),
+    )
+    .unwrap();
+    let loaded = load_obj(&path, Mat4::IDENTITY).unwrap();
+    let mtl = std::fs::read_to_string(path.with_extension("mtl")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(mtl.matches("newmtl").count(), 2);
+    let names: Vec<&str> = loaded
+        .iter()
+        .map(|object| object.material().unwrap().name.as_str())
+        .collect();
+    assert_eq!(names, ["paint", "paint_2", "paint"]);
+    assert_eq!(loaded[1].material().unwrap().diffuse, [0.0, 0.0, 1.0]);
+}
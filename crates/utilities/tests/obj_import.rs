@@ -0,0 +1,244 @@
+use std::path::{Path, PathBuf};
+
+use glam::{Mat4, Vec3};
+use utilities::{
+    obj_import::{ObjImportOptions, UpAxis, load_obj, load_obj_with, validate_mesh},
+    prelude::*,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/obj")
+        .join(name)
+}
+
+#[test]
+fn missing_material_library_is_reported() {
+    match load_obj(fixture("missing_mtl.obj"), Mat4::IDENTITY) {
+        Err(ImportError::MissingMaterialLibrary { path }) => {
+            assert_eq!(path, fixture("nowhere.mtl"));
+        }
+        other => panic!("expected MissingMaterialLibrary, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn missing_file_keeps_io_error() {
+    match load_obj(fixture("nowhere.obj"), Mat4::IDENTITY) {
+        Err(ImportError::Io {
+            path: Some(path),
+            source,
+        }) => {
+            assert_eq!(path, fixture("nowhere.obj"));
+            assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+        }
+        other => panic!("expected Io, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn diffuse_colour_becomes_vertex_colour() {
+    let objects = load_obj(fixture("materials.obj"), Mat4::IDENTITY).unwrap();
+    assert_eq!(objects.len(), 2);
+
+    let checker = &objects[0];
+    assert_eq!(checker.material().unwrap().name, "checker");
+    for v in checker.vertices() {
+        assert_eq!(v.color, [1.0, 1.0, 1.0]);
+    }
+
+    // цвет вершины из `v x y z r g b` умножается на Kd
+    let tinted = &objects[1];
+    let material = tinted.material().unwrap();
+    assert_eq!(material.name, "red");
+    assert_eq!(material.opacity, 0.5);
+    for v in tinted.vertices() {
+        assert_eq!(v.color, [0.4, 0.2, 0.2]);
+    }
+}
+
+#[test]
+fn texture_paths_resolve_relative_to_obj() {
+    let objects = load_obj(fixture("materials.obj"), Mat4::IDENTITY).unwrap();
+    let checker = &objects[0];
+    assert_eq!(
+        checker.material().unwrap().diffuse_texture,
+        Some(fixture("textures/checker.png"))
+    );
+    let texture = checker.texture().expect("texture is loaded");
+    assert_eq!((texture.width(), texture.height()), (2, 2));
+    assert_eq!(&texture.data()[..8], &[255, 255, 255, 255, 0, 0, 0, 255]);
+    // у меша без UV текстура не назначается
+    assert!(objects[1].texture().is_none());
+}
+
+fn roof(options: &ObjImportOptions) -> Object3D {
+    let mut objects = load_obj_with(fixture("roof.obj"), Mat4::IDENTITY, options).unwrap();
+    assert_eq!(objects.len(), 1);
+    objects.remove(0)
+}
+
+/// нормали вершин в точке `position`
+fn normals_at(object: &Object3D, position: [f32; 3]) -> Vec<Vec3> {
+    object
+        .vertices()
+        .iter()
+        .filter(|v| v.position == position)
+        .map(|v| Vec3::from(v.normal))
+        .collect()
+}
+
+/// нормаль грани по обходу треугольника
+fn face_normals(object: &Object3D) -> Vec<Vec3> {
+    object
+        .indices()
+        .chunks_exact(3)
+        .map(|tri| {
+            let [a, b, c] =
+                [0, 1, 2].map(|k| Vec3::from(object.vertices()[tri[k] as usize].position));
+            (b - a).cross(c - a).normalize()
+        })
+        .collect()
+}
+
+#[test]
+fn missing_normals_are_generated() {
+    // плоские: у вершины на ребре две разные нормали — по одной от каждого ската
+    let flat = roof(&ObjImportOptions {
+        normals: NormalMode::Flat,
+        ..Default::default()
+    });
+    assert_eq!(flat.vertices().len(), 6);
+    let ridge = normals_at(&flat, [0.0, 0.2, 0.0]);
+    assert_eq!(ridge.len(), 2);
+    assert!(ridge[0].z * ridge[1].z < 0.0);
+    for normal in ridge {
+        assert!((normal.length() - 1.0).abs() < 1e-5 && normal.y > 0.9);
+    }
+
+    // сглаженные: угол между скатами меньше порога, вершины ребра общие и смотрят вверх
+    let smooth = roof(&ObjImportOptions::default());
+    assert_eq!(smooth.vertices().len(), 4);
+    let ridge = normals_at(&smooth, [0.0, 0.2, 0.0]);
+    assert_eq!(ridge.len(), 1);
+    assert!((ridge[0] - Vec3::Y).length() < 1e-5);
+
+    // порог меньше угла между скатами — ребро остаётся острым
+    let sharp = roof(&ObjImportOptions {
+        normals: NormalMode::Smooth {
+            angle_threshold: 10f32.to_radians(),
+        },
+        ..Default::default()
+    });
+    assert_eq!(normals_at(&sharp, [0.0, 0.2, 0.0]).len(), 2);
+}
+
+#[test]
+fn z_up_is_converted_to_y_up() {
+    let object = roof(&ObjImportOptions {
+        up_axis: UpAxis::Z,
+        ..Default::default()
+    });
+    // (x, y, z) -> (x, z, -y)
+    let positions: Vec<[f32; 3]> = object.vertices().iter().map(|v| v.position).collect();
+    assert_eq!(positions.len(), 4);
+    for expected in [
+        [0.0, 0.0, -0.2],
+        [1.0, 0.0, -0.2],
+        [0.0, 1.0, 0.0],
+        [0.0, -1.0, 0.0],
+    ] {
+        assert!(
+            positions.contains(&expected),
+            "{expected:?} in {positions:?}"
+        );
+    }
+    // конёк крыши смотрел по +Y, теперь по +Z файла -> -Z
+    assert!((normals_at(&object, [0.0, 0.0, -0.2])[0] + Vec3::Z).length() < 1e-5);
+}
+
+#[test]
+fn winding_can_be_flipped() {
+    let original = roof(&ObjImportOptions::default());
+    let flipped = roof(&ObjImportOptions {
+        flip_winding: true,
+        ..Default::default()
+    });
+    // генерация нормалей переиндексирует вершины, поэтому сравниваем позиции
+    let corners = |object: &Object3D| -> Vec<[[f32; 3]; 3]> {
+        object
+            .indices()
+            .chunks_exact(3)
+            .map(|tri| [0, 1, 2].map(|k| object.vertices()[tri[k] as usize].position))
+            .collect()
+    };
+    for (a, b) in corners(&original).into_iter().zip(corners(&flipped)) {
+        assert_eq!([a[0], a[2], a[1]], b);
+    }
+    // сгенерированные нормали следуют новому обходу
+    for normal in face_normals(&flipped) {
+        assert!(normal.y < 0.0);
+    }
+    assert!(normals_at(&flipped, [0.0, 0.2, 0.0])[0].y < 0.0);
+}
+
+#[test]
+fn scale_is_applied_and_mirroring_keeps_faces_outward() {
+    let doubled = roof(&ObjImportOptions {
+        scale: 2.0,
+        ..Default::default()
+    });
+    assert_eq!(doubled.vertices()[0].position, [0.0, 0.4, 0.0]);
+
+    // квадрат с `vn 0 0 1`: после зеркального масштаба нормаль и обход должны совпадать
+    let options = ObjImportOptions {
+        scale: -1.0,
+        ..Default::default()
+    };
+    let mirrored = load_obj_with(fixture("materials.obj"), Mat4::IDENTITY, &options).unwrap();
+    let quad = &mirrored[0];
+    assert_eq!(quad.vertices()[1].position, [-1.0, 0.0, 0.0]);
+    for (tri, face) in quad.indices().chunks_exact(3).zip(face_normals(quad)) {
+        for &i in tri {
+            let normal = Vec3::from(quad.vertices()[i as usize].normal);
+            assert!(normal.dot(face) > 0.99, "{normal} vs {face}");
+        }
+    }
+}
+
+#[test]
+fn invalid_meshes_are_rejected() {
+    assert!(matches!(
+        load_obj(fixture("non_finite.obj"), Mat4::IDENTITY),
+        Err(ImportError::NonFinite { mesh }) if mesh == "broken"
+    ));
+    assert!(matches!(
+        load_obj(fixture("no_faces.obj"), Mat4::IDENTITY),
+        Err(ImportError::EmptyMesh { mesh }) if mesh == "no_faces"
+    ));
+    // tobj сам проверяет индексы граней файла
+    assert!(matches!(
+        load_obj(fixture("out_of_range.obj"), Mat4::IDENTITY),
+        Err(ImportError::Obj(tobj::LoadError::FaceVertexOutOfBounds))
+    ));
+    let mesh = tobj::Mesh {
+        positions: vec![0.0; 9],
+        indices: vec![0, 1, 3],
+        ..Default::default()
+    };
+    assert!(matches!(
+        validate_mesh("manual", &mesh),
+        Err(ImportError::IndexOutOfRange {
+            index: 3,
+            vertex_count: 3,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn empty_groups_are_skipped() {
+    let objects = load_obj(fixture("empty_group.obj"), Mat4::IDENTITY).unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].indices().len(), 3);
+}
@@ -0,0 +1,103 @@
+use glam::{Mat4, Vec3};
+use utilities::prelude::*;
+
+/// волнистая сетка n x n в плоскости XZ
+fn terrain(n: u32) -> Mesh {
+    let mut vertices = Vec::new();
+    for j in 0..=n {
+        for i in 0..=n {
+            let (x, z) = (i as f32 / n as f32, j as f32 / n as f32);
+            vertices.push(Vertex {
+                position: [x, 0.1 * (x * 7.0).sin() * (z * 5.0).cos(), z],
+                ..Default::default()
+            });
+        }
+    }
+    let mut indices = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            let a = j * (n + 1) + i;
+            let (b, c, d) = (a + 1, a + n + 1, a + n + 2);
+            indices.extend([a, c, d, a, d, b]);
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+#[test]
+fn triangle_hit_has_barycentrics() {
+    let ray = Ray::new(Vec3::new(0.25, 0.25, 5.0), -Vec3::Z);
+    let (t, u, v) = ray
+        .intersect_triangle(Vec3::ZERO, Vec3::X, Vec3::Y)
+        .unwrap();
+    assert!((t - 5.0).abs() < 1e-5);
+    assert!((u - 0.25).abs() < 1e-5 && (v - 0.25).abs() < 1e-5);
+    // мимо и позади начала луча
+    assert!(
+        ray.intersect_triangle(Vec3::X, Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0))
+            .is_none()
+    );
+    let behind = Ray::new(Vec3::new(0.25, 0.25, -1.0), -Vec3::Z);
+    assert!(
+        behind
+            .intersect_triangle(Vec3::ZERO, Vec3::X, Vec3::Y)
+            .is_none()
+    );
+}
+
+#[test]
+fn ray_enters_boxes_and_spheres() {
+    let ray = Ray::new(Vec3::new(-5.0, 0.5, 0.5), Vec3::X);
+    let aabb = Aabb::new(Vec3::ZERO, Vec3::ONE);
+    assert_eq!(ray.intersect_aabb(&aabb), Some(5.0));
+    assert_eq!(
+        Ray::new(Vec3::splat(0.5), Vec3::Y).intersect_aabb(&aabb),
+        Some(0.0)
+    );
+    assert_eq!(
+        Ray::new(Vec3::new(-5.0, 2.0, 0.5), Vec3::X).intersect_aabb(&aabb),
+        None
+    );
+
+    let sphere = BoundingSphere::new(Vec3::new(3.0, 0.5, 0.5), 1.0);
+    assert!((ray.intersect_sphere(&sphere).unwrap() - 7.0).abs() < 1e-5);
+    assert_eq!(
+        Ray::new(Vec3::ZERO, -Vec3::X).intersect_sphere(&sphere),
+        None
+    );
+}
+
+#[test]
+fn mesh_raycast_finds_nearest_triangle() {
+    let mesh = terrain(10);
+    let ray = Ray::new(Vec3::new(0.35, 1.0, 0.55), -Vec3::Y);
+    let hit = mesh.raycast(&ray, f32::INFINITY).unwrap();
+    assert!((hit.point.x - 0.35).abs() < 1e-5 && (hit.point.z - 0.55).abs() < 1e-5);
+    let [w0, w1, w2] = hit.barycentric;
+    assert!((w0 + w1 + w2 - 1.0).abs() < 1e-5);
+    assert!(hit.normal.y > 0.0);
+    // треугольник, в который попали, содержит точку
+    let tri = &mesh.indices[hit.triangle * 3..hit.triangle * 3 + 3];
+    let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(mesh.vertices[tri[k] as usize].position));
+    assert!((a * w0 + b * w1 + c * w2 - hit.point).length() < 1e-5);
+    assert!(mesh.raycast(&ray, 0.5).is_none());
+}
+
+#[test]
+fn object_raycast_in_world_space() {
+    let model = Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0))
+        * Mat4::from_scale(Vec3::new(2.0, 1.0, 2.0));
+    let object = Object3D::from_mesh(terrain(8).into(), model);
+    let ray = Ray::new(Vec3::new(11.0, 5.0, 1.0), -Vec3::Y);
+    let hit = object.raycast(&ray, f32::INFINITY).unwrap();
+    assert!((hit.point.x - 11.0).abs() < 1e-4 && (hit.point.z - 1.0).abs() < 1e-4);
+    assert!((hit.distance - (5.0 - hit.point.y)).abs() < 1e-4);
+    assert!((hit.normal.length() - 1.0).abs() < 1e-4 && hit.normal.y > 0.0);
+    // ограничение по дальности и промах
+    assert!(object.raycast(&ray, 1.0).is_none());
+    assert!(
+        object
+            .raycast(&Ray::new(Vec3::new(0.0, 5.0, 0.0), -Vec3::Y), f32::INFINITY)
+            .is_none()
+    );
+}
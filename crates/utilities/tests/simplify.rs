@@ -0,0 +1,90 @@
+use glam::Vec3;
+use utilities::prelude::*;
+
+/// сетка n x n квадратов в плоскости XY, высота задаётся функцией
+fn grid(n: u32, height: impl Fn(f32, f32) -> f32) -> Mesh {
+    let mut vertices = Vec::new();
+    for j in 0..=n {
+        for i in 0..=n {
+            let (x, y) = (i as f32 / n as f32, j as f32 / n as f32);
+            vertices.push(Vertex {
+                position: [x, y, height(x, y)],
+                normal: [0.0, 0.0, 1.0],
+                uv: [x, y],
+                ..Default::default()
+            });
+        }
+    }
+    let mut indices = Vec::new();
+    for j in 0..n {
+        for i in 0..n {
+            let a = j * (n + 1) + i;
+            let (b, c, d) = (a + 1, a + n + 1, a + n + 2);
+            indices.extend([a, b, d, a, d, c]);
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+fn triangle_normals(mesh: &Mesh) -> impl Iterator<Item = Vec3> + '_ {
+    mesh.indices.chunks_exact(3).map(|tri| {
+        let [a, b, c] = [0, 1, 2].map(|k| Vec3::from(mesh.vertices[tri[k] as usize].position));
+        (b - a).cross(c - a)
+    })
+}
+
+#[test]
+fn flat_grid_keeps_shape() {
+    let mesh = grid(16, |_, _| 0.0);
+    let simplified = simplify(&mesh, 50);
+    let triangles = simplified.indices.len() / 3;
+    assert!(triangles > 0 && triangles <= 50, "{triangles} triangles");
+    // всё в плоскости, грани не перевёрнуты, углы на месте
+    assert!(
+        simplified
+            .vertices
+            .iter()
+            .all(|v| v.position[2].abs() < 1e-6)
+    );
+    assert!(triangle_normals(&simplified).all(|n| n.z > 0.0));
+    assert_eq!(simplified.aabb(), mesh.aabb());
+}
+
+#[test]
+fn curved_surface_stays_close() {
+    let height = |x: f32, y: f32| 0.2 * (x * 3.0).sin() * (y * 3.0).cos();
+    let mesh = grid(24, height);
+    let simplified = simplify(&mesh, 200);
+    assert!(simplified.indices.len() / 3 <= 200);
+    for v in &simplified.vertices {
+        let [x, y, z] = v.position;
+        assert!(
+            (z - height(x, y)).abs() < 0.05,
+            "vertex {:?} left the surface",
+            v.position
+        );
+    }
+    assert!(triangle_normals(&simplified).all(|n| n.z > 0.0));
+}
+
+#[test]
+fn lod_chain_shrinks_each_level() {
+    let mesh = grid(16, |x, y| x * y);
+    let chain = lod_chain(&mesh, 3, 0.5);
+    assert_eq!(chain.len(), 3);
+    let mut previous = mesh.indices.len();
+    for level in &chain {
+        assert!(level.indices.len() <= previous / 2 + 3);
+        previous = level.indices.len();
+    }
+
+    let object = Object3D::from_mesh(mesh.into(), glam::Mat4::IDENTITY).with_generated_lods(2, 0.5);
+    assert_eq!(object.lods().len(), 2);
+    assert!(object.lods()[0].screen_size > object.lods()[1].screen_size);
+}
+
+#[test]
+fn lines_are_not_simplified() {
+    let mesh = grid(2, |_, _| 0.0).with_topology(Topology::LineList);
+    assert_eq!(simplify(&mesh, 1).indices, mesh.indices);
+}
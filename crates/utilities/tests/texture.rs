@@ -0,0 +1,90 @@
+use glam::Vec3;
+use utilities::prelude::*;
+
+#[test]
+fn rgba8_size_is_checked() {
+    let texture = Texture::from_rgba8(2, 3, vec![0; 24]).unwrap();
+    assert_eq!((texture.width(), texture.height()), (2, 3));
+    assert!(texture.is_srgb() && !texture.clone().linear().is_srgb());
+    assert!(matches!(
+        Texture::from_rgba8(2, 3, vec![0; 23]),
+        Err(TextureError::InvalidSize {
+            width: 2,
+            height: 3,
+            len: 23
+        })
+    ));
+    // у каждой текстуры свой id — по нему рендерер кэширует загрузку
+    assert_ne!(Texture::solid([0; 4]).id(), Texture::solid([0; 4]).id());
+}
+
+#[test]
+fn mip_chain_halves_down_to_one_pixel() {
+    let texture = Texture::from_rgba8(8, 3, vec![200; 8 * 3 * 4]).unwrap();
+    let sizes: Vec<(u32, u32)> = texture
+        .mip_chain()
+        .iter()
+        .map(|(w, h, data)| {
+            assert_eq!(data.len(), (w * h * 4) as usize);
+            (*w, *h)
+        })
+        .collect();
+    assert_eq!(sizes, [(8, 3), (4, 1), (2, 1), (1, 1)]);
+    assert_eq!(Texture::solid([1, 2, 3, 4]).mip_chain().len(), 1);
+}
+
+#[test]
+fn mip_chain_averages_2x2_blocks() {
+    // шахматка 2x2 из чёрного и белого усредняется в серый
+    let mut data = Vec::new();
+    for value in [0u8, 255, 255, 0] {
+        data.extend([value, value, value, 255]);
+    }
+    let texture = Texture::from_rgba8(2, 2, data).unwrap();
+    let chain = texture.mip_chain();
+    assert_eq!(chain[1], (1, 1, vec![127, 127, 127, 255]));
+}
+
+#[test]
+fn tangents_are_orthogonal_to_normals() {
+    // наклонный квадрат с UV, повёрнутыми относительно рёбер
+    let positions = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.5],
+        [1.0, 1.0, 0.5],
+        [0.0, 1.0, 0.0],
+    ];
+    let uvs = [[0.2, 0.0], [1.0, 0.3], [0.8, 1.0], [0.0, 0.7]];
+    let normal = (Vec3::new(1.0, 0.0, 0.5).cross(Vec3::Y)).normalize();
+    let mut vertices: Vec<Vertex> = positions
+        .iter()
+        .zip(uvs)
+        .map(|(&position, uv)| Vertex {
+            position,
+            normal: normal.to_array(),
+            uv,
+            ..Default::default()
+        })
+        .collect();
+    let indices = [0, 1, 2, 0, 2, 3];
+    Vertex::compute_tangents(&mut vertices, &indices);
+    for vertex in &vertices {
+        let tangent = Vec3::new(vertex.tangent[0], vertex.tangent[1], vertex.tangent[2]);
+        assert!((tangent.length() - 1.0).abs() < 1e-5);
+        assert!(tangent.dot(normal).abs() < 1e-5);
+        assert_eq!(vertex.tangent[3].abs(), 1.0);
+    }
+
+    // зеркальные UV меняют знак битангента
+    let mut mirrored = vertices.clone();
+    for vertex in &mut mirrored {
+        vertex.uv[0] = 1.0 - vertex.uv[0];
+    }
+    Vertex::compute_tangents(&mut mirrored, &indices);
+    assert!(
+        vertices
+            .iter()
+            .zip(&mirrored)
+            .all(|(a, b)| a.tangent[3] == -b.tangent[3])
+    );
+}
@@ -0,0 +1,215 @@
+use std::{
+    fmt,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+static NEXT_TEXTURE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Ошибка загрузки текстуры
+#[derive(Debug)]
+pub enum TextureError {
+    Io(std::io::Error),
+    Decode(image::ImageError),
+    /// размер данных не совпадает с width * height * 4
+    InvalidSize {
+        width: u32,
+        height: u32,
+        len: usize,
+    },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(e) => write!(f, "texture io error: {e}"),
+            TextureError::Decode(e) => write!(f, "texture decode error: {e}"),
+            TextureError::InvalidSize { width, height, len } => write!(
+                f,
+                "texture data has {len} bytes, expected {} for {width}x{height} RGBA8",
+                *width as usize * *height as usize * 4
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureError::Io(e) => Some(e),
+            TextureError::Decode(e) => Some(e),
+            TextureError::InvalidSize { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TextureError {
+    fn from(e: std::io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+impl From<image::ImageError> for TextureError {
+    fn from(e: image::ImageError) -> Self {
+        TextureError::Decode(e)
+    }
+}
+
+/// Параметры сэмплера: фильтрация и адресация
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SamplerDesc {
+    pub mag_filter: wgpu::FilterMode,
+    pub min_filter: wgpu::FilterMode,
+    pub mipmap_filter: wgpu::FilterMode,
+    pub address_mode_u: wgpu::AddressMode,
+    pub address_mode_v: wgpu::AddressMode,
+    pub address_mode_w: wgpu::AddressMode,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self::linear(wgpu::AddressMode::Repeat)
+    }
+}
+
+impl SamplerDesc {
+    /// трилинейная фильтрация с одним режимом адресации по всем осям
+    pub fn linear(address_mode: wgpu::AddressMode) -> Self {
+        Self {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+        }
+    }
+
+    /// без фильтрации (пиксель-арт, lookup-таблицы)
+    pub fn nearest(address_mode: wgpu::AddressMode) -> Self {
+        Self {
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+        }
+    }
+}
+
+/// Текстура на CPU: RGBA8 пиксели + настройки сэмплера.
+/// Рендерер загружает её на GPU один раз и кэширует по `id`.
+#[derive(Clone, Debug)]
+pub struct Texture {
+    id: u64,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+    srgb: bool,
+    sampler: SamplerDesc,
+}
+
+impl Texture {
+    /// загрузить PNG/JPEG с диска
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TextureError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// декодировать PNG/JPEG из памяти (формат определяется по сигнатуре)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TextureError> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Self::from_rgba8(width, height, image.into_raw())
+    }
+
+    /// создать из сырых RGBA8 пикселей
+    pub fn from_rgba8(width: u32, height: u32, data: Vec<u8>) -> Result<Self, TextureError> {
+        if data.len() != width as usize * height as usize * 4 {
+            return Err(TextureError::InvalidSize {
+                width,
+                height,
+                len: data.len(),
+            });
+        }
+        Ok(Self {
+            id: NEXT_TEXTURE_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            data,
+            srgb: true,
+            sampler: SamplerDesc::default(),
+        })
+    }
+
+    /// одноцветная текстура 1x1
+    pub fn solid(color: [u8; 4]) -> Self {
+        Self::from_rgba8(1, 1, color.to_vec()).expect("1x1 texture")
+    }
+
+    /// данные в линейном пространстве (normal map, roughness и т.п.)
+    pub fn linear(mut self) -> Self {
+        self.srgb = false;
+        self
+    }
+
+    pub fn with_sampler(mut self, sampler: SamplerDesc) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn is_srgb(&self) -> bool {
+        self.srgb
+    }
+
+    pub fn sampler(&self) -> &SamplerDesc {
+        &self.sampler
+    }
+
+    /// Цепочка мип-уровней (уровень 0 — исходное изображение),
+    /// каждый следующий — box-фильтр 2x2 предыдущего
+    pub fn mip_chain(&self) -> Vec<(u32, u32, Vec<u8>)> {
+        let mut levels = vec![(self.width, self.height, self.data.clone())];
+        while let Some((w, h, prev)) = levels.last() {
+            if *w == 1 && *h == 1 {
+                break;
+            }
+            let (w, h) = (*w, *h);
+            let nw = (w / 2).max(1);
+            let nh = (h / 2).max(1);
+            let mut next = vec![0u8; (nw * nh * 4) as usize];
+            for y in 0..nh {
+                for x in 0..nw {
+                    for c in 0..4 {
+                        let mut sum = 0u32;
+                        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                            let sx = (x * 2 + dx).min(w - 1);
+                            let sy = (y * 2 + dy).min(h - 1);
+                            sum += prev[((sy * w + sx) * 4 + c) as usize] as u32;
+                        }
+                        next[((y * nw + x) * 4 + c) as usize] = (sum / 4) as u8;
+                    }
+                }
+            }
+            levels.push((nw, nh, next));
+        }
+        levels
+    }
+}
@@ -1,24 +1,138 @@
-use glam::{Mat4, Vec3};
+use std::sync::Arc;
 
-use crate::common::{Object3D, Vertex};
+use glam::{Mat3, Mat4, Vec3};
 
-/// Трейт объекта сцены
+use crate::{
+    bounds::{Aabb, BoundingSphere},
+    common::{Mesh, Object3D, Topology, Vertex},
+    material::Material,
+};
+
+/// Трейт объекта сцены: общая геометрия (`Mesh`) плюс собственная трансформация.
+/// Реализации задают только доступ к мешу и матрице, остальное — методы по умолчанию.
 pub trait Object {
-    /// вершины в локальных координатах
-    fn vertices(&self) -> &[Vertex];
-    /// индексы (триангулированные, u16)
-    fn indices(&self) -> &[u32];
+    /// геометрия объекта; один `Arc<Mesh>` может быть у нескольких объектов
+    fn mesh(&self) -> &Arc<Mesh>;
     /// модельная матрица (локальная -> мировая)
     fn model_matrix(&self) -> Mat4;
 
-    fn to_object3d(self) -> Object3D;
+    fn set_model_matrix(&mut self, model_matrix: Mat4);
+
+    /// вершины в локальных координатах
+    fn vertices(&self) -> &[Vertex] {
+        &self.mesh().vertices
+    }
+
+    /// индексы (триангулированные)
+    fn indices(&self) -> &[u32] {
+        &self.mesh().indices
+    }
+
+    /// как собирать индексы в примитивы
+    fn topology(&self) -> Topology {
+        self.mesh().topology
+    }
+
+    /// толщина линий / размер точек в пикселях (для треугольников не используется)
+    fn line_width(&self) -> f32 {
+        1.0
+    }
+
+    /// отбрасывает ли объект тень
+    fn cast_shadows(&self) -> bool {
+        true
+    }
+
+    /// затеняется ли объект другими
+    fn receive_shadows(&self) -> bool {
+        true
+    }
 
-    fn translate(&mut self, offset: Vec3); /*{
-    self.model_matrix = Mat4::from_translation(offset) * self.model_matrix;
-    } */
+    /// непрозрачность: 1.0 — непрозрачный, меньше — рисуется в проходе прозрачных объектов
+    fn opacity(&self) -> f32 {
+        self.material().map_or(1.0, |material| material.opacity)
+    }
+
+    /// объект сцены с той же геометрией; вершины не копируются
+    fn to_object3d(self) -> Object3D
+    where
+        Self: Sized,
+    {
+        let object = Object3D::from_mesh(self.mesh().clone(), self.model_matrix())
+            .with_line_width(self.line_width())
+            .with_shadows(self.cast_shadows(), self.receive_shadows());
+        if self.opacity() < 1.0 {
+            object.with_opacity(self.opacity())
+        } else {
+            object
+        }
+    }
+
+    /// бокс геометрии в локальных координатах
+    fn local_aabb(&self) -> Aabb {
+        self.mesh().aabb()
+    }
+
+    /// сфера вокруг геометрии в локальных координатах
+    fn local_bounding_sphere(&self) -> BoundingSphere {
+        self.mesh().bounding_sphere()
+    }
+
+    /// бокс в мировых координатах (для отсечения и выделения)
+    fn world_aabb(&self) -> Aabb {
+        self.local_aabb().transform(self.model_matrix())
+    }
+
+    /// сфера в мировых координатах
+    fn world_bounding_sphere(&self) -> BoundingSphere {
+        self.local_bounding_sphere().transform(self.model_matrix())
+    }
+
+    /// материал объекта (для экспорта); процедурная геометрия обходится цветом вершин
+    fn material(&self) -> Option<&Material> {
+        None
+    }
+
+    /// сдвинуть объект в мировых координатах
+    fn translate(&mut self, offset: Vec3) {
+        self.set_model_matrix(Mat4::from_translation(offset) * self.model_matrix());
+    }
 
     /// масштабировать объект
-    fn scale(&mut self, factor: Vec3); /*{
-    self.model_matrix = Mat4::from_scale(factor) * self.model_matrix;
-    }*/
+    fn scale(&mut self, factor: Vec3) {
+        self.set_model_matrix(Mat4::from_scale(factor) * self.model_matrix());
+    }
+
+    /// вершины в мировых координатах (model_matrix "запечена" в позиции и нормали)
+    fn world_vertices(&self) -> Vec<Vertex> {
+        let model = self.model_matrix();
+        let normal_matrix = Mat3::from_mat4(model).inverse().transpose();
+        self.vertices()
+            .iter()
+            .map(|v| {
+                let position = model.transform_point3(Vec3::from(v.position));
+                let normal = (normal_matrix * Vec3::from(v.normal)).normalize_or_zero();
+                let tangent =
+                    Mat3::from_mat4(model) * Vec3::new(v.tangent[0], v.tangent[1], v.tangent[2]);
+                let tangent = tangent.normalize_or_zero();
+                Vertex {
+                    position: position.to_array(),
+                    normal: normal.to_array(),
+                    tangent: [tangent.x, tangent.y, tangent.z, v.tangent[3]],
+                    ..*v
+                }
+            })
+            .collect()
+    }
+
+    /// индексы для `world_vertices`: зеркальная model_matrix меняет порядок обхода треугольников
+    fn world_indices(&self) -> Vec<u32> {
+        let mut indices = self.indices().to_vec();
+        if self.topology() == Topology::TriangleList && self.model_matrix().determinant() < 0.0 {
+            for tri in indices.chunks_exact_mut(3) {
+                tri.swap(1, 2);
+            }
+        }
+        indices
+    }
 }